@@ -0,0 +1,89 @@
+//! Registers every `src/days/day_XX.rs` module: the generated `days.rs` declares the modules and
+//! lists them in `DAYS` with the title found in the `--- Day N: Title ---` doc header. Adding a day
+//! is only a matter of adding its file.
+
+use std::fmt::Write;
+use std::path::Path;
+
+fn main() {
+    let days_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("days");
+    println!("cargo:rerun-if-changed={}", days_dir.display());
+
+    let mut days: Vec<(usize, String, String)> = Vec::new();
+
+    for entry in std::fs::read_dir(&days_dir).expect("src/days should be readable") {
+        let path = entry.expect("src/days should be readable").path();
+        let Some(module) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|stem| stem.starts_with("day_"))
+        else {
+            continue;
+        };
+
+        let day: usize = module["day_".len()..]
+            .parse()
+            .unwrap_or_else(|_| panic!("{module}: expected day_<number>.rs"));
+        let source = std::fs::read_to_string(&path).expect("day module should be readable");
+        let title = parse_title(&source, day)
+            .unwrap_or_else(|| panic!("{module}: expected a `/// --- Day {day}: <title> ---` doc"));
+
+        days.push((day, module.to_string(), title));
+    }
+
+    days.sort();
+
+    let mut registry = String::from("// Generated by build.rs from the day_XX.rs files\n");
+    for (_, module, _) in &days {
+        let path = days_dir.join(format!("{module}.rs"));
+        writeln!(
+            registry,
+            "#[path = {:?}]\npub mod {module};",
+            path.display().to_string()
+        )
+        .unwrap();
+    }
+
+    registry += "\n/// Every day found in `src/days`, in order\npub const DAYS: &[DayEntry] = &[\n";
+    for (day, module, title) in &days {
+        let solution = format!("{module}::Day{day:02}");
+        writeln!(
+            registry,
+            "    DayEntry {{ day: {day}, title: {title:?}, parts: <{solution} as Solution>::PARTS, \
+             params: <{solution} as Solution>::PARAMS, solver: &{solution} }},"
+        )
+        .unwrap();
+    }
+    registry += "];\n\n";
+
+    for (day, module, _) in &days {
+        let solution = format!("{module}::Day{day:02}");
+        writeln!(
+            registry,
+            "const _: () = assert!(<{solution} as Solution>::DAY == {day}, \"{module}.rs declares \
+             another day\");"
+        )
+        .unwrap();
+        writeln!(
+            registry,
+            "const _: () = assert!(matches!(<{solution} as Solution>::PARTS, 1 | 2), \"{module}.rs \
+             declares neither 1 nor 2 parts\");"
+        )
+        .unwrap();
+    }
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    std::fs::write(Path::new(&out_dir).join("days.rs"), registry).expect("OUT_DIR is writable");
+}
+
+/// `Title` from the `/// --- Day N: Title ---` header of the puzzle statement
+fn parse_title(source: &str, day: usize) -> Option<String> {
+    let prefix = format!("/// --- Day {day}: ");
+
+    source.lines().find_map(|line| {
+        let title = line.strip_prefix(&prefix)?.strip_suffix(" ---")?;
+        Some(title.to_string())
+    })
+}
@@ -0,0 +1,141 @@
+use crate::report::{self, DayError};
+use aoc_2022::days::bench::{Baseline, Stats, STEPS};
+use aoc_2022::days::{DayEntry, InputSource, PartOutcome, RunOptions};
+use std::time::{Duration, Instant};
+
+/// Time after which a day is not run again, so that the slow days do not take ages to bench
+const DAY_TIME_BUDGET: Duration = Duration::from_secs(5);
+
+const HEADERS: [&str; 7] = [
+    "Day", "Step", "Median", "Min", "Std dev", "Runs", "Baseline",
+];
+
+/// Times of a day over several runs
+pub struct BenchRow {
+    pub day: usize,
+    /// Parse, part 1 and part 2, or why a step has no times, e.g. `unimplemented`
+    pub steps: Result<[Result<Stats, &'static str>; 3], DayError>,
+}
+
+/// Run each day up to `runs` times, one day after the other so that they do not disturb each
+/// other's timings
+pub fn bench_days(
+    entries: &[(&DayEntry, RunOptions)],
+    input_source: &InputSource,
+    runs: usize,
+) -> Vec<BenchRow> {
+    entries
+        .iter()
+        .map(|(entry, options)| BenchRow {
+            day: entry.day,
+            steps: bench_day(entry, options, input_source, runs),
+        })
+        .collect()
+}
+
+fn bench_day(
+    entry: &DayEntry,
+    options: &RunOptions,
+    input_source: &InputSource,
+    runs: usize,
+) -> Result<[Result<Stats, &'static str>; 3], DayError> {
+    let data = input_source.load(entry.day).map_err(DayError::Input)?;
+
+    let mut samples: [Vec<Duration>; 3] = Default::default();
+    // Why the parts have no times, from the last run
+    let mut statuses = ["-"; 3];
+    let start = Instant::now();
+
+    for _ in 0..runs {
+        let report = entry.solver.run(&data, options).map_err(DayError::Parse)?;
+
+        samples[0].push(report.parse_time);
+        for (part_idx, part) in report.parts.iter().enumerate() {
+            match &part.outcome {
+                PartOutcome::Solved(_) => samples[part_idx + 1].push(part.time),
+                PartOutcome::Skipped => statuses[part_idx + 1] = "-",
+                PartOutcome::Unimplemented => statuses[part_idx + 1] = "unimplemented",
+                PartOutcome::Failed(_) => statuses[part_idx + 1] = "failed",
+            }
+        }
+
+        if start.elapsed() > DAY_TIME_BUDGET {
+            break;
+        }
+    }
+
+    Ok([0, 1, 2].map(|step_idx| Stats::new(&samples[step_idx]).ok_or(statuses[step_idx])))
+}
+
+/// Table of the times of each step, with the change of the median since the baseline when there is
+/// one, and the errors listed below it
+pub fn print_bench(rows: &[BenchRow], baseline: Option<&Baseline>) {
+    let mut lines = vec![HEADERS.map(String::from).to_vec()];
+    let mut errors = Vec::new();
+
+    for row in rows {
+        let steps = match &row.steps {
+            Ok(steps) => steps,
+            Err(err) => {
+                errors.push(format!("Day {:02}: {err}", row.day));
+                continue;
+            }
+        };
+
+        for (step, stats) in STEPS.iter().zip(steps) {
+            let mut line = vec![format!("{:02}", row.day), step.replace('_', " ")];
+            match stats {
+                Ok(stats) => line.extend([
+                    report::format_duration(stats.median),
+                    report::format_duration(stats.min),
+                    report::format_duration(stats.std_dev),
+                    stats.runs.to_string(),
+                    format_change(stats, baseline.and_then(|b| b.get(row.day, step))),
+                ]),
+                Err(status) => line.extend([status, "-", "-", "-", "-"].map(String::from)),
+            }
+            lines.push(line);
+        }
+    }
+
+    if baseline.is_none() {
+        for line in &mut lines {
+            line.pop();
+        }
+    }
+    report::print_aligned(&lines, 2, false);
+
+    if !errors.is_empty() {
+        println!();
+        for error in errors {
+            println!("{error}");
+        }
+    }
+}
+
+/// Change of the median compared to the baseline one, in percent
+fn format_change(stats: &Stats, baseline: Option<Duration>) -> String {
+    match baseline {
+        Some(baseline) if !baseline.is_zero() => {
+            let ratio = stats.median.as_secs_f64() / baseline.as_secs_f64();
+            format!("{:+.1}%", (ratio - 1.0) * 100.0)
+        }
+        _ => "-".to_string(),
+    }
+}
+
+/// Baseline updated with the medians of the days that ran, the other days keep their times
+pub fn update_baseline(baseline: &mut Baseline, rows: &[BenchRow]) {
+    for row in rows {
+        let Ok(steps) = &row.steps else {
+            continue;
+        };
+
+        baseline.clear_day(row.day);
+        for (step, stats) in STEPS.iter().zip(steps) {
+            if let Ok(stats) = stats {
+                baseline.insert(row.day, step, stats.median);
+            }
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use aoc_2022::days::utils::default_data_dir;
+use aoc_2022::days::{answers, bench, params, InputSource, PartSelection};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: aoc_2022 <day | first-last | all> [options]
+       aoc_2022 list
+       aoc_2022 bench [day | first-last | all] [options]
+       aoc_2022 generate <day> [--seed <n>] [--size <n>] [--param <k=v>]
+
+Running a range of days or all of them prints a timing table instead of the answers, list prints
+the days that have a solution with their title and parameters. bench runs the days, all of them
+by default, several times and prints the median, min and standard deviation of each step, compared
+with the baseline when there is one. generate prints a random input for the day, e.g. to pipe it
+into aoc_2022 <day> -.
+
+Options:
+  --input <path>     Read the puzzle input from <path>, use - to read from stdin
+  -                  Same as --input -
+  --data-dir <dir>   Read day_XX.txt from <dir>, defaults to $AOC_DATA_DIR or ./data
+  --check            Compare the answers with the recorded ones instead of printing them
+  --answers <path>   Recorded answers for --check, defaults to answers.toml next to the data dir
+  --format <format>  text, the default, or json for one JSON object per line and day
+  --part <1|2>       Only solve the given part
+  --param <k=v>      Set the puzzle parameter k of the selected days, see list for the parameters
+  --config <path>    Puzzle parameters of each day, defaults to aoc.toml next to the data dir
+  -v, -vv            Print what the days are doing on stderr, -vv for every step
+  --trace <days>     Only print it for some days, e.g. 11,20-23, implies -v
+  -j, --jobs <n>     Run up to <n> days at the same time, defaults to 1
+  --runs <n>         Number of runs of each day for bench, defaults to 10, slow days stop after
+                     about 5 seconds
+  --baseline <path>  Times to compare with for bench, defaults to bench.toml next to the data dir
+  --save-baseline    Record the medians of bench in the baseline file
+  --seed <n>         Seed of the input made up by generate, defaults to 0
+  --size <n>         Size of the input made up by generate, what it counts depends on the day,
+                     defaults to 100
+  -h, --help         Print this message";
+
+/// Runs of each day for `bench` without `--runs`
+const DEFAULT_BENCH_RUNS: usize = 10;
+
+/// Size of the inputs made up by `generate` without `--size`
+const DEFAULT_GENERATE_SIZE: usize = 100;
+
+/// What to do with the selected days
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Command {
+    /// Solve them
+    #[default]
+    Run,
+    /// Print the days that have a solution instead
+    List,
+    /// Solve them several times for their timings
+    Bench,
+    /// Print a random input for the day
+    Generate,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Expected text or json as format, got: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaySelection {
+    Single(usize),
+    Range(RangeInclusive<usize>),
+    All,
+}
+
+impl std::str::FromStr for DaySelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_day = |day: &str| -> Result<usize, String> {
+            day.trim()
+                .parse()
+                .map_err(|_| format!("Expected a day number, got: {day}"))
+        };
+
+        if s == "all" {
+            return Ok(DaySelection::All);
+        }
+
+        if let Some((first, last)) = s.split_once('-') {
+            let (first, last) = (parse_day(first)?, parse_day(last)?);
+            if first > last {
+                return Err(format!("Empty day range: {s}"));
+            }
+            return Ok(DaySelection::Range(first..=last));
+        }
+
+        Ok(DaySelection::Single(parse_day(s)?))
+    }
+}
+
+impl DaySelection {
+    pub fn contains(&self, day: usize) -> bool {
+        match self {
+            DaySelection::Single(selected) => *selected == day,
+            DaySelection::Range(range) => range.contains(&day),
+            DaySelection::All => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Args {
+    pub days: DaySelection,
+    pub input: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub check: bool,
+    pub answers: Option<PathBuf>,
+    pub format: Format,
+    pub parts: PartSelection,
+    /// `--param` values, in the order given
+    pub params: Vec<(String, i64)>,
+    pub config: Option<PathBuf>,
+    /// Number of `v` given with `-v` or `-vv`
+    pub verbosity: u8,
+    /// Days to trace, all of them when empty
+    pub trace_days: Vec<usize>,
+    /// Number of days run at the same time
+    pub jobs: usize,
+    pub help: bool,
+    pub command: Command,
+    /// `--runs`, the most runs of each day for `bench`
+    pub runs: usize,
+    pub baseline: Option<PathBuf>,
+    pub save_baseline: bool,
+    pub seed: u64,
+    /// `--size` of the input to `generate`
+    pub size: usize,
+}
+
+impl Args {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut days = None;
+        let mut input = None;
+        let mut data_dir = None;
+        let mut check = false;
+        let mut answers = None;
+        let mut format = Format::default();
+        let mut jobs = 1;
+        let mut parts = PartSelection::Both;
+        let mut params = Vec::new();
+        let mut config = None;
+        let mut verbosity = 0;
+        let mut trace_days = Vec::new();
+        let mut help = false;
+        let mut command = Command::default();
+        let mut runs = DEFAULT_BENCH_RUNS;
+        let mut baseline = None;
+        let mut save_baseline = false;
+        let mut seed = None;
+        let mut size = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => help = true,
+                "-" => input = Some(PathBuf::from("-")),
+                "--input" => input = Some(expect_value(&mut args, &arg)?.into()),
+                "--data-dir" => data_dir = Some(expect_value(&mut args, &arg)?.into()),
+                "--check" => check = true,
+                "--answers" => answers = Some(expect_value(&mut args, &arg)?.into()),
+                "--format" => format = expect_value(&mut args, &arg)?.parse()?,
+                "--part" => {
+                    let value = expect_value(&mut args, &arg)?;
+                    parts = match value.as_str() {
+                        "1" => PartSelection::Only(1),
+                        "2" => PartSelection::Only(2),
+                        _ => return Err(format!("Expected 1 or 2 as part, got: {value}")),
+                    };
+                }
+                "--param" => {
+                    let value = expect_value(&mut args, &arg)?;
+                    let (name, param_value) = value.split_once('=').ok_or_else(|| {
+                        format!("Expected name=value after --param, got: {value}")
+                    })?;
+                    let param_value = param_value.replace('_', "").parse().map_err(|_| {
+                        format!("Expected a number as value of {name}, got: {param_value}")
+                    })?;
+                    params.push((name.to_string(), param_value));
+                }
+                "-v" => verbosity += 1,
+                "-vv" => verbosity += 2,
+                "--trace" => {
+                    for days in expect_value(&mut args, &arg)?.split(',') {
+                        match days.parse()? {
+                            DaySelection::Single(day) => trace_days.push(day),
+                            DaySelection::Range(range) => trace_days.extend(range),
+                            DaySelection::All => trace_days.clear(),
+                        }
+                    }
+                    verbosity = verbosity.max(1);
+                }
+                "--config" => config = Some(expect_value(&mut args, &arg)?.into()),
+                "-j" | "--jobs" => {
+                    let value = expect_value(&mut args, &arg)?;
+                    jobs = value.parse().ok().filter(|jobs| *jobs > 0).ok_or_else(|| {
+                        format!("Expected a positive number of jobs, got: {value}")
+                    })?;
+                }
+                "--runs" => {
+                    let value = expect_value(&mut args, &arg)?;
+                    runs = value.parse().ok().filter(|runs| *runs > 0).ok_or_else(|| {
+                        format!("Expected a positive number of runs, got: {value}")
+                    })?;
+                }
+                "--baseline" => baseline = Some(expect_value(&mut args, &arg)?.into()),
+                "--save-baseline" => save_baseline = true,
+                "--seed" => {
+                    let value = expect_value(&mut args, &arg)?;
+                    seed = Some(value.parse().map_err(|_| {
+                        format!("Expected a positive number as seed, got: {value}")
+                    })?);
+                }
+                "--size" => {
+                    let value = expect_value(&mut args, &arg)?;
+                    size = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|size| *size > 0)
+                            .ok_or_else(|| format!("Expected a positive size, got: {value}"))?,
+                    );
+                }
+                _ if arg.starts_with('-') => return Err(format!("Unknown option: {arg}")),
+                "list" | "bench" | "generate" if days.is_none() && command == Command::Run => {
+                    command = match arg.as_str() {
+                        "list" => Command::List,
+                        "bench" => Command::Bench,
+                        _ => Command::Generate,
+                    };
+                }
+                _ if command == Command::List => return Err(format!("Unexpected argument: {arg}")),
+                _ if days.is_none() => days = Some(arg.parse()?),
+                _ => return Err(format!("Unexpected argument: {arg}")),
+            }
+        }
+
+        let days = match days {
+            Some(days) => days,
+            // Whatever, the usage gets printed
+            None if help || matches!(command, Command::List | Command::Bench) => DaySelection::All,
+            None => return Err("Expected the number of the day as first argument".to_string()),
+        };
+
+        if input.is_some() && !matches!(days, DaySelection::Single(_)) {
+            return Err("--input can only be used when running a single day".to_string());
+        }
+
+        let bench = command == Command::Bench;
+        if bench && (check || format != Format::Text || jobs != 1) {
+            return Err("bench cannot be combined with --check, --format or --jobs".to_string());
+        }
+        if !bench && (baseline.is_some() || save_baseline) {
+            return Err("--baseline and --save-baseline are options of bench".to_string());
+        }
+
+        let generate = command == Command::Generate;
+        if generate && !matches!(days, DaySelection::Single(_)) {
+            return Err("generate makes up the input of a single day".to_string());
+        }
+        if generate && (input.is_some() || check || format != Format::Text) {
+            return Err(
+                "generate cannot be combined with --input, --check or --format".to_string(),
+            );
+        }
+        if !generate && (seed.is_some() || size.is_some()) {
+            return Err("--seed and --size are options of generate".to_string());
+        }
+
+        Ok(Args {
+            days,
+            input,
+            data_dir,
+            check,
+            answers,
+            format,
+            parts,
+            params,
+            config,
+            verbosity,
+            trace_days,
+            jobs,
+            help,
+            command,
+            runs,
+            baseline,
+            save_baseline,
+            seed: seed.unwrap_or_default(),
+            size: size.unwrap_or(DEFAULT_GENERATE_SIZE),
+        })
+    }
+
+    pub fn input_source(&self) -> InputSource {
+        match (&self.input, &self.data_dir) {
+            (Some(path), _) if path.as_os_str() == "-" => InputSource::Stdin,
+            (Some(path), _) => InputSource::File(path.clone()),
+            (None, Some(data_dir)) => InputSource::DataDir(data_dir.clone()),
+            (None, None) => InputSource::default(),
+        }
+    }
+
+    /// `--config`, or `aoc.toml` next to the data dir if there is one
+    pub fn config_path(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.config {
+            return Some(path.clone());
+        }
+
+        let data_dir = self.data_dir.clone().unwrap_or_else(default_data_dir);
+        Some(params::config_path(&data_dir)).filter(|path| path.is_file())
+    }
+
+    /// `--baseline`, or `bench.toml` next to the data dir
+    pub fn baseline_path(&self) -> PathBuf {
+        if let Some(path) = &self.baseline {
+            return path.clone();
+        }
+
+        let data_dir = self.data_dir.clone().unwrap_or_else(default_data_dir);
+        bench::baseline_path(&data_dir)
+    }
+
+    pub fn answers_path(&self) -> PathBuf {
+        if let Some(path) = &self.answers {
+            return path.clone();
+        }
+
+        let data_dir = self.data_dir.clone().unwrap_or_else(default_data_dir);
+        answers::answers_path(&data_dir)
+    }
+}
+
+fn expect_value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("Expected a value after {option}"))
+}
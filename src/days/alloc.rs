@@ -0,0 +1,141 @@
+//! Allocation accounting of each step of a day. With the `alloc-stats` feature the global
+//! allocator counts, for the current thread, the allocations, the bytes they requested and the
+//! bytes live at the peak, so that days run on other threads with `--jobs` do not get mixed up.
+//! Without the feature nothing is counted and [`measure`] gives no stats.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Allocations made while running a step
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// A reallocation counts as one more allocation
+    pub allocations: u64,
+    /// Sum of the sizes requested, of the new size for a reallocation
+    pub bytes: u64,
+    /// Most bytes live at once that were allocated by the step, memory freed by the step but
+    /// allocated before it does not lower it
+    pub peak_bytes: u64,
+}
+
+/// System allocator keeping count of what each thread allocates
+pub struct CountingAllocator;
+
+struct Counters {
+    allocations: Cell<u64>,
+    bytes: Cell<u64>,
+    /// Signed as a thread can free memory allocated by another one
+    live: Cell<i64>,
+    peak: Cell<i64>,
+}
+
+thread_local! {
+    // Const initialized and without destructor, so that accessing it never allocates
+    static COUNTERS: Counters = const {
+        Counters {
+            allocations: Cell::new(0),
+            bytes: Cell::new(0),
+            live: Cell::new(0),
+            peak: Cell::new(0),
+        }
+    };
+}
+
+fn record(allocated: usize, freed: usize) {
+    // The counters are gone while the thread is being torn down, nothing to measure then
+    let _ = COUNTERS.try_with(|counters| {
+        if allocated != 0 {
+            counters.allocations.set(counters.allocations.get() + 1);
+            counters.bytes.set(counters.bytes.get() + allocated as u64);
+        }
+        let live = counters.live.get() + allocated as i64 - freed as i64;
+        counters.live.set(live);
+        counters.peak.set(counters.peak.get().max(live));
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(0, layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size, layout.size());
+        }
+        new_ptr
+    }
+}
+
+/// Whether the counting allocator is the global one
+pub const fn enabled() -> bool {
+    cfg!(feature = "alloc-stats")
+}
+
+/// Run `f`, along with what it allocated on this thread when the allocations are counted
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    if !enabled() {
+        return (f(), None);
+    }
+
+    let (allocations, bytes, live) = COUNTERS.with(|counters| {
+        counters.peak.set(counters.live.get());
+        (
+            counters.allocations.get(),
+            counters.bytes.get(),
+            counters.live.get(),
+        )
+    });
+
+    let res = f();
+
+    let stats = COUNTERS.with(|counters| AllocStats {
+        allocations: counters.allocations.get() - allocations,
+        bytes: counters.bytes.get() - bytes,
+        peak_bytes: (counters.peak.get() - live).max(0) as u64,
+    });
+    (res, Some(stats))
+}
+
+#[cfg(all(test, feature = "alloc-stats"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_allocations() {
+        let (_, stats) = measure(|| {
+            let big = vec![0u8; 1000];
+            drop(big);
+            let mut small: Vec<u8> = Vec::with_capacity(10);
+            small.reserve_exact(90);
+            small
+        });
+
+        assert_eq!(
+            stats,
+            Some(AllocStats {
+                allocations: 3,
+                bytes: 1100,
+                peak_bytes: 1000,
+            })
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Known good answers, recorded in a small subset of TOML:
+//!
+//! ```toml
+//! [day_05]
+//! part_1 = "TLNGFGMFN"
+//! part_2 = 42
+//!
+//! [day_10]
+//! part_2 = """
+//! ###..###..
+//! """
+//! ```
+//!
+//! Numbers become [`Answer::Number`] and strings [`Answer::Text`], the multi-line strings follow
+//! TOML and drop the newline right after the opening quotes.
+
+use super::toml::{self, TomlError, Value};
+use super::{Answer, PartOutcome};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the answers file, it sits next to the `data` directory
+pub const ANSWERS_FILE_NAME: &str = "answers.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KnownAnswers {
+    answers: BTreeMap<(usize, usize), Answer>,
+}
+
+/// Outcome of comparing a part against the recorded answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail {
+        expected: Answer,
+        got: PartOutcome,
+    },
+    /// Nothing recorded for this part yet
+    Missing,
+}
+
+/// `answers.toml` in the directory holding `data_dir`
+pub fn answers_path(data_dir: &Path) -> PathBuf {
+    data_dir
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(ANSWERS_FILE_NAME)
+}
+
+impl KnownAnswers {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|err| format!("could not read {}: {err}", path.display()))?;
+        data.parse()
+            .map_err(|err| format!("{}, {err}", path.display()))
+    }
+
+    pub fn get(&self, day: usize, part: usize) -> Option<&Answer> {
+        self.answers.get(&(day, part))
+    }
+
+    pub fn insert(&mut self, day: usize, part: usize, answer: Answer) {
+        self.answers.insert((day, part), answer);
+    }
+
+    /// `None` when there is nothing to check: a part that was skipped, or no answer recorded and
+    /// none expected, i.e. the second part of day 25
+    pub fn check(&self, day: usize, part: usize, outcome: &PartOutcome) -> Option<CheckStatus> {
+        match (self.get(day, part), outcome) {
+            (_, PartOutcome::Skipped) => None,
+            (None, PartOutcome::Solved(Answer::Empty)) => None,
+            (None, _) => Some(CheckStatus::Missing),
+            (Some(expected), PartOutcome::Solved(answer)) if expected == answer => {
+                Some(CheckStatus::Pass)
+            }
+            (Some(expected), got) => Some(CheckStatus::Fail {
+                expected: expected.clone(),
+                got: got.clone(),
+            }),
+        }
+    }
+}
+
+impl std::str::FromStr for KnownAnswers {
+    type Err = TomlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut known_answers = KnownAnswers::default();
+
+        for entry in toml::parse(s)? {
+            let day = toml::parse_suffix(&entry.table, "day_").map_err(|err| entry.error(err))?;
+            let part = toml::parse_suffix(&entry.key, "part_").map_err(|err| entry.error(err))?;
+
+            let answer = match entry.value {
+                Value::Integer(number) => Answer::Number(number),
+                Value::String(text) => Answer::Text(text),
+            };
+            known_answers.insert(day, part, answer);
+        }
+
+        Ok(known_answers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_answers() {
+        let known_answers: KnownAnswers = "\
+# Comment
+[day_05]
+part_1 = \"TLNGFGMFN\" # Trailing comment
+part_2 = 1_000
+
+[day_10]
+part_2 = \"\"\"
+#..#
+.##.
+\"\"\"
+"
+        .parse()
+        .unwrap();
+
+        assert_eq!(known_answers.get(5, 1), Some(&Answer::from("TLNGFGMFN")));
+        assert_eq!(known_answers.get(5, 2), Some(&Answer::Number(1000)));
+        assert_eq!(known_answers.get(10, 1), None);
+        assert_eq!(
+            known_answers.get(10, 2),
+            Some(&Answer::from("#..#\n.##.\n"))
+        );
+    }
+
+    #[test]
+    fn parse_error_line() {
+        let err = "[day_01]\npart_1 = 1\npart_2 = oops\n"
+            .parse::<KnownAnswers>()
+            .unwrap_err();
+
+        assert_eq!(err.line, 3);
+    }
+
+    #[test]
+    fn check() {
+        let mut known_answers = KnownAnswers::default();
+        known_answers.insert(25, 1, Answer::from("2=-1=0"));
+
+        let solved = |answer: &str| PartOutcome::Solved(Answer::from(answer));
+        assert_eq!(
+            known_answers.check(25, 1, &solved("2=-1=0")),
+            Some(CheckStatus::Pass)
+        );
+        assert!(matches!(
+            known_answers.check(25, 1, &solved("2=-1=1")),
+            Some(CheckStatus::Fail { .. })
+        ));
+        assert_eq!(
+            known_answers.check(24, 1, &solved("18")),
+            Some(CheckStatus::Missing)
+        );
+        assert_eq!(
+            known_answers.check(25, 2, &PartOutcome::Solved(Answer::Empty)),
+            None
+        );
+        assert_eq!(known_answers.check(25, 1, &PartOutcome::Skipped), None);
+    }
+}
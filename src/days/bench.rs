@@ -0,0 +1,205 @@
+//! Timing statistics of repeated runs, and the baseline they are compared against. The baseline
+//! records the median time of each step of each day, in nanoseconds, in the TOML subset of the
+//! answers file:
+//!
+//! ```toml
+//! [day_15]
+//! parse = 41_250
+//! part_1 = 152_811_904
+//! part_2 = 86_514_007
+//! ```
+
+use super::toml::{self, TomlError, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Name of the baseline file, it sits next to the `data` directory
+pub const BASELINE_FILE_NAME: &str = "bench.toml";
+
+/// Steps of a day, as named in the baseline file
+pub const STEPS: [&str; 3] = ["parse", "part_1", "part_2"];
+
+/// Summary of the times of a step over several runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: usize,
+    pub median: Duration,
+    pub min: Duration,
+    /// Standard deviation of the sample, zero for a single run
+    pub std_dev: Duration,
+}
+
+impl Stats {
+    /// `None` without any sample
+    pub fn new(samples: &[Duration]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let runs = sorted.len();
+        let min = *sorted.first()?;
+        let median = match runs % 2 {
+            1 => sorted[runs / 2],
+            _ => (sorted[runs / 2 - 1] + sorted[runs / 2]) / 2,
+        };
+
+        let mean = sorted.iter().map(Duration::as_secs_f64).sum::<f64>() / runs as f64;
+        let variance = sorted
+            .iter()
+            .map(|sample| (sample.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / (runs.max(2) - 1) as f64;
+
+        Some(Stats {
+            runs,
+            median,
+            min,
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+/// Median time of each step of the days benchmarked so far
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline {
+    medians: BTreeMap<(usize, &'static str), Duration>,
+}
+
+/// `bench.toml` in the directory holding `data_dir`
+pub fn baseline_path(data_dir: &Path) -> PathBuf {
+    data_dir
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(BASELINE_FILE_NAME)
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|err| format!("could not read {}: {err}", path.display()))?;
+        data.parse()
+            .map_err(|err| format!("{}, {err}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_string())
+            .map_err(|err| format!("could not write {}: {err}", path.display()))
+    }
+
+    /// `step` being one of [`STEPS`]
+    pub fn get(&self, day: usize, step: &str) -> Option<Duration> {
+        let step = STEPS.iter().find(|name| **name == step)?;
+        self.medians.get(&(day, *step)).copied()
+    }
+
+    /// Panics when `step` is not one of [`STEPS`]
+    pub fn insert(&mut self, day: usize, step: &str, median: Duration) {
+        let step = STEPS
+            .iter()
+            .find(|name| **name == step)
+            .unwrap_or_else(|| panic!("Unknown step {step}"));
+        self.medians.insert((day, step), median);
+    }
+
+    /// Forget the times of `day`, so that the steps that did not run this time are not kept
+    pub fn clear_day(&mut self, day: usize) {
+        self.medians
+            .retain(|(baseline_day, _), _| *baseline_day != day);
+    }
+}
+
+impl std::str::FromStr for Baseline {
+    type Err = TomlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut baseline = Baseline::default();
+
+        for entry in toml::parse(s)? {
+            let day = toml::parse_suffix(&entry.table, "day_").map_err(|err| entry.error(err))?;
+            if !STEPS.contains(&entry.key.as_str()) {
+                let err = format!("Expected {}, got: {}", STEPS.join(", "), entry.key);
+                return Err(entry.error(err));
+            }
+            let nanos = match entry.value {
+                Value::Integer(nanos) => u64::try_from(nanos).ok(),
+                Value::String(_) => None,
+            };
+            let nanos = nanos.ok_or_else(|| {
+                entry.error(format!(
+                    "Expected a number of nanoseconds for {}",
+                    entry.key
+                ))
+            })?;
+
+            baseline.insert(day, &entry.key, Duration::from_nanos(nanos));
+        }
+
+        Ok(baseline)
+    }
+}
+
+impl std::fmt::Display for Baseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "# Median times in nanoseconds, written by `aoc_2022 bench --save-baseline`"
+        )?;
+
+        let mut current_day = None;
+        for (&(day, step), median) in &self.medians {
+            if current_day != Some(day) {
+                writeln!(f, "\n[day_{day:02}]")?;
+                current_day = Some(day);
+            }
+            writeln!(f, "{step} = {}", median.as_nanos())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let samples = [4, 2, 9, 5, 4, 5, 7, 4].map(Duration::from_millis);
+        let stats = Stats::new(&samples).unwrap();
+
+        assert_eq!(stats.runs, 8);
+        assert_eq!(stats.min, Duration::from_millis(2));
+        assert_eq!(
+            stats.median,
+            Duration::from_millis(4) + Duration::from_micros(500)
+        );
+        // Mean of 5 and squared deviations adding up to 32, over 7
+        assert!((2137..=2138).contains(&stats.std_dev.as_micros()));
+
+        let single = Stats::new(&[Duration::from_millis(3)]).unwrap();
+        assert_eq!(
+            (single.median, single.std_dev),
+            (Duration::from_millis(3), Duration::ZERO)
+        );
+        assert_eq!(Stats::new(&[]), None);
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let mut baseline = Baseline::default();
+        baseline.insert(15, "part_2", Duration::from_nanos(86_514_007));
+        baseline.insert(7, "parse", Duration::from_nanos(1_250));
+        baseline.insert(15, "parse", Duration::from_nanos(41_250));
+
+        let saved = baseline.to_string();
+        assert!(saved.contains("\n[day_07]\nparse = 1250\n\n[day_15]\nparse = 41250\npart_2"));
+        assert_eq!(saved.parse::<Baseline>().unwrap(), baseline);
+
+        baseline.clear_day(15);
+        assert_eq!(baseline.get(15, "parse"), None);
+        assert_eq!(baseline.get(7, "parse"), Some(Duration::from_nanos(1_250)));
+
+        let err = "[day_01]\npart_3 = 12\n".parse::<Baseline>().unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+}
@@ -1,4 +1,6 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, Solution};
 
 /// --- Day 1: Calorie Counting ---
 /// Santa's reindeer typically eat regular reindeer food, but they need a lot of magical energy to
@@ -67,40 +69,97 @@ use super::load_file;
 ///
 /// Find the top three Elves carrying the most Calories. How many Calories are those Elves carrying
 /// in total?
-pub fn day_01() {
-    let data = load_file(1);
-    let data_per_elf = data.split("\n\n");
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: usize = 1;
 
     // Max heap once I got to part 2, before was storing max value as I went
-    let mut max_heap = std::collections::BinaryHeap::new();
+    type Input = std::collections::BinaryHeap<u64>;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        let data_per_elf = data.trim_end().split("\n\n");
+
+        let mut max_heap = std::collections::BinaryHeap::new();
 
-    for elf_data in data_per_elf {
-        let elf_data_as_lines = elf_data.split('\n');
+        for elf_data in data_per_elf {
+            let elf_data_as_lines = elf_data.split('\n');
 
-        let mut current_calories = 0u64;
+            let mut current_calories = 0u64;
 
-        for line in elf_data_as_lines {
-            let value: Result<u64, _> = line.parse();
-            match value {
-                Ok(value) => current_calories += value,
-                _ => {
-                    break;
-                }
+            for line in elf_data_as_lines {
+                current_calories += parse::number::<u64>(data, line)?;
             }
+            max_heap.push(current_calories);
         }
-        max_heap.push(current_calories);
+
+        Ok(max_heap)
     }
 
-    // We have a max heap, let's actually use it
+    fn part_1(input: &Self::Input) -> Answer {
+        let max_calories = *input.peek().unwrap();
+
+        max_calories.into()
+    }
 
-    let max_calories = max_heap.pop().unwrap();
+    fn part_2(input: &Self::Input) -> Answer {
+        // We have a max heap, let's actually use it
+        let mut max_heap = input.clone();
 
-    let mut sum_max_3 = max_calories;
+        let mut sum_max_3 = 0;
 
-    for _ in 0..2 {
-        sum_max_3 += max_heap.pop().unwrap()
+        for _ in 0..3 {
+            sum_max_3 += max_heap.pop().unwrap()
+        }
+
+        sum_max_3.into()
     }
 
-    println!("Part 1: {max_calories}");
-    println!("Part 2: {sum_max_3}");
+    // `size` elves
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let elves: Vec<String> = (0..size)
+            .map(|_| {
+                let items = rng.range(1..=15);
+                (0..items)
+                    .map(|_| format!("{}\n", rng.range(1000..=60000)))
+                    .collect()
+            })
+            .collect();
+
+        Some(elves.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day01::parse(EXAMPLE).unwrap();
+        assert_eq!(Day01::part_1(&input), Answer::Number(24000));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day01::parse(EXAMPLE).unwrap();
+        assert_eq!(Day01::part_2(&input), Answer::Number(45000));
+    }
 }
@@ -1,4 +1,6 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, Solution};
 
 /// --- Day 2: Rock Paper Scissors ---
 /// The Elves begin to set up camp on the beach. To decide whose tent gets to be closest to the
@@ -58,75 +60,123 @@ use super::load_file;
 ///
 /// Following the Elf's instructions for the second column, what would your total score be if
 /// everything goes exactly according to your strategy guide?
-pub fn day_02() {
-    let data = load_file(2);
-    let data_as_lines = data.split('\n');
+pub struct Day02;
 
-    let mut acc_part_1 = 0u64;
-    let mut acc_part_2 = 0u64;
+impl Solution for Day02 {
+    const DAY: usize = 2;
 
-    data_as_lines.into_iter().for_each(|val| {
-        if let Some((elf_move, my_move)) = val.trim().split_once(' ') {
-            match (elf_move, my_move) {
-                // Rock vs rock
-                // Rock, lose
-                ("A", "X") => {
-                    acc_part_1 += 1 + 3;
-                    acc_part_2 += 3; // + 0;
+    type Input = Vec<(String, String)>;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        data.lines()
+            .map(|line| {
+                let (elf_move, my_move) = parse::split_once(data, line.trim(), " ")?;
+
+                if !["A", "B", "C"].contains(&elf_move) {
+                    return Err(ParseError::at(data, elf_move, "A, B or C"));
                 }
-                // Rock vs paper
-                // Rock, draw
-                ("A", "Y") => {
-                    acc_part_1 += 2 + 6;
-                    acc_part_2 += 1 + 3;
+                if !["X", "Y", "Z"].contains(&my_move) {
+                    return Err(ParseError::at(data, my_move, "X, Y or Z"));
                 }
+
+                Ok((elf_move.to_string(), my_move.to_string()))
+            })
+            .collect()
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        let mut acc_part_1 = 0u64;
+
+        for (elf_move, my_move) in input {
+            match (elf_move.as_str(), my_move.as_str()) {
+                // Rock vs rock
+                ("A", "X") => acc_part_1 += 1 + 3,
+                // Rock vs paper
+                ("A", "Y") => acc_part_1 += 2 + 6,
                 // Rock vs scissors
-                // Rock, win
-                ("A", "Z") => {
-                    acc_part_1 += 3; // + 0;
-                    acc_part_2 += 2 + 6;
-                }
+                ("A", "Z") => acc_part_1 += 3, // + 0;
                 // Paper vs rock
-                // Paper, lose
-                ("B", "X") => {
-                    acc_part_1 += 1; // + 0;
-                    acc_part_2 += 1; // + 0;
-                }
+                ("B", "X") => acc_part_1 += 1, // + 0;
                 // Paper vs paper
-                // Paper, draw
-                ("B", "Y") => {
-                    acc_part_1 += 2 + 3;
-                    acc_part_2 += 2 + 3;
-                }
+                ("B", "Y") => acc_part_1 += 2 + 3,
                 // Paper vs scissors
-                // Paper, win
-                ("B", "Z") => {
-                    acc_part_1 += 3 + 6;
-                    acc_part_2 += 3 + 6;
-                }
+                ("B", "Z") => acc_part_1 += 3 + 6,
                 // Scissors vs rock
-                // Scissors, lose
-                ("C", "X") => {
-                    acc_part_1 += 1 + 6;
-                    acc_part_2 += 2; // + 0;
-                }
+                ("C", "X") => acc_part_1 += 1 + 6,
                 // Scissors vs paper
-                // Scissors, draw
-                ("C", "Y") => {
-                    acc_part_1 += 2; // + 0;
-                    acc_part_2 += 3 + 3;
-                }
+                ("C", "Y") => acc_part_1 += 2, // + 0;
                 // Scissors vs scissors
+                ("C", "Z") => acc_part_1 += 3 + 3,
+                (_, _) => unreachable!(),
+            }
+        }
+
+        acc_part_1.into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        let mut acc_part_2 = 0u64;
+
+        for (elf_move, my_move) in input {
+            match (elf_move.as_str(), my_move.as_str()) {
+                // Rock, lose
+                ("A", "X") => acc_part_2 += 3, // + 0;
+                // Rock, draw
+                ("A", "Y") => acc_part_2 += 1 + 3,
+                // Rock, win
+                ("A", "Z") => acc_part_2 += 2 + 6,
+                // Paper, lose
+                ("B", "X") => acc_part_2 += 1, // + 0;
+                // Paper, draw
+                ("B", "Y") => acc_part_2 += 2 + 3,
+                // Paper, win
+                ("B", "Z") => acc_part_2 += 3 + 6,
+                // Scissors, lose
+                ("C", "X") => acc_part_2 += 2, // + 0;
+                // Scissors, draw
+                ("C", "Y") => acc_part_2 += 3 + 3,
                 // Scissors, win
-                ("C", "Z") => {
-                    acc_part_1 += 3 + 3;
-                    acc_part_2 += 1 + 6;
-                }
+                ("C", "Z") => acc_part_2 += 1 + 6,
                 (_, _) => unreachable!(),
             }
         }
-    });
 
-    println!("Part 1: {acc_part_1}");
-    println!("Part 2: {acc_part_2}");
+        acc_part_2.into()
+    }
+
+    // `size` rounds
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let rounds = (0..size)
+            .map(|_| {
+                let elf_move = rng.choose(&["A", "B", "C"]);
+                let my_move = rng.choose(&["X", "Y", "Z"]);
+                format!("{elf_move} {my_move}\n")
+            })
+            .collect();
+
+        Some(rounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+A Y
+B X
+C Z
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day02::parse(EXAMPLE).unwrap();
+        assert_eq!(Day02::part_1(&input), Answer::Number(15));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day02::parse(EXAMPLE).unwrap();
+        assert_eq!(Day02::part_2(&input), Answer::Number(12));
+    }
 }
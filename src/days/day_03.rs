@@ -1,4 +1,5 @@
-use super::load_file;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, Solution};
 
 /// --- Day 3: Rucksack Reorganization ---
 /// One Elf has the important job of loading all of the rucksacks with supplies for the jungle
@@ -80,59 +81,147 @@ use super::load_file;
 ///
 /// Find the item type that corresponds to the badges of each three-Elf group. What is the sum of
 /// the priorities of those item types?
-pub fn day_03() {
-    let data = load_file(3);
+pub struct Day03;
 
-    let data_as_lines = data.split('\n');
+pub fn char_priority(c: char) -> u64 {
+    let mut priority = 0;
+    if c.is_ascii_uppercase() {
+        priority += 26;
+    }
+    priority += (c.to_lowercase().next().unwrap() as u64) - 96;
+    priority
+}
 
-    let mut priorities_part_1 = 0u64;
-    let mut priorities_part_2 = 0u64;
+impl Solution for Day03 {
+    const DAY: usize = 3;
 
-    let mut group_hash_set = std::collections::HashSet::new();
+    type Input = Vec<String>;
 
-    let mut group_idx = 0;
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        data.lines()
+            .map(|line| {
+                let line = line.trim();
 
-    fn char_priority(c: char) -> u64 {
-        let mut priority = 0;
-        if c.is_ascii_uppercase() {
-            priority += 26;
-        }
-        priority += (c.to_lowercase().next().unwrap() as u64) - 96;
-        priority
+                if let Some((idx, c)) = line.char_indices().find(|(_, c)| !c.is_ascii_alphabetic())
+                {
+                    let found = &line[idx..idx + c.len_utf8()];
+                    return Err(ParseError::at(data, found, "an item type letter"));
+                }
+                if line.len() % 2 != 0 {
+                    return Err(ParseError::at(data, line, "an even number of items"));
+                }
+
+                Ok(line.to_string())
+            })
+            .collect()
     }
 
-    for line in data_as_lines {
-        let trimmed_line = line.trim();
-        if group_idx == 0 {
-            group_hash_set.clear();
-            group_hash_set.extend(trimmed_line.chars());
-        } else {
-            let tmp_group_hash_set = std::collections::HashSet::from_iter(trimmed_line.chars());
-            let intersection = group_hash_set.intersection(&tmp_group_hash_set);
-            group_hash_set =
-                std::collections::HashSet::from_iter(intersection.into_iter().copied());
+    fn part_1(input: &Self::Input) -> Answer {
+        let mut priorities_part_1 = 0u64;
+
+        for trimmed_line in input {
+            let (first_half, second_half) = trimmed_line.split_at(trimmed_line.len() / 2);
+
+            let mut hash_set_first = std::collections::HashSet::new();
+            hash_set_first.extend(first_half.chars());
+            let hash_set_second = std::collections::HashSet::from_iter(second_half.chars());
+
+            let common_char = hash_set_first.intersection(&hash_set_second);
+            for &char in common_char {
+                priorities_part_1 += char_priority(char);
+            }
         }
 
-        if group_idx == 2 {
-            for &char in group_hash_set.iter() {
-                priorities_part_2 += char_priority(char);
+        priorities_part_1.into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        let mut priorities_part_2 = 0u64;
+
+        let mut group_hash_set = std::collections::HashSet::new();
+
+        let mut group_idx = 0;
+
+        for trimmed_line in input {
+            if group_idx == 0 {
+                group_hash_set.clear();
+                group_hash_set.extend(trimmed_line.chars());
+            } else {
+                let tmp_group_hash_set = std::collections::HashSet::from_iter(trimmed_line.chars());
+                let intersection = group_hash_set.intersection(&tmp_group_hash_set);
+                group_hash_set =
+                    std::collections::HashSet::from_iter(intersection.into_iter().copied());
             }
+
+            if group_idx == 2 {
+                for &char in group_hash_set.iter() {
+                    priorities_part_2 += char_priority(char);
+                }
+            }
+
+            group_idx = (group_idx + 1) % 3;
         }
 
-        group_idx = (group_idx + 1) % 3;
+        priorities_part_2.into()
+    }
+
+    // `size` groups of three elves
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let mut rucksacks = String::new();
 
-        let (first_half, second_half) = trimmed_line.split_at(trimmed_line.len() / 2);
+        for _ in 0..size {
+            let mut item_types: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+            rng.shuffle(&mut item_types);
+            let badge = item_types.pop().unwrap();
 
-        let mut hash_set_first = std::collections::HashSet::new();
-        hash_set_first.extend(first_half.chars());
-        let hash_set_second = std::collections::HashSet::from_iter(second_half.chars());
+            // The elves of a group don't have any other type in common, and the halves of a
+            // rucksack only share one type
+            for elf_types in item_types.chunks_exact(item_types.len() / 3) {
+                let (shared, others) = elf_types.split_first().unwrap();
+                let (first_types, second_types) = others.split_at(others.len() / 2);
+                let half_len = rng.range(2..=16);
 
-        let common_char = hash_set_first.intersection(&hash_set_second);
-        for &char in common_char {
-            priorities_part_1 += char_priority(char);
+                let mut halves = [first_types, second_types].map(|half_types| {
+                    let mut half = vec![*shared];
+                    half.extend((1..half_len).map(|_| *rng.choose(half_types)));
+                    half
+                });
+                halves[rng.range(0..2)][rng.range(1..half_len)] = badge;
+                for half in &mut halves {
+                    rng.shuffle(half);
+                }
+
+                rucksacks.extend(halves.concat());
+                rucksacks.push('\n');
+            }
         }
+
+        Some(rucksacks)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
 
-    println!("Part 1: {priorities_part_1}");
-    println!("Part 2: {priorities_part_2}");
+    #[test]
+    fn part_1_example() {
+        let input = Day03::parse(EXAMPLE).unwrap();
+        assert_eq!(Day03::part_1(&input), Answer::Number(157));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day03::parse(EXAMPLE).unwrap();
+        assert_eq!(Day03::part_2(&input), Answer::Number(70));
+    }
 }
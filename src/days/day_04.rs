@@ -1,4 +1,6 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, Solution};
 
 /// --- Day 4: Camp Cleanup ---
 /// Space needs to be cleared before the last supplies can be unloaded from the ships, and so
@@ -65,38 +67,105 @@ use super::load_file;
 /// So, in this example, the number of overlapping assignment pairs is 4.
 ///
 /// In how many assignment pairs do the ranges overlap?
-pub fn day_04() {
-    let data = load_file(4);
-    let data_as_lines = data.split('\n');
-
-    let mut count_part_1 = 0u64;
-    let mut count_part_2 = 0u64;
-
-    for line in data_as_lines {
-        let trimmed_line = line.trim();
-
-        if let Some((first_elf, second_elf)) = trimmed_line.split_once(',') {
-            let (min_first_elf, max_first_elf) = first_elf.split_once('-').unwrap();
-            let (min_second_elf, max_second_elf) = second_elf.split_once('-').unwrap();
-
-            let min_first_elf: u64 = min_first_elf.parse().unwrap();
-            let max_first_elf: u64 = max_first_elf.parse().unwrap();
-            let min_second_elf: u64 = min_second_elf.parse().unwrap();
-            let max_second_elf: u64 = max_second_elf.parse().unwrap();
-
-            // PartialCmp exists for &str, kind of annoying
-            if (min_first_elf <= min_second_elf && max_first_elf >= max_second_elf)
-                || (min_second_elf <= min_first_elf && max_second_elf >= max_first_elf)
-            {
-                count_part_1 += 1;
-            }
-
-            if !(min_first_elf > max_second_elf || min_second_elf > max_first_elf) {
-                count_part_2 += 1;
-            }
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: usize = 4;
+
+    // ((min_first_elf, max_first_elf), (min_second_elf, max_second_elf))
+    type Input = Vec<((u64, u64), (u64, u64))>;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        let data_as_lines = data.lines();
+
+        let mut assignments = vec![];
+
+        for line in data_as_lines {
+            let trimmed_line = line.trim();
+
+            let (first_elf, second_elf) = parse::split_once(data, trimmed_line, ",")?;
+            let (min_first_elf, max_first_elf) = parse::split_once(data, first_elf, "-")?;
+            let (min_second_elf, max_second_elf) = parse::split_once(data, second_elf, "-")?;
+
+            let min_first_elf: u64 = parse::number(data, min_first_elf)?;
+            let max_first_elf: u64 = parse::number(data, max_first_elf)?;
+            let min_second_elf: u64 = parse::number(data, min_second_elf)?;
+            let max_second_elf: u64 = parse::number(data, max_second_elf)?;
+
+            assignments.push((
+                (min_first_elf, max_first_elf),
+                (min_second_elf, max_second_elf),
+            ));
         }
+
+        Ok(assignments)
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        let count_part_1 = input
+            .iter()
+            .filter(
+                |&&((min_first_elf, max_first_elf), (min_second_elf, max_second_elf))| {
+                    // PartialCmp exists for &str, kind of annoying
+                    (min_first_elf <= min_second_elf && max_first_elf >= max_second_elf)
+                        || (min_second_elf <= min_first_elf && max_second_elf >= max_first_elf)
+                },
+            )
+            .count();
+
+        count_part_1.into()
     }
 
-    println!("Part 1: {count_part_1}");
-    println!("Part 2: {count_part_2}");
+    fn part_2(input: &Self::Input) -> Answer {
+        let count_part_2 = input
+            .iter()
+            .filter(
+                |&&((min_first_elf, max_first_elf), (min_second_elf, max_second_elf))| {
+                    !(min_first_elf > max_second_elf || min_second_elf > max_first_elf)
+                },
+            )
+            .count();
+
+        count_part_2.into()
+    }
+
+    // `size` pairs of elves
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let mut assignment = || {
+            let min = rng.range(1..=99);
+            format!("{min}-{}", rng.range(min..=99))
+        };
+
+        let pairs = (0..size)
+            .map(|_| format!("{},{}\n", assignment(), assignment()))
+            .collect();
+
+        Some(pairs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day04::parse(EXAMPLE).unwrap();
+        assert_eq!(Day04::part_1(&input), Answer::Number(2));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day04::parse(EXAMPLE).unwrap();
+        assert_eq!(Day04::part_2(&input), Answer::Number(4));
+    }
 }
@@ -1,4 +1,7 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, Solution};
+use std::str::FromStr;
 
 /// --- Day 5: Supply Stacks ---
 /// The expedition can depart as soon as the final supplies have been unloaded from the ships.
@@ -17,8 +20,8 @@ use super::load_file;
 /// They do, however, have a drawing of the starting stacks of crates and the rearrangement
 /// procedure (your puzzle input). For example:
 ///
-///     [D]    
-/// [N] [C]    
+///     [D]
+/// [N] [C]
 /// [Z] [M] [P]
 ///  1   2   3
 ///
@@ -34,8 +37,8 @@ use super::load_file;
 /// is moved from one stack to a different stack. In the first step of the above rearrangement
 /// procedure, one crate is moved from stack 2 to stack 1, resulting in this configuration:
 ///
-/// [D]        
-/// [N] [C]    
+/// [D]
+/// [N] [C]
 /// [Z] [M] [P]
 ///  1   2   3
 /// In the second step, three crates are moved from stack 1 to stack 3. Crates are moved one at a
@@ -79,14 +82,14 @@ use super::load_file;
 ///
 /// Again considering the example above, the crates begin in the same configuration:
 ///
-///     [D]    
-/// [N] [C]    
+///     [D]
+/// [N] [C]
 /// [Z] [M] [P]
 ///  1   2   3
 /// Moving a single crate from stack 2 to stack 1 behaves the same as before:
 ///
-/// [D]        
-/// [N] [C]    
+/// [D]
+/// [N] [C]
 /// [Z] [M] [P]
 ///  1   2   3
 /// However, the action of moving three crates from stack 1 to stack 3 means that those three moved
@@ -117,84 +120,226 @@ use super::load_file;
 /// Before the rearrangement process finishes, update your simulation so that the Elves know where
 /// they should stand to be ready to unload the final supplies. After the rearrangement procedure
 /// completes, what crate ends up on top of each stack?
-pub fn day_05() {
-    let data = load_file(5);
-    let (stock, instructions) = data.split_once("\n\n").unwrap();
+pub struct Day05;
 
-    let mut stock_as_lines: Vec<&str> = stock.split('\n').into_iter().collect();
-    stock_as_lines.reverse();
-    let stock_as_lines = stock_as_lines;
+#[derive(Debug, Clone, Copy)]
+pub struct Instruction {
+    pub move_count: usize,
+    pub src_stack_idx: usize,
+    pub dst_stack_idx: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct CargoPlan {
+    pub stacks: Vec<Vec<String>>,
+    pub instructions: Vec<Instruction>,
+}
 
-    let stack_count = stock_as_lines.first().unwrap().split_whitespace().count();
+impl FromStr for Instruction {
+    type Err = ParseError;
 
-    let mut stacks = vec![vec![String::new(); 0]; stack_count];
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = parse::strip_prefix(s, s, "move ")?;
+        let (move_count, rest) = parse::split_once(s, rest, " from ")?;
+        let (src_stack, dst_stack) = parse::split_once(s, rest, " to ")?;
 
-    for stack_content in stock_as_lines.iter().skip(1) {
-        for (stack_idx, char) in stack_content.chars().skip(1).step_by(4).enumerate() {
-            match char {
-                ' ' => (),
-                _ => stacks[stack_idx].push(char.to_string()),
+        let move_count = parse::number(s, move_count)?;
+
+        // Index were given starting at 1
+        let stack_idx = |stack: &str| {
+            parse::number::<usize>(s, stack)?
+                .checked_sub(1)
+                .ok_or_else(|| ParseError::at(s, stack, "a stack number starting at 1"))
+        };
+
+        Ok(Instruction {
+            move_count,
+            src_stack_idx: stack_idx(src_stack)?,
+            dst_stack_idx: stack_idx(dst_stack)?,
+        })
+    }
+}
+
+pub fn top_crates(stacks: &[Vec<String>]) -> String {
+    stacks
+        .iter()
+        .map(|stack| stack.last().unwrap().as_str())
+        .collect()
+}
+
+impl Solution for Day05 {
+    const DAY: usize = 5;
+
+    type Input = CargoPlan;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        let (stock, instructions) = parse::split_once(data, data, "\n\n")?;
+
+        let mut stock_as_lines: Vec<&str> = stock.split('\n').collect();
+        stock_as_lines.reverse();
+        let stock_as_lines = stock_as_lines;
+
+        // There is always a line, split returns the whole string when there is no separator
+        let stack_count = stock_as_lines[0].split_whitespace().count();
+
+        let mut stacks = vec![Vec::<String>::new(); stack_count];
+
+        for stack_content in stock_as_lines.iter().skip(1) {
+            let crates = stack_content.char_indices().skip(1).step_by(4);
+            for (stack_idx, (char_idx, char)) in crates.enumerate() {
+                match char {
+                    ' ' => (),
+                    _ if stack_idx >= stack_count => {
+                        let found = &stack_content[char_idx..char_idx + char.len_utf8()];
+                        let expected = format!("at most {stack_count} stacks");
+                        return Err(ParseError::at(data, found, expected));
+                    }
+                    _ => stacks[stack_idx].push(char.to_string()),
+                }
             }
         }
-    }
 
-    let mut stacks_part1 = stacks.clone();
-    let mut stacks_part2 = stacks;
+        let instructions = instructions
+            .trim()
+            .lines()
+            .map(|line| {
+                let instruction: Instruction = line
+                    .parse()
+                    .map_err(|err: ParseError| err.within(data, line))?;
 
-    for instruction in instructions.trim().split('\n') {
-        let mut split_instruction = instruction
-            .split_whitespace()
-            .into_iter()
-            .skip(1)
-            .step_by(2);
+                if instruction.src_stack_idx.max(instruction.dst_stack_idx) >= stack_count {
+                    let expected = format!("stacks between 1 and {stack_count}");
+                    return Err(ParseError::at(data, line, expected));
+                }
 
-        let move_count: usize = split_instruction.next().unwrap().parse().unwrap();
-        let src_stack_idx: usize = split_instruction.next().unwrap().parse().unwrap();
-        let dst_stack_idx: usize = split_instruction.next().unwrap().parse().unwrap();
+                Ok(instruction)
+            })
+            .collect::<Result<_, _>>()?;
 
-        // Index were given starting at 1
-        let src_stack_idx = src_stack_idx - 1;
-        let dst_stack_idx = dst_stack_idx - 1;
-
-        let (src_stack_part2, dst_stack_part2) = {
-            if src_stack_idx < dst_stack_idx {
-                let split_len = src_stack_idx + 1;
-                let (first_half, second_half) = stacks_part2.split_at_mut(split_len);
-                (
-                    &mut first_half[src_stack_idx],
-                    &mut second_half[dst_stack_idx - split_len],
-                )
-            } else {
-                let split_len = dst_stack_idx + 1;
-                let (first_half, second_half) = stacks_part2.split_at_mut(split_len);
-                (
-                    &mut second_half[src_stack_idx - split_len],
-                    &mut first_half[dst_stack_idx],
-                )
+        Ok(CargoPlan {
+            stacks,
+            instructions,
+        })
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        let mut stacks_part1 = input.stacks.clone();
+
+        for &Instruction {
+            move_count,
+            src_stack_idx,
+            dst_stack_idx,
+        } in input.instructions.iter()
+        {
+            for _ in 0..move_count {
+                let value_to_move = stacks_part1[src_stack_idx].pop().unwrap();
+                stacks_part1[dst_stack_idx].push(value_to_move);
             }
-        };
+        }
+
+        top_crates(&stacks_part1).into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        let mut stacks_part2 = input.stacks.clone();
+
+        for &Instruction {
+            move_count,
+            src_stack_idx,
+            dst_stack_idx,
+        } in input.instructions.iter()
+        {
+            let (src_stack_part2, dst_stack_part2) = {
+                if src_stack_idx < dst_stack_idx {
+                    let split_len = src_stack_idx + 1;
+                    let (first_half, second_half) = stacks_part2.split_at_mut(split_len);
+                    (
+                        &mut first_half[src_stack_idx],
+                        &mut second_half[dst_stack_idx - split_len],
+                    )
+                } else {
+                    let split_len = dst_stack_idx + 1;
+                    let (first_half, second_half) = stacks_part2.split_at_mut(split_len);
+                    (
+                        &mut second_half[src_stack_idx - split_len],
+                        &mut first_half[dst_stack_idx],
+                    )
+                }
+            };
+
+            let src_stack_len = src_stack_part2.len();
+            let start_drain = src_stack_len - move_count;
+
+            let drained_values = src_stack_part2.drain(start_drain..);
+            dst_stack_part2.extend(drained_values);
+        }
+
+        top_crates(&stacks_part2).into()
+    }
 
-        let src_stack_len = src_stack_part2.len();
-        let start_drain = src_stack_len - move_count;
+    // `size` rearrangement steps
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let stack_count = rng.range(3..=9);
+        let mut heights: Vec<usize> = (0..stack_count).map(|_| rng.range(1..=8)).collect();
+        // Some stack has a crate to spare, and keeps it as the moves never empty a stack
+        heights[0] = heights[0].max(2);
+
+        let max_height = heights.iter().copied().max().unwrap();
+        let mut drawing = String::new();
+        for level in (0..max_height).rev() {
+            let crates: Vec<String> = heights
+                .iter()
+                .map(|&height| match level < height {
+                    true => format!("[{}]", rng.range(b'A'..=b'Z') as char),
+                    false => "   ".to_string(),
+                })
+                .collect();
+            drawing += &crates.join(" ");
+            drawing.push('\n');
+        }
+        let numbers: Vec<String> = (1..=stack_count).map(|nb| format!(" {nb} ")).collect();
+        drawing += &numbers.join(" ");
+        drawing += "\n\n";
 
-        let drained_values = src_stack_part2.drain(start_drain..);
-        dst_stack_part2.extend(drained_values);
+        for _ in 0..size {
+            let candidates: Vec<usize> = (0..stack_count).filter(|&idx| heights[idx] > 1).collect();
+            let src = *rng.choose(&candidates);
+            let dst = (src + rng.range(1..stack_count)) % stack_count;
+            let move_count = rng.range(1..heights[src]);
 
-        for _ in 0..move_count {
-            let value_to_move = stacks_part1[src_stack_idx].pop().unwrap();
-            stacks_part1[dst_stack_idx].push(value_to_move);
+            heights[src] -= move_count;
+            heights[dst] += move_count;
+            drawing += &format!("move {move_count} from {} to {}\n", src + 1, dst + 1);
         }
+
+        Some(drawing)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
 
-    print!("Part 1: ");
-    for stack in stacks_part1 {
-        print!("{}", stack.last().unwrap());
+    #[test]
+    fn part_1_example() {
+        let input = Day05::parse(EXAMPLE).unwrap();
+        assert_eq!(Day05::part_1(&input), Answer::from("CMZ"));
     }
-    println!();
 
-    print!("Part 2: ");
-    for stack in stacks_part2 {
-        print!("{}", stack.last().unwrap());
+    #[test]
+    fn part_2_example() {
+        let input = Day05::parse(EXAMPLE).unwrap();
+        assert_eq!(Day05::part_2(&input), Answer::from("MCD"));
     }
-    println!();
 }
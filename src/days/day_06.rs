@@ -1,4 +1,5 @@
-use super::load_file;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, RunOptions, Solution};
 
 /// --- Day 6: Tuning Trouble ---
 /// The preparations are finally complete; you and the Elves leave camp on foot and begin to make
@@ -62,71 +63,158 @@ use super::load_file;
 /// nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg: first marker after character 29
 /// zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw: first marker after character 26
 /// How many characters need to be processed before the first start-of-message marker is detected?
-pub fn day_06() {
-    let data = load_file(6);
-
-    let mut start_of_packet_buffer: [char; 4] = Default::default();
-    let mut start_of_packet_hash_map =
-        std::collections::HashMap::<char, usize>::with_capacity(start_of_packet_buffer.len());
-
-    let mut start_of_message_buffer: [char; 14] = Default::default();
-    let mut start_of_message_hash_map =
-        std::collections::HashMap::<char, usize>::with_capacity(start_of_message_buffer.len());
-
-    let mut part1_solution: Option<usize> = None;
-    let mut part2_solution: Option<usize> = None;
-
-    fn find_first_all_different_chars(
-        idx: usize,
-        c: char,
-        buffer: &mut [char],
-        hash_map: &mut std::collections::HashMap<char, usize>,
-    ) -> Option<usize> {
-        let store_idx = idx % buffer.len();
-
-        hash_map.entry(c).and_modify(|e| *e += 1).or_insert(1);
-        let oldest_char = buffer[store_idx];
-        buffer[store_idx] = c;
-        let oldest_char_entry = hash_map.entry(oldest_char).and_modify(|e| *e -= 1);
-        let oldest_char_count = match oldest_char_entry {
-            std::collections::hash_map::Entry::Occupied(e) => *e.get(),
-            _ => 0,
-        };
+pub struct Day06;
 
-        if oldest_char_count == 0 {
-            hash_map.remove(&oldest_char);
-        }
+pub fn find_first_all_different_chars(
+    idx: usize,
+    c: char,
+    buffer: &mut [char],
+    hash_map: &mut std::collections::HashMap<char, usize>,
+) -> Option<usize> {
+    let store_idx = idx % buffer.len();
 
-        if idx >= buffer.len() - 1 && buffer.len() == hash_map.len() {
-            return Some(idx + 1);
-        }
+    hash_map.entry(c).and_modify(|e| *e += 1).or_insert(1);
+    let oldest_char = buffer[store_idx];
+    buffer[store_idx] = c;
+    let oldest_char_entry = hash_map.entry(oldest_char).and_modify(|e| *e -= 1);
+    let oldest_char_count = match oldest_char_entry {
+        std::collections::hash_map::Entry::Occupied(e) => *e.get(),
+        _ => 0,
+    };
 
-        None
+    if oldest_char_count == 0 {
+        hash_map.remove(&oldest_char);
     }
 
+    if idx >= buffer.len() - 1 && buffer.len() == hash_map.len() {
+        return Some(idx + 1);
+    }
+
+    None
+}
+
+/// Number of characters read once the last `MARKER_LEN` ones are all different, `None` when they
+/// never are
+pub fn find_marker<const MARKER_LEN: usize>(data: &str) -> Option<usize> {
+    let mut buffer: [char; MARKER_LEN] = [Default::default(); MARKER_LEN];
+    let mut hash_map = std::collections::HashMap::<char, usize>::with_capacity(buffer.len());
+
     for (idx, c) in data.char_indices() {
-        if part1_solution.is_none() {
-            part1_solution = find_first_all_different_chars(
-                idx,
-                c,
-                &mut start_of_packet_buffer,
-                &mut start_of_packet_hash_map,
-            );
+        if let Some(solution) = find_first_all_different_chars(idx, c, &mut buffer, &mut hash_map) {
+            return Some(solution);
         }
-        if part2_solution.is_none() {
-            part2_solution = find_first_all_different_chars(
-                idx,
-                c,
-                &mut start_of_message_buffer,
-                &mut start_of_message_hash_map,
-            );
+    }
+
+    None
+}
+
+impl Solution for Day06 {
+    const DAY: usize = 6;
+
+    type Input = String;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
+    }
+
+    // A datastream without the marker of a selected part has no answer for it
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let datastream = data.trim_end();
+
+        if let Some((idx, c)) = datastream
+            .char_indices()
+            .find(|(_, c)| !c.is_ascii_lowercase())
+        {
+            let found = &datastream[idx..idx + c.len_utf8()];
+            return Err(ParseError::at(data, found, "a lowercase letter"));
         }
 
-        if part1_solution.is_some() && part2_solution.is_some() {
-            break;
+        let end = &datastream[datastream.len()..];
+        if options.parts.includes(1) && find_marker::<4>(datastream).is_none() {
+            return Err(ParseError::at(data, end, "a start-of-packet marker"));
+        }
+        if options.parts.includes(2) && find_marker::<14>(datastream).is_none() {
+            return Err(ParseError::at(data, end, "a start-of-message marker"));
         }
+
+        Ok(datastream.to_string())
     }
 
-    println!("Part 1: {}", part1_solution.unwrap());
-    println!("Part 2: {}", part2_solution.unwrap());
+    fn part_1(input: &Self::Input) -> Answer {
+        find_marker::<4>(input)
+            .expect("Marker found when parsing")
+            .into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        find_marker::<14>(input)
+            .expect("Marker found when parsing")
+            .into()
+    }
+
+    // About `size` characters before the start-of-message marker, and as many after
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let mut letters: Vec<u8> = (b'a'..=b'z').collect();
+        rng.shuffle(&mut letters);
+
+        // 13 letters cannot make a start-of-message marker, the first one comes after them
+        let mut datastream: Vec<u8> = (0..size).map(|_| *rng.choose(&letters[..13])).collect();
+        datastream.extend(&letters[..14]);
+        datastream.extend((0..size).map(|_| *rng.choose(&letters)));
+
+        Some(String::from_utf8(datastream).unwrap() + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::PartSelection;
+
+    /// Datastream and the position of its start-of-packet and start-of-message markers
+    const EXAMPLES: [(&str, usize, usize); 5] = [
+        ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+        ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+        ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+        ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+        ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+    ];
+
+    #[test]
+    fn part_1_examples() {
+        for (data, packet_marker, _) in EXAMPLES {
+            let input = Day06::parse(data).unwrap();
+            assert_eq!(Day06::part_1(&input), Answer::from(packet_marker), "{data}");
+        }
+    }
+
+    #[test]
+    fn part_2_examples() {
+        for (data, _, message_marker) in EXAMPLES {
+            let input = Day06::parse(data).unwrap();
+            assert_eq!(
+                Day06::part_2(&input),
+                Answer::from(message_marker),
+                "{data}"
+            );
+        }
+    }
+
+    #[test]
+    fn no_marker() {
+        let err = Day06::parse("abc\n").unwrap_err();
+        assert_eq!(err.expected, "a start-of-packet marker");
+
+        // Enough for a start-of-packet marker but not for a start-of-message one
+        let data = "abcdefghijklm\n";
+        let err = Day06::parse(data).unwrap_err();
+        assert_eq!(err.expected, "a start-of-message marker");
+
+        let options = RunOptions {
+            parts: PartSelection::Only(1),
+            ..Default::default()
+        };
+        let input = Day06::parse_with(data, &options).unwrap();
+        assert_eq!(Day06::part_1(&input), Answer::from(4));
+    }
 }
@@ -1,5 +1,8 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
 
 /// --- Day 7: No Space Left On Device ---
@@ -120,209 +123,419 @@ use std::rc::Rc;
 ///
 /// Find the smallest directory that, if deleted, would free up enough space on the filesystem to
 /// run the update. What is the total size of that directory?
-pub fn day_07() {
-    let data = load_file(7);
-
-    #[derive(Debug)]
-    struct File<'a> {
-        #[allow(dead_code)]
-        name: &'a str,
-        size: usize,
-    }
+pub struct Day07;
 
-    impl<'a> File<'a> {
-        pub fn new(name: &'a str, size: usize) -> Self {
-            File { name, size }
-        }
+#[derive(Debug)]
+pub struct File {
+    name: String,
+    size: usize,
+}
 
-        #[allow(dead_code)]
-        pub fn name(&self) -> &str {
-            self.name
+impl File {
+    pub fn new(name: &str, size: usize) -> Self {
+        File {
+            name: name.to_string(),
+            size,
         }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 
-        pub fn size(&self) -> usize {
-            self.size
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+#[derive(Debug)]
+pub struct Dir {
+    name: String,
+    size: RefCell<Option<usize>>,
+    content: Vec<Rc<RefCell<FsElement>>>,
+}
+
+impl Dir {
+    pub fn new(name: &str) -> Self {
+        Dir {
+            name: name.to_string(),
+            size: RefCell::new(None),
+            content: Default::default(),
         }
     }
 
-    #[derive(Debug)]
-    struct Dir<'a> {
-        name: &'a str,
-        size: RefCell<Option<usize>>,
-        content: Vec<Rc<RefCell<FsElement<'a>>>>,
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
-    impl<'a> Dir<'a> {
-        pub fn new(name: &'a str) -> Self {
-            Dir {
-                name,
-                size: RefCell::new(None),
-                content: Default::default(),
+    pub fn size(&self) -> usize {
+        {
+            let mut size = self.size.borrow_mut();
+            if size.is_none() {
+                let new_size = self
+                    .content
+                    .iter()
+                    .fold(0, |acc, x| acc + x.borrow().size());
+                *size = Some(new_size);
             }
         }
 
-        pub fn name(&self) -> &str {
-            self.name
-        }
+        return self.size.borrow().unwrap();
+    }
 
-        pub fn size(&self) -> usize {
-            {
-                let mut size = self.size.borrow_mut();
-                if size.is_none() {
-                    let new_size = self
-                        .content
-                        .iter()
-                        .fold(0, |acc, x| acc + x.borrow().size());
-                    *size = Some(new_size);
-                }
-            }
+    pub fn add_element(&mut self, element: Rc<RefCell<FsElement>>) {
+        self.content.push(element);
+    }
 
-            return self.size.borrow().unwrap();
-        }
+    pub fn get_dir_by_name(&self, name: &str) -> Option<Rc<RefCell<FsElement>>> {
+        // Linear dumb search because fighting with hashing does not appeal to me yet
+        for element in self.content.iter() {
+            let elt = element.borrow();
+            let is_correct_dir = elt.is_dir() && elt.as_dir().name() == name;
 
-        pub fn add_element(&mut self, element: Rc<RefCell<FsElement<'a>>>) {
-            self.content.push(element);
+            if is_correct_dir {
+                return Some(element.clone());
+            }
         }
 
-        pub fn get_dir_by_name(&self, name: &'a str) -> Option<Rc<RefCell<FsElement<'a>>>> {
-            // Linear dumb search because fighting with hashing does not appeal to me yet
-            for element in self.content.iter() {
-                let elt = element.borrow();
-                let is_correct_dir = elt.is_dir() && elt.as_dir().name() == name;
+        None
+    }
+}
 
-                if is_correct_dir {
-                    return Some(element.clone());
-                }
-            }
+#[derive(Debug)]
+pub enum FsElement {
+    File(File),
+    Dir(Dir),
+}
 
-            None
+impl FsElement {
+    pub fn is_dir(&self) -> bool {
+        match self {
+            FsElement::File(_) => false,
+            FsElement::Dir(_) => true,
         }
     }
 
-    #[derive(Debug)]
-    enum FsElement<'a> {
-        File(File<'a>),
-        Dir(Dir<'a>),
+    pub fn as_dir(&self) -> &Dir {
+        match self {
+            FsElement::File(_) => panic!("Not a dir"),
+            FsElement::Dir(dir) => dir,
+        }
     }
 
-    impl<'a> FsElement<'a> {
-        pub fn is_dir(&self) -> bool {
-            match self {
-                FsElement::File(_) => false,
-                FsElement::Dir(_) => true,
-            }
+    pub fn as_mut_dir(&mut self) -> &mut Dir {
+        match self {
+            FsElement::File(_) => panic!("Not a dir"),
+            FsElement::Dir(dir) => dir,
         }
+    }
 
-        pub fn as_dir(&self) -> &Dir<'a> {
-            match self {
-                FsElement::File(_) => panic!("Not a dir"),
-                FsElement::Dir(dir) => dir,
-            }
+    pub fn size(&self) -> usize {
+        match self {
+            FsElement::File(file) => file.size(),
+            FsElement::Dir(dir) => dir.size(),
         }
+    }
+}
 
-        pub fn as_mut_dir(&mut self) -> &mut Dir<'a> {
-            match self {
-                FsElement::File(_) => panic!("Not a dir"),
-                FsElement::Dir(dir) => dir,
-            }
-        }
+pub struct FileSystem {
+    pub root_dir: Rc<RefCell<FsElement>>,
+    pub all_dirs: Vec<Rc<RefCell<FsElement>>>,
+    pub total_space: usize,
+    pub required_space: usize,
+}
 
-        pub fn size(&self) -> usize {
-            match self {
-                FsElement::File(file) => file.size(),
-                FsElement::Dir(dir) => dir.size(),
-            }
-        }
+const TOTAL_SPACE: Param = Param {
+    name: "total_space",
+    default: 70_000_000,
+    min: 0,
+    max: i64::MAX,
+    doc: "disk space available to the filesystem",
+};
+
+const REQ_SPACE: Param = Param {
+    name: "required_space",
+    default: 30_000_000,
+    min: 0,
+    max: i64::MAX,
+    doc: "unused space needed by the update",
+};
+
+/// Directory of a generated file system, the root being the first one
+#[derive(Debug, Clone, Default)]
+struct RandomDir {
+    name: String,
+    subdirs: Vec<usize>,
+    /// Names and sizes
+    files: Vec<(String, usize)>,
+}
+
+/// Lowercase name of 1 to 8 letters, some files get an extension
+fn random_name(rng: &mut Rng, is_file: bool) -> String {
+    let letters = |rng: &mut Rng, count| -> String {
+        (0..count)
+            .map(|_| rng.range(b'a'..=b'z') as char)
+            .collect()
+    };
+
+    let name_len = rng.range(1..=8);
+    let name = letters(rng, name_len);
+    if is_file && rng.chance(0.5) {
+        return format!("{name}.{}", letters(rng, 3));
     }
+    name
+}
 
-    let root_dir = Rc::new(RefCell::new(FsElement::Dir(Dir::new("/"))));
-    let mut all_dirs = Vec::<Rc<RefCell<FsElement>>>::new();
-    all_dirs.push(root_dir.clone());
+/// Commands listing `dir` then exploring its subdirectories, in a random order
+fn explore(rng: &mut Rng, dirs: &[RandomDir], dir: usize, transcript: &mut String) {
+    let mut listing: Vec<String> = dirs[dir]
+        .subdirs
+        .iter()
+        .map(|&subdir| format!("dir {}", dirs[subdir].name))
+        .chain(
+            dirs[dir]
+                .files
+                .iter()
+                .map(|(name, size)| format!("{size} {name}")),
+        )
+        .collect();
+    rng.shuffle(&mut listing);
 
-    let mut location = Vec::<Rc<RefCell<FsElement>>>::new();
+    transcript.push_str("$ ls\n");
+    for line in listing {
+        transcript.push_str(&line);
+        transcript.push('\n');
+    }
 
-    let mut lines: std::collections::VecDeque<_> = data.split('\n').into_iter().collect();
+    let mut subdirs = dirs[dir].subdirs.clone();
+    rng.shuffle(&mut subdirs);
+    for subdir in subdirs {
+        transcript.push_str(&format!("$ cd {}\n", dirs[subdir].name));
+        explore(rng, dirs, subdir, transcript);
+        transcript.push_str("$ cd ..\n");
+    }
+}
 
-    while !lines.is_empty() {
-        let line = lines.pop_front().unwrap();
-        if line.starts_with('$') {
-            let (_, cmd) = line.split_once(' ').unwrap();
-            let mut cmd_with_args = cmd.split_whitespace();
+impl Solution for Day07 {
+    const DAY: usize = 7;
+
+    const PARAMS: &'static [Param] = &[TOTAL_SPACE, REQ_SPACE];
+
+    type Input = FileSystem;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
+    }
+
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let root_dir = Rc::new(RefCell::new(FsElement::Dir(Dir::new("/"))));
+        let mut all_dirs = Vec::<Rc<RefCell<FsElement>>>::new();
+        all_dirs.push(root_dir.clone());
 
-            let cmd = cmd_with_args.next().unwrap();
+        let mut location = Vec::<Rc<RefCell<FsElement>>>::new();
 
-            match cmd {
-                "cd" => {
-                    let dest_dir = cmd_with_args.next().unwrap();
+        let mut lines: std::collections::VecDeque<_> = data.lines().collect();
+
+        while let Some(line) = lines.pop_front() {
+            let cmd = parse::strip_prefix(data, line, "$ ")?;
+            let mut cmd_with_args = cmd.split_whitespace();
+
+            match cmd_with_args.next() {
+                Some("cd") => {
+                    let dest_dir = cmd_with_args.next().ok_or_else(|| {
+                        ParseError::at_end(line, "a directory").within(data, line)
+                    })?;
                     if dest_dir != "/" {
                         if dest_dir == ".." {
                             location.pop();
                             continue;
                         }
-                        let curr_dir = location.last().unwrap().clone();
+                        let curr_dir = location
+                            .last()
+                            .ok_or_else(|| ParseError::at(data, line, "cd / first"))?
+                            .clone();
                         let dest_dir = curr_dir
                             .borrow()
                             .as_dir()
                             .get_dir_by_name(dest_dir)
-                            .unwrap();
+                            .ok_or_else(|| {
+                                ParseError::at(data, dest_dir, "a directory listed by ls")
+                            })?;
                         location.push(dest_dir);
                     } else {
                         location.clear();
                         location.push(root_dir.clone());
                     }
                 }
-                "ls" => {
-                    let mut curr_dir_rc = location.last().unwrap().borrow_mut();
+                Some("ls") => {
+                    let mut curr_dir_rc = location
+                        .last()
+                        .ok_or_else(|| ParseError::at(data, line, "cd / first"))?
+                        .borrow_mut();
                     let curr_dir = curr_dir_rc.as_mut_dir();
-                    while !lines.is_empty() && !lines.front().unwrap().starts_with('$') {
-                        let line = lines.pop_front().unwrap();
-                        if let Some((dir_or_size, name)) = line.trim().split_once(' ') {
-                            let new_fs_element = match dir_or_size {
-                                "dir" => {
-                                    let new_dir =
-                                        Rc::new(RefCell::new(FsElement::Dir(Dir::new(name))));
-                                    all_dirs.push(new_dir.clone());
-                                    new_dir
-                                }
-                                size => {
-                                    let size: usize = size.parse().unwrap();
-                                    Rc::new(RefCell::new(FsElement::File(File::new(name, size))))
-                                }
-                            };
-                            curr_dir.add_element(new_fs_element);
+                    while let Some(line) = lines.pop_front() {
+                        if line.starts_with('$') {
+                            lines.push_front(line);
+                            break;
                         }
+
+                        let (dir_or_size, name) = parse::split_once(data, line.trim(), " ")?;
+                        let new_fs_element = match dir_or_size {
+                            "dir" => {
+                                let new_dir = Rc::new(RefCell::new(FsElement::Dir(Dir::new(name))));
+                                all_dirs.push(new_dir.clone());
+                                new_dir
+                            }
+                            size => {
+                                let size: usize = parse::number(data, size)?;
+                                Rc::new(RefCell::new(FsElement::File(File::new(name, size))))
+                            }
+                        };
+                        curr_dir.add_element(new_fs_element);
                     }
                 }
-                _ => unreachable!(),
+                _ => return Err(ParseError::at(data, cmd, "cd or ls")),
             }
         }
+
+        assert!(all_dirs.iter().all(|x| x.borrow().is_dir()));
+
+        Ok(FileSystem {
+            root_dir,
+            all_dirs,
+            total_space: options.params.get(&TOTAL_SPACE),
+            required_space: options.params.get(&REQ_SPACE),
+        })
     }
 
-    assert!(all_dirs.iter().all(|x| x.borrow().is_dir()));
+    fn part_1(input: &Self::Input) -> Answer {
+        let sum_size_dirs_under_100_000: usize = input
+            .all_dirs
+            .iter()
+            .filter(|&x| x.borrow().size() <= 100_000)
+            .fold(0, |acc, x| acc + x.borrow().size());
 
-    let sum_size_dirs_under_100_000: usize = all_dirs
-        .iter()
-        .filter(|&x| x.borrow().size() <= 100_000)
-        .fold(0, |acc, x| acc + x.borrow().size());
+        sum_size_dirs_under_100_000.into()
+    }
 
-    println!("Part 1: {sum_size_dirs_under_100_000}");
+    fn part_2(input: &Self::Input) -> Answer {
+        let remaining_space = input.total_space - input.root_dir.borrow().size();
+        let space_to_free = input.required_space - remaining_space;
 
-    const TOTAL_SPACE: usize = 70_000_000;
-    const REQ_SPACE: usize = 30_000_000;
-    let remaining_space = TOTAL_SPACE - root_dir.borrow().size();
-    let space_to_free = REQ_SPACE - remaining_space;
+        let dirs_freeing_enough_space: Vec<_> = input
+            .all_dirs
+            .iter()
+            .filter(|&x| x.borrow().size() >= space_to_free)
+            .collect();
 
-    let dirs_freeing_enough_space: Vec<_> = all_dirs
-        .iter()
-        .filter(|&x| x.borrow().size() >= space_to_free)
-        .collect();
+        let mut min_dir = &input.root_dir;
+        for dir in dirs_freeing_enough_space {
+            if dir.borrow().size() < min_dir.borrow().size() {
+                min_dir = dir;
+            }
+        }
+
+        let min_dir_size = min_dir.borrow().size();
+        min_dir_size.into()
+    }
+
+    // `size` directories
+    fn generate(rng: &mut Rng, size: usize, params: &Params) -> Option<String> {
+        let total_space: usize = params.get(&TOTAL_SPACE);
+        let required_space: usize = params.get(&REQ_SPACE);
+
+        let mut dirs = vec![RandomDir::default(); size.max(1)];
+        // Names already used in each directory
+        let mut taken = vec![HashSet::new(); dirs.len()];
+        let mut unique_name = |rng: &mut Rng, dir: usize, is_file: bool| loop {
+            let name = random_name(rng, is_file);
+            if taken[dir].insert(name.clone()) {
+                return name;
+            }
+        };
+
+        for dir in 1..dirs.len() {
+            let parent = rng.range(0..dir);
+            dirs[dir].name = unique_name(rng, parent, false);
+            dirs[parent].subdirs.push(dir);
+        }
+        for (dir_idx, dir) in dirs.iter_mut().enumerate() {
+            for _ in 0..rng.range(0..=4) {
+                let file = (unique_name(rng, dir_idx, true), 1 << rng.range(0..20));
+                dir.files.push(file);
+            }
+        }
+        if dirs.iter().all(|dir| dir.files.is_empty()) {
+            dirs[0].files.push((unique_name(rng, 0, true), 1));
+        }
 
-    let mut min_dir = root_dir;
-    for dir in dirs_freeing_enough_space {
-        if dir.borrow().size() < min_dir.borrow().size() {
-            min_dir = dir.clone();
+        // The sizes drawn above are weights spanning several orders of magnitude, so that there
+        // are small directories for part 1, the files fill the disk enough for part 2 to have to
+        // free some space
+        let used = rng.range(total_space.saturating_sub(required_space) + 1..=total_space);
+        let weights: usize = dirs
+            .iter()
+            .flat_map(|dir| &dir.files)
+            .map(|(_, weight)| weight)
+            .sum();
+        let mut files: Vec<&mut usize> = dirs
+            .iter_mut()
+            .flat_map(|dir| &mut dir.files)
+            .map(|(_, size)| size)
+            .collect();
+        for size in files.iter_mut() {
+            **size = (**size * used / weights).max(1);
         }
+        let scaled: usize = files.iter().map(|size| **size).sum();
+        let file_idx = rng.range(0..files.len());
+        *files[file_idx] += used.saturating_sub(scaled);
+
+        let mut transcript = "$ cd /\n".to_string();
+        explore(rng, &dirs, 0, &mut transcript);
+
+        Some(transcript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day07::parse(EXAMPLE).unwrap();
+        assert_eq!(Day07::part_1(&input), Answer::Number(95437));
     }
 
-    println!("Part 2: {}", min_dir.borrow().size());
+    #[test]
+    fn part_2_example() {
+        let input = Day07::parse(EXAMPLE).unwrap();
+        assert_eq!(Day07::part_2(&input), Answer::Number(24933642));
+    }
 }
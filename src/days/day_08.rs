@@ -1,4 +1,7 @@
-use super::load_file;
+use super::utils::grid::Grid;
+use super::utils::rng::Rng;
+use super::utils::vector::Direction;
+use super::{Answer, Params, ParseError, Solution};
 
 /// --- Day 8: Treetop Tree House ---
 /// The expedition comes across a peculiar patch of tall trees all planted carefully in a grid. The
@@ -82,162 +85,92 @@ use super::load_file;
 /// This tree's scenic score is 8 (2 * 2 * 1 * 2); this is the ideal spot for the tree house.
 ///
 /// Consider each tree on your map. What is the highest scenic score possible for any tree?
-pub fn day_08() {
-    let data = load_file(8);
-    let mut data_as_lines = data.trim().split('\n').peekable();
+pub struct Day08;
 
-    let line_len = data_as_lines.peek().unwrap().len();
-    let line_count = data_as_lines.clone().count();
+impl Solution for Day08 {
+    const DAY: usize = 8;
 
-    let mut forest = vec![0u32; line_len * line_count];
-    for (line, tree_line) in data_as_lines.zip(forest.chunks_mut(line_len)) {
-        tree_line
-            .iter_mut()
-            .zip(line.chars())
-            .for_each(|(dst, src)| *dst = src.to_digit(10).unwrap());
-    }
-
-    let forest = forest;
-
-    let mut visible_forest = vec![0u32; line_len * line_count];
-
-    for (tree_line, visibility_line) in forest
-        .chunks(line_len)
-        .zip(visible_forest.chunks_mut(line_len))
-    {
-        let mut must_be_higher_to_be_seen_from_left = tree_line[0];
-        visibility_line
-            .iter_mut()
-            .zip(tree_line.iter())
-            .for_each(|(visibility, &tree_height)| {
-                if tree_height > must_be_higher_to_be_seen_from_left {
-                    must_be_higher_to_be_seen_from_left = tree_height;
-                    *visibility |= 1;
-                }
-            });
-        visibility_line[0] |= 1;
-
-        let mut must_be_higher_to_be_seen_from_right = *tree_line.last().unwrap();
-
-        visibility_line
-            .iter_mut()
-            .zip(tree_line.iter())
-            .rev()
-            .for_each(|(visibility, &tree_height)| {
-                if tree_height > must_be_higher_to_be_seen_from_right {
-                    must_be_higher_to_be_seen_from_right = tree_height;
-                    *visibility |= 1;
-                }
-            });
-
-        let right_tree = visibility_line.last_mut().unwrap();
-        *right_tree |= 1;
-    }
-
-    for column_idx in 0..line_len {
-        let mut tree_column_iter = forest.iter().skip(column_idx).step_by(line_len);
-        let mut visibility_column_iter =
-            visible_forest.iter_mut().skip(column_idx).step_by(line_len);
-
-        let mut must_be_higher_to_be_seen_from_top = *tree_column_iter.next().unwrap();
-        let top_tree_visible = visibility_column_iter.next().unwrap();
-        *top_tree_visible |= 1;
-
-        visibility_column_iter
-            .zip(tree_column_iter)
-            .for_each(|(visibility, &tree_height)| {
-                if tree_height > must_be_higher_to_be_seen_from_top {
-                    must_be_higher_to_be_seen_from_top = tree_height;
-                    *visibility |= 1;
-                }
-            });
-
-        let mut tree_column_iter = forest.iter().skip(column_idx).step_by(line_len).rev();
-        let mut visibility_column_iter = visible_forest
-            .iter_mut()
-            .skip(column_idx)
-            .step_by(line_len)
-            .rev();
+    type Input = Grid<u32>;
 
-        let mut must_be_higher_to_be_seen_from_bot = *tree_column_iter.next().unwrap();
-        let bot_tree_visible = visibility_column_iter.next().unwrap();
-        *bot_tree_visible |= 1;
-
-        visibility_column_iter
-            .zip(tree_column_iter)
-            .for_each(|(visibility, &tree_height)| {
-                if tree_height > must_be_higher_to_be_seen_from_bot {
-                    must_be_higher_to_be_seen_from_bot = tree_height;
-                    *visibility |= 1;
-                }
-            });
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Grid::parse(data, "a tree height", |_, c| c.to_digit(10))
     }
 
-    let visible_tree_count: u32 = visible_forest.iter().sum();
-
-    println!("Part 1: {visible_tree_count}");
-
-    let mut scenic_score = vec![1u32; forest.len()];
-
-    for ((tree_idx, &tree_height), scenic_score) in
-        forest.iter().enumerate().zip(scenic_score.iter_mut())
-    {
-        let column_idx = tree_idx % line_len;
-
-        let mut right_viewing_distance = 0;
-        let right_view_iter = forest
+    fn part_1(input: &Self::Input) -> Answer {
+        let visible_tree_count = input
             .iter()
-            .skip(tree_idx + 1)
-            .take(line_len - (column_idx + 1));
-        for &other_tree_height in right_view_iter {
-            right_viewing_distance += 1;
-            if tree_height <= other_tree_height {
-                break;
-            }
-        }
-        *scenic_score *= right_viewing_distance;
+            .filter(|&(pos, &tree_height)| {
+                Direction::ALL.into_iter().any(|direction| {
+                    input
+                        .ray(pos, direction.offset())
+                        .all(|&other_tree_height| other_tree_height < tree_height)
+                })
+            })
+            .count();
+
+        visible_tree_count.into()
+    }
 
-        let mut left_viewing_distance = 0;
-        let left_view_iter = forest
+    fn part_2(input: &Self::Input) -> Answer {
+        let max_scenic_score = input
             .iter()
-            .rev()
-            .skip(forest.len() - tree_idx)
-            .take(column_idx);
-        for &other_tree_height in left_view_iter {
-            left_viewing_distance += 1;
-            if tree_height <= other_tree_height {
-                break;
-            }
-        }
-        *scenic_score *= left_viewing_distance;
-
-        let mut bot_viewing_distance = 0;
-        let bot_view_iter = forest.iter().skip(tree_idx).step_by(line_len).skip(1);
-        for &other_tree_height in bot_view_iter {
-            bot_viewing_distance += 1;
-            if tree_height <= other_tree_height {
-                break;
-            }
-        }
-        *scenic_score *= bot_viewing_distance;
+            .map(|(pos, &tree_height)| {
+                Direction::ALL
+                    .into_iter()
+                    .map(|direction| {
+                        let mut viewing_distance = 0;
+                        for &other_tree_height in input.ray(pos, direction.offset()) {
+                            viewing_distance += 1;
+                            if tree_height <= other_tree_height {
+                                break;
+                            }
+                        }
+                        viewing_distance
+                    })
+                    .product::<u32>()
+            })
+            .max()
+            .unwrap_or_default();
+
+        max_scenic_score.into()
+    }
 
-        let mut top_viewing_distance = 0;
-        let top_view_iter = forest
-            .iter()
-            .rev()
-            .skip(forest.len() - (tree_idx + 1))
-            .step_by(line_len)
-            .skip(1);
-        for &other_tree_height in top_view_iter {
-            top_viewing_distance += 1;
-            if tree_height <= other_tree_height {
-                break;
-            }
-        }
-        *scenic_score *= top_viewing_distance;
+    // `size` by `size` trees
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let forest = (0..size)
+            .map(|_| {
+                let row: String = (0..size)
+                    .map(|_| char::from_digit(rng.range(0..10), 10).unwrap())
+                    .collect();
+                row + "\n"
+            })
+            .collect();
+
+        Some(forest)
     }
+}
 
-    let max_scenic_score = *scenic_score.iter().max().unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+30373
+25512
+65332
+33549
+35390
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day08::parse(EXAMPLE).unwrap();
+        assert_eq!(Day08::part_1(&input), Answer::Number(21));
+    }
 
-    println!("Part 2: {max_scenic_score}");
+    #[test]
+    fn part_2_example() {
+        let input = Day08::parse(EXAMPLE).unwrap();
+        assert_eq!(Day08::part_2(&input), Answer::Number(8));
+    }
 }
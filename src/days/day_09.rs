@@ -1,4 +1,7 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::utils::vector::{Direction, Vec2};
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
 
 /// --- Day 9: Rope Bridge ---
 /// This rope bridge creaks as you walk along it. You aren't sure how old it is, or whether it can
@@ -697,104 +700,149 @@ use super::load_file;
 /// .........########.........
 /// Simulate your complete series of motions on a larger rope with ten knots. How many positions
 /// does the tail of the rope visit at least once?
-pub fn day_09() {
-    let data = load_file(9);
+pub struct Day09;
 
-    let data_as_lines = data.trim().split('\n');
-
-    #[derive(Clone, Copy, Default, Debug)]
-    struct Pos {
-        pub x: i32,
-        pub y: i32,
+/// Moves a knot to keep it touching the one ahead of it, diagonally if they are not in line
+pub fn move_tail_towards_head(tail: &mut Vec2<i32>, head: Vec2<i32>) {
+    if tail.chebyshev(head) > 1 {
+        *tail += (head - *tail).signum();
     }
+}
 
-    let mut head_part1: Pos = Default::default();
-    let mut tail_part1: Pos = Default::default();
-
-    let mut tail_pos_hash_set_part1 = std::collections::HashSet::new();
+/// Positions visited by the last knot of a rope of `knots` knots, head included
+pub fn count_tail_positions(motions: &[(Direction, usize)], knots: usize) -> usize {
+    assert!(knots >= 2, "A rope has at least a head and a tail");
 
-    let mut head_part2: Pos = Default::default();
-    let mut tail_part2: [Pos; 9] = [Default::default(); 9];
+    let mut rope: Vec<Vec2<i32>> = vec![Vec2::default(); knots];
 
-    let mut tail_pos_hash_set_part2 = std::collections::HashSet::new();
+    let mut tail_pos_hash_set = std::collections::HashSet::new();
 
-    fn move_head(head: &mut Pos, direction: &str) {
-        match direction {
-            "U" => {
-                head.y += 1;
-            }
-            "D" => {
-                head.y -= 1;
-            }
-            "L" => {
-                head.x -= 1;
-            }
-            "R" => {
-                head.x += 1;
+    for &(direction, len) in motions {
+        for _ in 0..len {
+            rope[0] += direction.offset();
+            for knot_idx in 1..rope.len() {
+                let head = rope[knot_idx - 1];
+                move_tail_towards_head(&mut rope[knot_idx], head);
             }
-            _ => unreachable!(),
+            tail_pos_hash_set.insert(*rope.last().unwrap());
         }
     }
 
-    fn move_tail_towards_head(tail: &mut Pos, head: &mut Pos) {
-        let delta_x = head.x - tail.x;
-        let delta_y = head.y - tail.y;
+    tail_pos_hash_set.len()
+}
 
-        if delta_x == 0 {
-            if delta_y > 1 {
-                tail.y += 1;
-            } else if delta_y < -1 {
-                tail.y -= 1;
-            }
-        }
-        if delta_y == 0 {
-            if delta_x > 1 {
-                tail.x += 1;
-            } else if delta_x < -1 {
-                tail.x -= 1;
-            }
-        }
+const KNOTS: Param = Param {
+    name: "knots",
+    default: 10,
+    min: 2,
+    max: 1_000,
+    doc: "knots of the part 2 rope, head included",
+};
 
-        if delta_x.abs() == 1 && delta_y.abs() == 2 {
-            tail.x += delta_x;
-            tail.y += delta_y / 2;
-        }
+pub struct Motions {
+    pub motions: Vec<(Direction, usize)>,
+    pub knots: usize,
+}
 
-        if delta_y.abs() == 1 && delta_x.abs() == 2 {
-            tail.x += delta_x / 2;
-            tail.y += delta_y;
-        }
+impl Solution for Day09 {
+    const DAY: usize = 9;
 
-        if delta_x.abs() == 2 && delta_y.abs() == 2 {
-            tail.x += delta_x / 2;
-            tail.y += delta_y / 2;
-        }
+    const PARAMS: &'static [Param] = &[KNOTS];
+
+    type Input = Motions;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
     }
 
-    for line in data_as_lines {
-        let (direction, len) = line.trim().split_once(' ').unwrap();
-        let len: usize = len.parse().unwrap();
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let motions = data
+            .trim()
+            .split('\n')
+            .map(|line| {
+                let (direction, len) = parse::split_once(data, line.trim(), " ")?;
+                let direction = match direction {
+                    "U" => Direction::Up,
+                    "D" => Direction::Down,
+                    "L" => Direction::Left,
+                    "R" => Direction::Right,
+                    _ => return Err(ParseError::at(data, direction, "U, D, L or R")),
+                };
+                Ok((direction, parse::number(data, len)?))
+            })
+            .collect::<Result<_, _>>()?;
 
-        for _ in 0..len {
-            move_head(&mut head_part1, direction);
-            move_tail_towards_head(&mut tail_part1, &mut head_part1);
+        Ok(Motions {
+            motions,
+            knots: options.params.get(&KNOTS),
+        })
+    }
 
-            tail_pos_hash_set_part1.insert((tail_part1.x, tail_part1.y));
+    fn part_1(input: &Self::Input) -> Answer {
+        count_tail_positions(&input.motions, 2).into()
+    }
 
-            move_head(&mut head_part2, direction);
-            move_tail_towards_head(&mut tail_part2[0], &mut head_part2);
-            for tail_idx in 1..tail_part2.len() {
-                let (head_section, tail_section) = tail_part2.split_at_mut(tail_idx);
-                let head = head_section.last_mut().unwrap();
-                let tail = tail_section.first_mut().unwrap();
+    fn part_2(input: &Self::Input) -> Answer {
+        count_tail_positions(&input.motions, input.knots).into()
+    }
 
-                move_tail_towards_head(tail, head);
-            }
-            let last_tail = tail_part2.last().unwrap();
-            tail_pos_hash_set_part2.insert((last_tail.x, last_tail.y));
-        }
+    // `size` motions
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let motions = (0..size)
+            .map(|_| {
+                let direction = rng.choose(&["U", "D", "L", "R"]);
+                format!("{direction} {}\n", rng.range(1..=20))
+            })
+            .collect();
+
+        Some(motions)
     }
+}
 
-    println!("Part 1: {}", tail_pos_hash_set_part1.len());
-    println!("Part 2: {}", tail_pos_hash_set_part2.len());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2
+";
+
+    const LARGER_EXAMPLE: &str = "\
+R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day09::parse(EXAMPLE).unwrap();
+        assert_eq!(Day09::part_1(&input), Answer::Number(13));
+    }
+
+    #[test]
+    fn part_2_examples() {
+        let input = Day09::parse(EXAMPLE).unwrap();
+        assert_eq!(Day09::part_2(&input), Answer::Number(1));
+
+        let input = Day09::parse(LARGER_EXAMPLE).unwrap();
+        assert_eq!(Day09::part_2(&input), Answer::Number(36));
+
+        // Same rope as part 1
+        let mut options = RunOptions::default();
+        options.params.set("knots", 2);
+        let input = Day09::parse_with(EXAMPLE, &options).unwrap();
+        assert_eq!(Day09::part_2(&input), Answer::Number(13));
+    }
 }
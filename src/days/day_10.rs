@@ -1,4 +1,8 @@
-use super::load_file;
+use super::parse;
+use super::utils::grid::Grid;
+use super::utils::rng::Rng;
+use super::utils::vector::Vec2;
+use super::{Answer, Params, ParseError, Solution};
 
 /// --- Day 10: Cathode-Ray Tube ---
 /// You avoid the ropes, plunge into the river, and swim to shore.
@@ -337,30 +341,32 @@ use super::load_file;
 /// ######......######......######......####
 /// #######.......#######.......#######.....
 /// Render the image given by your program. What eight capital letters appear on your CRT?
-pub fn day_10() {
-    let data = load_file(10);
+pub struct Day10;
 
-    let instructions = data.trim().split('\n');
+pub struct CpuTrace {
+    pub twenties_strength: i32,
+    pub crt: Grid<char>,
+}
 
+// None is a noop, Some(v) is an addx v
+pub fn run_program(instructions: &[Option<i32>]) -> CpuTrace {
     let mut reg_x: i32 = 1;
     let mut current_cycle: usize = 0;
 
     let mut twenties_strength = 0;
 
-    let mut crt: Vec<char> = vec!['.'; 40 * 6];
+    let mut crt = Grid::new(40, 6, '.');
 
-    for instruction in instructions {
-        let (cycles, add_v): (i32, Option<i32>) = match instruction {
-            "noop" => (1, None),
-            _ => (
-                2,
-                Some(instruction.split_once(' ').unwrap().1.parse().unwrap()),
-            ),
+    for &add_v in instructions {
+        let cycles = match add_v {
+            None => 1,
+            Some(_) => 2,
         };
 
         for _ in 0..cycles {
-            let curr_pixel_x: i32 = (current_cycle % 40).try_into().unwrap();
-            let curr_pixel = &mut crt[current_cycle];
+            let curr_pixel_pos = Vec2::new(current_cycle % crt.width(), current_cycle / crt.width());
+            let curr_pixel_x: i32 = curr_pixel_pos.x.try_into().unwrap();
+            let curr_pixel = &mut crt[curr_pixel_pos];
 
             let sprite_center = reg_x;
             if sprite_center - 1 <= curr_pixel_x && curr_pixel_x <= sprite_center + 1 {
@@ -368,7 +374,7 @@ pub fn day_10() {
             }
 
             current_cycle += 1;
-            if current_cycle <= 220 && (current_cycle + 20) % 40 == 0 {
+            if current_cycle <= 220 && (current_cycle + 20).is_multiple_of(40) {
                 let current_cycle: i32 = current_cycle.try_into().unwrap();
                 twenties_strength += current_cycle * reg_x;
             }
@@ -379,12 +385,231 @@ pub fn day_10() {
         }
     }
 
-    let newline = ['\n'];
-    let crt_string: String = crt
-        .chunks(40)
-        .flat_map(|x| x.iter().chain(newline.iter()))
-        .collect();
+    CpuTrace {
+        twenties_strength,
+        crt,
+    }
+}
+
+impl Solution for Day10 {
+    const DAY: usize = 10;
+
+    type Input = Vec<Option<i32>>;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        data.trim()
+            .split('\n')
+            .map(|instruction| match instruction {
+                "noop" => Ok(None),
+                _ => {
+                    let value = parse::strip_prefix(data, instruction, "addx ")?;
+                    Ok(Some(parse::number(data, value)?))
+                }
+            })
+            .collect()
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        run_program(input).twenties_strength.into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        run_program(input).crt.to_string().into()
+    }
+
+    // Always the 240 cycles drawing the screen, whatever the size
+    fn generate(rng: &mut Rng, _size: usize, _params: &Params) -> Option<String> {
+        let mut program = String::new();
+        let (mut x, mut cycles) = (1, 0);
+
+        while cycles < 240 {
+            if cycles == 239 || rng.chance(0.3) {
+                program += "noop\n";
+                cycles += 1;
+            } else {
+                // The sprite stays on the screen
+                let next_x = rng.range(-1..=40);
+                program += &format!("addx {}\n", next_x - x);
+                x = next_x;
+                cycles += 2;
+            }
+        }
+
+        Some(program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+addx 15
+addx -11
+addx 6
+addx -3
+addx 5
+addx -1
+addx -8
+addx 13
+addx 4
+noop
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx -35
+addx 1
+addx 24
+addx -19
+addx 1
+addx 16
+addx -11
+noop
+noop
+addx 21
+addx -15
+noop
+noop
+addx -3
+addx 9
+addx 1
+addx -3
+addx 8
+addx 1
+addx 5
+noop
+noop
+noop
+noop
+noop
+addx -36
+noop
+addx 1
+addx 7
+noop
+noop
+noop
+addx 2
+addx 6
+noop
+noop
+noop
+noop
+noop
+addx 1
+noop
+noop
+addx 7
+addx 1
+noop
+addx -13
+addx 13
+addx 7
+noop
+addx 1
+addx -33
+noop
+noop
+noop
+addx 2
+noop
+noop
+noop
+addx 8
+noop
+addx -1
+addx 2
+addx 1
+noop
+addx 17
+addx -9
+addx 1
+addx 1
+addx -3
+addx 11
+noop
+noop
+addx 1
+noop
+addx 1
+noop
+noop
+addx -13
+addx -19
+addx 1
+addx 3
+addx 26
+addx -30
+addx 12
+addx -1
+addx 3
+addx 1
+noop
+noop
+noop
+addx -9
+addx 18
+addx 1
+addx 2
+noop
+noop
+addx 9
+noop
+noop
+noop
+addx -1
+addx 2
+addx -37
+addx 1
+addx 3
+noop
+addx 15
+addx -21
+addx 22
+addx -6
+addx 1
+noop
+addx 2
+addx 1
+noop
+addx -10
+noop
+noop
+addx 20
+addx 1
+addx 2
+addx 2
+addx -6
+addx -11
+noop
+noop
+noop
+";
+
+    const EXAMPLE_IMAGE: &str = "\
+##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....
+";
 
-    println!("Part 1: {twenties_strength}");
-    println!("Part 2:\n{crt_string}");
+    #[test]
+    fn part_1_example() {
+        let input = Day10::parse(EXAMPLE).unwrap();
+        assert_eq!(Day10::part_1(&input), Answer::Number(13140));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day10::parse(EXAMPLE).unwrap();
+        assert_eq!(Day10::part_2(&input), Answer::from(EXAMPLE_IMAGE));
+    }
 }
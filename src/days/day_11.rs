@@ -1,4 +1,7 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
 
 /// --- Day 11: Monkey in the Middle ---
 /// As you finally start making your way upriver, you realize your pack is much lighter than you
@@ -323,268 +326,596 @@ use super::load_file;
 /// Worry levels are no longer divided by three after each item is inspected; you'll need to find
 /// another way to keep your worry levels manageable. Starting again from the initial state in your
 /// puzzle input, what is the level of monkey business after 10000 rounds?
-pub fn day_11() {
-    let data = load_file(11);
+pub struct Day11;
 
-    let data_by_monkey = data.trim().split("\n\n");
-
-    let monkey_count = data_by_monkey.clone().count();
-
-    let mut monkeys_objects_part1 = vec![std::collections::VecDeque::new(); monkey_count];
+pub struct MonkeyCircuit {
+    operation: Box<dyn Fn(u64) -> u64>,
+    divisible_by: u64,
+    monkey_if_true: usize,
+    monkey_if_false: usize,
+}
 
-    struct MonkeyCircuit {
+impl MonkeyCircuit {
+    pub fn new(
         operation: Box<dyn Fn(u64) -> u64>,
         divisible_by: u64,
         monkey_if_true: usize,
         monkey_if_false: usize,
+    ) -> Self {
+        MonkeyCircuit {
+            operation,
+            divisible_by,
+            monkey_if_true,
+            monkey_if_false,
+        }
     }
 
-    const VERBOSE: bool = false;
+    pub fn process_monkey_brain_part1(&self, worry_level: u64) -> (usize, u64) {
+        trace!("  Monkey inspects an item with a worry level of {worry_level}.");
+        trace!("    ...");
+        let new_worry_level = self.operation.as_ref()(worry_level) / 3;
+        trace!(
+            "    Monkey gets bored with item. Worry level is divided by 3 to {new_worry_level}."
+        );
+
+        if new_worry_level % self.divisible_by == 0 {
+            trace!(
+                "    Current worry level is divisible by {}.",
+                self.divisible_by
+            );
+            return (self.monkey_if_true, new_worry_level);
+        }
+
+        trace!(
+            "    Current worry level is not divisible by {}.",
+            self.divisible_by
+        );
 
-    macro_rules! vprint {
-        ($($x:tt)*) => { if VERBOSE { println!($($x)*); } }
+        (self.monkey_if_false, new_worry_level)
     }
 
-    impl MonkeyCircuit {
-        pub fn new(
-            operation: Box<dyn Fn(u64) -> u64>,
-            divisible_by: u64,
-            monkey_if_true: usize,
-            monkey_if_false: usize,
-        ) -> Self {
-            MonkeyCircuit {
-                operation,
-                divisible_by,
-                monkey_if_true,
-                monkey_if_false,
-            }
-        }
+    pub fn process_monkey_brain_part2(&self, worry_level: u64) -> (usize, u64) {
+        trace!("  Monkey inspects an item with a worry level of {worry_level}.");
+        trace!("    ...");
+        let new_worry_level = self.operation.as_ref()(worry_level);
 
-        pub fn process_monkey_brain_part1(&self, worry_level: u64) -> (usize, u64) {
-            vprint!("  Monkey inspects an item with a worry level of {worry_level}.");
-            vprint!("    ...");
-            let new_worry_level = self.operation.as_ref()(worry_level) / 3;
-            vprint!(
-                "    Monkey gets bored with item. Worry level is divided by 3 to {new_worry_level}."
+        if new_worry_level % self.divisible_by == 0 {
+            trace!(
+                "    Current worry level is divisible by {}.",
+                self.divisible_by
             );
+            return (self.monkey_if_true, new_worry_level);
+        }
 
-            if new_worry_level % self.divisible_by == 0 {
-                vprint!(
-                    "    Current worry level is divisible by {}.",
-                    self.divisible_by
-                );
-                return (self.monkey_if_true, new_worry_level);
-            }
+        trace!(
+            "    Current worry level is not divisible by {}.",
+            self.divisible_by
+        );
 
-            vprint!(
-                "    Current worry level is not divisible by {}.",
-                self.divisible_by
-            );
+        (self.monkey_if_false, new_worry_level)
+    }
+}
+
+impl std::default::Default for MonkeyCircuit {
+    fn default() -> Self {
+        MonkeyCircuit {
+            operation: Box::new(|_: u64| 0u64),
+            divisible_by: 0,
+            monkey_if_true: 0,
+            monkey_if_false: 0,
+        }
+    }
+}
 
-            (self.monkey_if_false, new_worry_level)
+pub struct MonkeyGroup {
+    pub monkey_circuits: Vec<MonkeyCircuit>,
+    pub monkeys_objects: Vec<std::collections::VecDeque<u64>>,
+    pub divisor_product: u64,
+    pub rounds: [usize; 2],
+}
+
+impl MonkeyGroup {
+    fn monkey_business<const PART_1: bool>(&self, rounds: usize) -> usize {
+        let monkey_count = self.monkey_circuits.len();
+        let mut monkeys_objects = self.monkeys_objects.clone();
+        let mut monkey_cumulative_objects = vec![0usize; monkey_count];
+
+        for _ in 0..rounds {
+            for (src_monkey_idx, monkey) in self.monkey_circuits.iter().enumerate() {
+                trace!("Monkey {src_monkey_idx}:");
+                let actions: Vec<(usize, u64)> = monkeys_objects[src_monkey_idx]
+                    .drain(..)
+                    .map(|old_worry| {
+                        let res = if PART_1 {
+                            monkey.process_monkey_brain_part1(old_worry)
+                        } else {
+                            monkey.process_monkey_brain_part2(old_worry)
+                        };
+                        monkey_cumulative_objects[src_monkey_idx] += 1;
+                        trace!(
+                            "    Item with worry level {} is thrown to monkey {}.",
+                            res.1,
+                            res.0
+                        );
+                        // Dividing by 3 does not carry over to the modulus, part 1 keeps the
+                        // whole worry levels
+                        match PART_1 {
+                            true => res,
+                            false => (res.0, res.1 % self.divisor_product),
+                        }
+                    })
+                    .collect();
+
+                for (dst_monkey, new_worry) in actions {
+                    monkeys_objects[dst_monkey].push_back(new_worry);
+                }
+            }
         }
+        for (monkey_idx, inspections) in monkey_cumulative_objects.iter().enumerate() {
+            debug!("Monkey {monkey_idx} inspected items {inspections} times.");
+        }
+
+        monkey_cumulative_objects.sort();
+        monkey_cumulative_objects.reverse();
+
+        monkey_cumulative_objects[0] * monkey_cumulative_objects[1]
+    }
+}
 
-        pub fn process_monkey_brain_part2(&self, worry_level: u64) -> (usize, u64) {
-            vprint!("  Monkey inspects an item with a worry level of {worry_level}.");
-            vprint!("    ...");
-            let new_worry_level = self.operation.as_ref()(worry_level);
-
-            if new_worry_level % self.divisible_by == 0 {
-                vprint!(
-                    "    Current worry level is divisible by {}.",
-                    self.divisible_by
-                );
-                return (self.monkey_if_true, new_worry_level);
+const ROUNDS_PART_1: Param = Param {
+    name: "rounds_1",
+    default: 20,
+    min: 0,
+    max: i64::MAX,
+    doc: "rounds of part 1, worry levels divided by 3",
+};
+
+const ROUNDS_PART_2: Param = Param {
+    name: "rounds_2",
+    default: 10000,
+    min: 0,
+    max: i64::MAX,
+    doc: "rounds of part 2",
+};
+
+/// Monkey of a generated input
+struct RandomMonkey {
+    items: Vec<u64>,
+    /// `*` or `+`, and the value, `None` for `old`
+    operation: (char, Option<u64>),
+    divisible_by: u64,
+    /// When divisible, when not
+    targets: [usize; 2],
+}
+
+fn random_monkeys(rng: &mut Rng, item_count: usize) -> Vec<RandomMonkey> {
+    // Distinct primes, as in the puzzle inputs, their product must be small enough to square
+    let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19];
+    rng.shuffle(&mut divisors);
+    let monkey_count = rng.range(2..=8);
+    let squaring_monkey = rng.range(0..monkey_count);
+
+    let mut monkeys: Vec<RandomMonkey> = (0..monkey_count)
+        .map(|monkey| {
+            let operation = match (monkey == squaring_monkey, rng.chance(0.25)) {
+                (true, _) => ('*', None),
+                (false, true) => ('*', Some(rng.range(2..=19))),
+                (false, false) => ('+', Some(rng.range(1..=8))),
+            };
+
+            // Never to itself, and to two different monkeys when there are more than two
+            let mut other_monkey = || (monkey + rng.range(1..monkey_count)) % monkey_count;
+            let if_true = other_monkey();
+            let mut if_false = other_monkey();
+            while monkey_count > 2 && if_false == if_true {
+                if_false = other_monkey();
             }
 
-            vprint!(
-                "    Current worry level is not divisible by {}.",
-                self.divisible_by
-            );
+            RandomMonkey {
+                items: vec![],
+                operation,
+                divisible_by: divisors[monkey],
+                targets: [if_true, if_false],
+            }
+        })
+        .collect();
 
-            (self.monkey_if_false, new_worry_level)
-        }
+    for _ in 0..item_count {
+        monkeys[rng.range(0..monkey_count)]
+            .items
+            .push(rng.range(50..=99));
     }
+    monkeys
+}
 
-    impl std::default::Default for MonkeyCircuit {
-        fn default() -> Self {
-            MonkeyCircuit {
-                operation: Box::new(|_: u64| 0u64),
-                divisible_by: 0,
-                monkey_if_true: 0,
-                monkey_if_false: 0,
+/// Whether the worry levels of part 1 stay within u64 for `rounds`
+fn fits_part_1(monkeys: &[RandomMonkey], rounds: usize) -> bool {
+    let mut items: Vec<Vec<u64>> = monkeys.iter().map(|monkey| monkey.items.clone()).collect();
+
+    for _ in 0..rounds {
+        for (idx, monkey) in monkeys.iter().enumerate() {
+            for item in std::mem::take(&mut items[idx]) {
+                let operand = monkey.operation.1.unwrap_or(item);
+                let worry = match monkey.operation.0 {
+                    '*' => item.checked_mul(operand),
+                    _ => item.checked_add(operand),
+                };
+                let Some(worry) = worry.map(|worry| worry / 3) else {
+                    return false;
+                };
+
+                let divisible = worry % monkey.divisible_by == 0;
+                items[monkey.targets[usize::from(!divisible)]].push(worry);
             }
         }
     }
 
-    let mut monkey_circuits: Vec<MonkeyCircuit> = Vec::with_capacity(monkey_count);
-    // Can't find a way around this ugly thing because of unclonable dyn closure.
-    for _ in 0..monkey_circuits.capacity() {
-        monkey_circuits.push(Default::default());
+    true
+}
+
+impl Solution for Day11 {
+    const DAY: usize = 11;
+
+    const PARAMS: &'static [Param] = &[ROUNDS_PART_1, ROUNDS_PART_2];
+
+    type Input = MonkeyGroup;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
     }
 
-    let mut monkey_cumulative_objects_part1 = vec![0usize; monkey_count];
-
-    // For part 2 the squaring makes using big integers is impossible, all the divisors are
-    // conveniently prime numbers, computing their products and keeping only the modulus allows to
-    // perform the divisibility test while keeping very small integers. If the divisors were not
-    // prime we would have needed to find the lowest common multiple of all divisors via a prime
-    // factor decomposition.
-    //
-    // The math property that will be useful (all variables used here are integers):
-    //
-    // if a == b mod c then there exists k such that a = kc + b
-    //
-    // if a == b mod c and d == e mod c then a + d == b + e mod c, i.e. we can add an integer to
-    // another and the resulting modulus is the sum of their moduli mod c, so monkeys can add values
-    // and the modulus will contain the divisibility information. Same goes for multiplication,
-    // which can be seen as a series of additions.
-    //
-    // Proof for addition:
-    // if a == b mod c then there exists k such that a = kc + b
-    // if e == d mod c then there exists l such that d = lc + e
-    // a + d = (k + l) c + b + e, or a + d == b + e mod c.
-    //
-    // Finally if b divides a, i.e. a == 0 mod b and c divides b i.e. b == 0 mod c then c divides a
-    // or a == 0 mod c
-    //
-    // c divides b means there exists a k such that b = kc
-    // b divides a means there exists an l such that a = lb <=> a = klc <=> a = mc where m = kl
-    //
-    // So we can compute the modulus by the products of divisors and as every monkey's dvisior
-    // divides the product of divisor we can just store the modulus of the worry levels to
-    // perform the divisibility test for each monkey.
-    let mut divisor_product: u64 = 1;
-
-    for monkey_data in data_by_monkey {
-        let mut monkey_data_as_lines = monkey_data.split('\n');
-        let (monkey_id_str, _) = monkey_data_as_lines
-            .next()
-            .unwrap()
-            .split_once(':')
-            .unwrap();
-        let (_, monkey_id_str) = monkey_id_str.split_once(' ').unwrap();
-        let monkey_idx: usize = monkey_id_str.parse().unwrap();
-
-        let monkey_objects = &mut monkeys_objects_part1[monkey_idx];
-
-        let (_, monkey_objects_str) = monkey_data_as_lines
-            .next()
-            .unwrap()
-            .split_once(':')
-            .unwrap();
-        let monkey_objects_str = monkey_objects_str.trim().split(',');
-        for monkey_object in monkey_objects_str {
-            let worry_level: u64 = monkey_object.trim().parse().unwrap();
-            monkey_objects.push_back(worry_level);
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let data_by_monkey = data.trim().split("\n\n");
+
+        let monkey_count = data_by_monkey.clone().count();
+
+        let mut monkeys_objects = vec![std::collections::VecDeque::new(); monkey_count];
+
+        let mut monkey_circuits: Vec<MonkeyCircuit> = Vec::with_capacity(monkey_count);
+        // Can't find a way around this ugly thing because of unclonable dyn closure.
+        for _ in 0..monkey_circuits.capacity() {
+            monkey_circuits.push(Default::default());
         }
 
-        let (_, operation_str) = monkey_data_as_lines
-            .next()
-            .unwrap()
-            .split_once("new = old")
-            .unwrap();
-        let (op, value) = operation_str.trim().split_once(' ').unwrap();
-        let parsed_value: Result<u64, _> = value.parse();
-
-        let operation: Box<dyn Fn(u64) -> u64> = match (op, parsed_value) {
-            ("*", Ok(value)) => Box::new(move |x: u64| x * value),
-            ("+", Ok(value)) => Box::new(move |x: u64| x + value),
-            ("*", Err(_)) => Box::new(|x: u64| x * x),
-            ("+", Err(_)) => Box::new(|x: u64| x + x),
-            _ => unreachable!(),
+        // For part 2 the squaring makes using big integers is impossible, all the divisors are
+        // conveniently prime numbers, computing their products and keeping only the modulus allows
+        // to perform the divisibility test while keeping very small integers. If the divisors were
+        // not prime we would have needed to find the lowest common multiple of all divisors via a
+        // prime factor decomposition.
+        //
+        // The math property that will be useful (all variables used here are integers):
+        //
+        // if a == b mod c then there exists k such that a = kc + b
+        //
+        // if a == b mod c and d == e mod c then a + d == b + e mod c, i.e. we can add an integer to
+        // another and the resulting modulus is the sum of their moduli mod c, so monkeys can add
+        // values and the modulus will contain the divisibility information. Same goes for
+        // multiplication, which can be seen as a series of additions.
+        //
+        // Proof for addition:
+        // if a == b mod c then there exists k such that a = kc + b
+        // if e == d mod c then there exists l such that d = lc + e
+        // a + d = (k + l) c + b + e, or a + d == b + e mod c.
+        //
+        // Finally if b divides a, i.e. a == 0 mod b and c divides b i.e. b == 0 mod c then c
+        // divides a or a == 0 mod c
+        //
+        // c divides b means there exists a k such that b = kc
+        // b divides a means there exists an l such that a = lb <=> a = klc <=> a = mc where m = kl
+        //
+        // So we can compute the modulus by the products of divisors and as every monkey's dvisior
+        // divides the product of divisor we can just store the modulus of the worry levels to
+        // perform the divisibility test for each monkey.
+        let mut divisor_product: u64 = 1;
+
+        let monkey_index = |idx_str: &str| -> Result<usize, ParseError> {
+            let monkey_idx: usize = parse::number(data, idx_str)?;
+            if monkey_idx >= monkey_count {
+                let expected = format!("a monkey number below {monkey_count}");
+                return Err(ParseError::at(data, idx_str, expected));
+            }
+            Ok(monkey_idx)
         };
 
-        let (_, divisible_by_str) = monkey_data_as_lines
-            .next()
-            .unwrap()
-            .split_once("divisible by")
-            .unwrap();
-        let divisible_by: u64 = divisible_by_str.trim().parse().unwrap();
-
-        divisor_product *= divisible_by;
-
-        let (_, monkey_if_true_str) = monkey_data_as_lines
-            .next()
-            .unwrap()
-            .split_once("throw to monkey")
-            .unwrap();
-        let monkey_if_true: usize = monkey_if_true_str.trim().parse().unwrap();
-
-        let (_, monkey_if_false_str) = monkey_data_as_lines
-            .next()
-            .unwrap()
-            .split_once("throw to monkey")
-            .unwrap();
-        let monkey_if_false: usize = monkey_if_false_str.trim().parse().unwrap();
-
-        monkey_circuits[monkey_idx] =
-            MonkeyCircuit::new(operation, divisible_by, monkey_if_true, monkey_if_false);
+        for monkey_data in data_by_monkey {
+            let mut monkey_data_as_lines = monkey_data.split('\n');
+            let mut next_line = |expected: &str| {
+                let block_end = &monkey_data[monkey_data.len()..];
+                monkey_data_as_lines
+                    .next()
+                    .ok_or_else(|| ParseError::at(data, block_end, expected))
+            };
+
+            let monkey_id_str = parse::strip_prefix(data, next_line("a monkey")?, "Monkey ")?;
+            let (monkey_id_str, _) = parse::split_once(data, monkey_id_str, ":")?;
+            let monkey_idx = monkey_index(monkey_id_str)?;
+
+            let monkey_objects = &mut monkeys_objects[monkey_idx];
+
+            let (_, monkey_objects_str) =
+                parse::split_once(data, next_line("starting items")?, "Starting items:")?;
+            let monkey_objects_str = monkey_objects_str.trim();
+            if !monkey_objects_str.is_empty() {
+                for monkey_object in monkey_objects_str.split(',') {
+                    let worry_level: u64 = parse::number(data, monkey_object.trim())?;
+                    monkey_objects.push_back(worry_level);
+                }
+            }
+
+            let (_, operation_str) =
+                parse::split_once(data, next_line("an operation")?, "new = old ")?;
+            let (op, value) = parse::split_once(data, operation_str.trim(), " ")?;
+            let parsed_value = match value {
+                "old" => None,
+                _ => Some(parse::number::<u64>(data, value)?),
+            };
+
+            let operation: Box<dyn Fn(u64) -> u64> = match (op, parsed_value) {
+                ("*", Some(value)) => Box::new(move |x: u64| x * value),
+                ("+", Some(value)) => Box::new(move |x: u64| x + value),
+                ("*", None) => Box::new(|x: u64| x * x),
+                ("+", None) => Box::new(|x: u64| x + x),
+                _ => return Err(ParseError::at(data, op, "* or +")),
+            };
+
+            let (_, divisible_by_str) =
+                parse::split_once(data, next_line("a test")?, "divisible by")?;
+            let divisible_by: u64 = parse::number(data, divisible_by_str.trim())?;
+
+            divisor_product *= divisible_by;
+
+            let (_, monkey_if_true_str) =
+                parse::split_once(data, next_line("a monkey if true")?, "throw to monkey")?;
+            let monkey_if_true = monkey_index(monkey_if_true_str.trim())?;
+
+            let (_, monkey_if_false_str) =
+                parse::split_once(data, next_line("a monkey if false")?, "throw to monkey")?;
+            let monkey_if_false = monkey_index(monkey_if_false_str.trim())?;
+
+            monkey_circuits[monkey_idx] =
+                MonkeyCircuit::new(operation, divisible_by, monkey_if_true, monkey_if_false);
+        }
+
+        Ok(MonkeyGroup {
+            monkey_circuits,
+            monkeys_objects,
+            divisor_product,
+            rounds: [
+                options.params.get(&ROUNDS_PART_1),
+                options.params.get(&ROUNDS_PART_2),
+            ],
+        })
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        input.monkey_business::<true>(input.rounds[0]).into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        input.monkey_business::<false>(input.rounds[1]).into()
     }
 
-    let mut monkeys_objects_part2 = monkeys_objects_part1.clone();
-    let mut monkey_cumulative_objects_part2 = monkey_cumulative_objects_part1.clone();
-
-    for _ in 0..20 {
-        for (src_monkey_idx, monkey) in monkey_circuits.iter().enumerate() {
-            vprint!("Monkey {src_monkey_idx}:");
-            let actions: Vec<(usize, u64)> = monkeys_objects_part1[src_monkey_idx]
-                .drain(..)
-                .map(|old_worry| {
-                    let res = monkey.process_monkey_brain_part1(old_worry);
-                    monkey_cumulative_objects_part1[src_monkey_idx] += 1;
-                    vprint!(
-                        "    Item with worry level {} is thrown to monkey {}.",
-                        res.1,
-                        res.0
-                    );
-                    (res.0, res.1 % divisor_product)
-                })
-                .collect();
-
-            for (dst_monkey, new_worry) in actions {
-                monkeys_objects_part1[dst_monkey].push_back(new_worry);
+    // `size` items, spread between 2 to 8 monkeys
+    fn generate(rng: &mut Rng, size: usize, params: &Params) -> Option<String> {
+        let rounds: usize = params.get(&ROUNDS_PART_1);
+
+        // As with the puzzle inputs the worry levels of part 1 must stay within u64, try again
+        // until they do
+        let monkeys = loop {
+            let monkeys = random_monkeys(rng, size);
+            if fits_part_1(&monkeys, rounds) {
+                break monkeys;
             }
+        };
+
+        let monkeys: Vec<String> = monkeys
+            .iter()
+            .enumerate()
+            .map(|(idx, monkey)| {
+                let items: Vec<String> = monkey.items.iter().map(u64::to_string).collect();
+                let operand = monkey
+                    .operation
+                    .1
+                    .map_or("old".to_string(), |value| value.to_string());
+
+                format!(
+                    "Monkey {idx}:\n  Starting items: {}\n  Operation: new = old {} {operand}\n  \
+                     Test: divisible by {}\n    If true: throw to monkey {}\n    \
+                     If false: throw to monkey {}\n",
+                    items.join(", "),
+                    monkey.operation.0,
+                    monkey.divisible_by,
+                    monkey.targets[0],
+                    monkey.targets[1]
+                )
+            })
+            .collect();
+
+        Some(monkeys.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day11::parse(EXAMPLE).unwrap();
+        assert_eq!(Day11::part_1(&input), Answer::Number(10605));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day11::parse(EXAMPLE).unwrap();
+        assert_eq!(Day11::part_2(&input), Answer::Number(2713310158));
+
+        // Inspections after the first rounds, from the puzzle statement
+        for (rounds, monkey_business) in [(1, 6 * 4), (20, 103 * 99)] {
+            let mut options = RunOptions::default();
+            options.params.set("rounds_2", rounds);
+            let input = Day11::parse_with(EXAMPLE, &options).unwrap();
+            assert_eq!(Day11::part_2(&input), Answer::Number(monkey_business));
         }
     }
-    vprint!("{monkey_cumulative_objects_part1:?}");
-
-    monkey_cumulative_objects_part1.sort();
-    monkey_cumulative_objects_part1.reverse();
-
-    let monkey_business = monkey_cumulative_objects_part1[0] * monkey_cumulative_objects_part1[1];
-
-    println!("Part 1: {monkey_business}");
-
-    for _ in 0..10000 {
-        for (src_monkey_idx, monkey) in monkey_circuits.iter().enumerate() {
-            vprint!("Monkey {src_monkey_idx}:");
-            let actions: Vec<(usize, u64)> = monkeys_objects_part2[src_monkey_idx]
-                .drain(..)
-                .map(|old_worry| {
-                    let res = monkey.process_monkey_brain_part2(old_worry);
-                    monkey_cumulative_objects_part2[src_monkey_idx] += 1;
-                    vprint!(
-                        "    Item with worry level {} is thrown to monkey {}.",
-                        res.1,
-                        res.0
-                    );
-                    (res.0, res.1 % divisor_product)
-                })
-                .collect();
-
-            for (dst_monkey, new_worry) in actions {
-                monkeys_objects_part2[dst_monkey].push_back(new_worry);
+
+    /// Worry level kept whole, no modulus involved, as base 2^32 digits, least significant first
+    #[derive(Debug, Clone)]
+    struct Worry(Vec<u32>);
+
+    impl Worry {
+        fn mul(&self, other: &Worry) -> Worry {
+            let mut digits = vec![0u64; self.0.len() + other.0.len() + 1];
+            for (i, &a) in self.0.iter().enumerate() {
+                let mut carry = 0;
+                for (j, &b) in other.0.iter().enumerate() {
+                    let total = digits[i + j] + a as u64 * b as u64 + carry;
+                    digits[i + j] = total & u32::MAX as u64;
+                    carry = total >> 32;
+                }
+                let mut k = i + other.0.len();
+                while carry != 0 {
+                    let total = digits[k] + carry;
+                    digits[k] = total & u32::MAX as u64;
+                    carry = total >> 32;
+                    k += 1;
+                }
+            }
+            Worry(digits.into_iter().map(|digit| digit as u32).collect()).trimmed()
+        }
+
+        fn add(&self, value: u32) -> Worry {
+            let mut digits = self.0.clone();
+            digits.push(0);
+            let mut carry = value as u64;
+            for digit in &mut digits {
+                let total = *digit as u64 + carry;
+                *digit = total as u32;
+                carry = total >> 32;
+            }
+            Worry(digits).trimmed()
+        }
+
+        /// Quotient and remainder
+        fn div(&self, divisor: u32) -> (Worry, u32) {
+            let mut quotient = vec![0; self.0.len()];
+            let mut remainder = 0u64;
+            for (idx, &digit) in self.0.iter().enumerate().rev() {
+                let current = (remainder << 32) | digit as u64;
+                quotient[idx] = (current / divisor as u64) as u32;
+                remainder = current % divisor as u64;
             }
+            (Worry(quotient).trimmed(), remainder as u32)
+        }
+
+        fn trimmed(mut self) -> Worry {
+            while self.0.len() > 1 && self.0.last() == Some(&0) {
+                self.0.pop();
+            }
+            self
         }
     }
 
-    vprint!("{monkey_cumulative_objects_part2:?}");
+    /// Monkey business of the monkeys described by `data`, straight from the puzzle statement
+    fn reference_monkey_business(data: &str, rounds: usize, relief: bool) -> usize {
+        let last_word = |line: &str| line.rsplit(' ').next().unwrap().to_string();
+        let monkeys: Vec<Vec<&str>> = data
+            .trim()
+            .split("\n\n")
+            .map(|monkey| monkey.lines().collect())
+            .collect();
+
+        let mut items: Vec<Vec<Worry>> = monkeys
+            .iter()
+            .map(|lines| {
+                let (_, items) = lines[1].split_once(':').unwrap();
+                items
+                    .split(',')
+                    .filter(|item| !item.trim().is_empty())
+                    .map(|item| Worry(vec![item.trim().parse().unwrap()]))
+                    .collect()
+            })
+            .collect();
+        let mut inspections = vec![0; monkeys.len()];
+
+        for _ in 0..rounds {
+            for (monkey_idx, lines) in monkeys.iter().enumerate() {
+                let operand = last_word(lines[2]);
+                let divisor: u32 = last_word(lines[3]).parse().unwrap();
+                let if_true: usize = last_word(lines[4]).parse().unwrap();
+                let if_false: usize = last_word(lines[5]).parse().unwrap();
+
+                for item in std::mem::take(&mut items[monkey_idx]) {
+                    inspections[monkey_idx] += 1;
+
+                    let mut worry = match (lines[2].contains('*'), operand.as_str()) {
+                        (true, "old") => item.mul(&item),
+                        (false, "old") => item.mul(&Worry(vec![2])),
+                        (true, value) => item.mul(&Worry(vec![value.parse().unwrap()])),
+                        (false, value) => item.add(value.parse().unwrap()),
+                    };
+                    if relief {
+                        worry = worry.div(3).0;
+                    }
+
+                    let target = match worry.div(divisor).1 {
+                        0 => if_true,
+                        _ => if_false,
+                    };
+                    items[target].push(worry);
+                }
+            }
+        }
 
-    monkey_cumulative_objects_part2.sort();
-    monkey_cumulative_objects_part2.reverse();
+        inspections.sort();
+        inspections.reverse();
+        inspections[0] * inspections[1]
+    }
+
+    #[test]
+    fn matches_reference() {
+        // Few rounds of part 2 so that the exact worry levels stay small enough to compute
+        let mut options = RunOptions::default();
+        options.params.set("rounds_2", 5);
 
-    let monkey_business = monkey_cumulative_objects_part2[0] * monkey_cumulative_objects_part2[1];
+        for seed in 0..50 {
+            let data = Day11::generate(&mut Rng::new(seed), 10, &options.params).unwrap();
+            let input = Day11::parse_with(&data, &options).unwrap();
 
-    println!("Part 2: {monkey_business}");
+            let expected = reference_monkey_business(&data, 20, true);
+            assert_eq!(Day11::part_1(&input), Answer::from(expected), "seed {seed}");
+            let expected = reference_monkey_business(&data, 5, false);
+            assert_eq!(Day11::part_2(&input), Answer::from(expected), "seed {seed}");
+        }
+    }
 }
@@ -1,4 +1,8 @@
-use super::load_file;
+use super::log::debug;
+use super::utils::grid::{Grid, Pos};
+use super::utils::rng::Rng;
+use super::utils::search;
+use super::{Answer, Params, ParseError, Solution};
 
 /// --- Day 12: Hill Climbing Algorithm ---
 /// You try contacting the Elves using your handheld device, but the river you're following must be
@@ -74,213 +78,202 @@ use super::load_file;
 ///
 /// What is the fewest steps required to move starting from any square with elevation a to the
 /// location that should get the best signal?
-pub fn day_12() {
-    let data = load_file(12);
+pub struct Day12;
 
-    let data_as_lines = data.trim().split('\n');
-    let line_count = data_as_lines.clone().count();
-
-    let mut elevation_map: Vec<i32> = vec![];
-
-    #[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
-    struct Pos {
-        pub x: usize,
-        pub y: usize,
-    }
+pub struct HeightMap {
+    pub elevation_map: Grid<i32>,
+    pub start_pos: Pos,
+    pub goal_pos: Pos,
+}
 
-    const VERBOSE: bool = false;
+impl HeightMap {
+    /// Squares from which `to` can be climbed, at most one higher. Searches start from the goal and
+    /// go backwards so that part 2 can stop at the first square at elevation `a`.
+    fn climbed_from(&self, to: &Pos) -> impl Iterator<Item = Pos> + '_ {
+        let to_elevation = self.elevation_map[*to];
 
-    macro_rules! vprint {
-        ($($x:tt)*) => { if VERBOSE { println!($($x)*); } }
+        self.elevation_map
+            .neighbours_4(*to)
+            .filter(move |&from| to_elevation - self.elevation_map[from] <= 1)
     }
 
-    let mut start_pos = Pos { x: 0, y: 0 };
-    let mut goal_pos = Pos { x: 0, y: 0 };
-
-    for (line_idx, line) in data_as_lines.enumerate() {
-        for (column_idx, c) in line.char_indices() {
-            match c {
-                'S' => {
-                    start_pos.x = column_idx;
-                    start_pos.y = line_idx;
-                    elevation_map.push(0);
-                }
-                'E' => {
-                    goal_pos.x = column_idx;
-                    goal_pos.y = line_idx;
-                    elevation_map.push(25);
-                }
-                c => {
-                    assert!(c.is_ascii_lowercase());
-                    let elevation = c as u8 - b'a';
-                    let elevation: i32 = elevation.into();
-                    elevation_map.push(elevation);
-                }
-            }
+    /// Map with the squares of `path`, as `#`
+    fn render(&self, path: &[Pos]) -> Grid<char> {
+        let mut visualization = self.elevation_map.map(|_| '.');
+        for &pos in path {
+            visualization[pos] = '#';
         }
+        visualization
     }
+}
 
-    let column_count = elevation_map.len() / line_count;
-
-    let get_neighbours = |current: &Pos| -> Vec<Pos> {
-        let mut neighbours: Vec<Pos> = vec![];
-        let curr_x = current.x;
-        let curr_y = current.y;
-        if curr_x > 0 {
-            neighbours.push(Pos {
-                x: curr_x - 1,
-                y: curr_y,
-            });
-        }
-        if curr_x < column_count - 1 {
-            neighbours.push(Pos {
-                x: curr_x + 1,
-                y: curr_y,
-            });
-        }
-        if curr_y > 0 {
-            neighbours.push(Pos {
-                x: curr_x,
-                y: curr_y - 1,
-            });
-        }
-        if curr_y < line_count - 1 {
-            neighbours.push(Pos {
-                x: curr_x,
-                y: curr_y + 1,
-            });
-        }
-
-        let can_go_to = |from: &Pos, to: &Pos| -> bool {
-            let from_idx = from.x + from.y * column_count;
-            let from_elevation = elevation_map[from_idx];
-
-            let to_idx = to.x + to.y * column_count;
-            let to_elevation = elevation_map[to_idx];
-
-            let elevation_diff = to_elevation - from_elevation;
-
-            // Reversed when starting from the top
-            elevation_diff >= -1
-        };
-
-        let neighbours = neighbours
-            .iter()
-            .filter(|&x| can_go_to(current, x))
-            .map(|x| x.to_owned())
-            .collect();
-
-        neighbours
-    };
+impl Solution for Day12 {
+    const DAY: usize = 12;
 
-    fn reconstruct_path(
-        came_from: &std::collections::HashMap<Pos, Pos>,
-        mut current: Pos,
-    ) -> Vec<Pos> {
-        let mut total_path = vec![current];
+    type Input = HeightMap;
 
-        while let Some(&from) = came_from.get(&current) {
-            total_path.push(from);
-            current = from;
-        }
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        let mut start_pos = None;
+        let mut goal_pos = None;
 
-        total_path.reverse();
-        total_path
-    }
-
-    fn h(from: Pos, to: Pos) -> usize {
-        from.x.abs_diff(to.x) + from.y.abs_diff(to.y)
+        let elevation_map = Grid::parse(data, "an elevation, S or E", |pos, c| match c {
+            'S' => {
+                start_pos = Some(pos);
+                Some(0)
+            }
+            'E' => {
+                goal_pos = Some(pos);
+                Some(25)
+            }
+            'a'..='z' => Some((c as u8 - b'a').into()),
+            _ => None,
+        })?;
+
+        Ok(HeightMap {
+            elevation_map,
+            start_pos: start_pos.ok_or_else(|| ParseError::at_end(data, "a start S"))?,
+            goal_pos: goal_pos.ok_or_else(|| ParseError::at_end(data, "a goal E"))?,
+        })
     }
 
-    // Let's use smarter people algorithms
-    let a_star = |start: Pos, goal: Pos| -> Option<Vec<Pos>> {
-        let mut counter = 0usize;
-
-        let mut visualization = vec!['.'; elevation_map.len()];
-
-        let mut open_set = std::collections::HashSet::from([start]);
-        let mut came_from = std::collections::HashMap::<Pos, Pos, _>::new();
-        let mut distance_travelled = std::collections::HashMap::<Pos, usize, _>::new();
-        distance_travelled.insert(start, 0);
-
-        let mut estimated_distance = std::collections::HashMap::<Pos, usize, _>::new();
-        estimated_distance.insert(start, h(start, goal));
+    fn part_1(input: &Self::Input) -> Answer {
+        let start = input.start_pos;
 
-        while !open_set.is_empty() {
-            vprint!("Counter: {counter}");
-            counter += 1;
-
-            let current =
-                estimated_distance
-                    .iter()
-                    .fold((Pos::default(), usize::MAX), |curr, x| {
-                        if !open_set.contains(x.0) {
-                            return curr;
-                        }
-
-                        if *x.1 < curr.1 {
-                            (x.0.to_owned(), *x.1)
-                        } else {
-                            curr
-                        }
-                    });
+        let path = search::astar(
+            [input.goal_pos],
+            |pos| input.climbed_from(pos).map(|from| (from, 1)),
+            |pos| pos.manhattan(start),
+            |&pos| pos == start,
+        )
+        .unwrap();
 
-            let current = current.0;
+        debug!("{}", input.render(&path.nodes));
 
-            vprint!("Processing: {current:?}");
+        path.cost.into()
+    }
 
-            visualization[current.x + current.y * column_count] = '#';
+    fn part_2(input: &Self::Input) -> Answer {
+        let path = search::bfs(
+            [input.goal_pos],
+            |pos| input.climbed_from(pos),
+            |&pos| input.elevation_map[pos] == 0,
+        )
+        .unwrap();
 
-            let newline = ['\n'];
-            let display: String = visualization
-                .chunks(column_count)
-                .flat_map(|x| x.iter().chain(newline.iter()))
-                .collect();
+        debug!("{}", input.render(&path.nodes));
 
-            vprint!("\n\n{display}\n\n");
+        path.cost.into()
+    }
 
-            if current == goal {
-                return Some(reconstruct_path(&came_from, current));
+    // `size` columns and a quarter as many rows, at least enough for a path from a to z
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let (width, height) = (size.max(26), (size / 4).max(5));
+
+        loop {
+            // Rocks the path has to go around, they get a random elevation
+            let cells = (0..width * height).map(|_| rng.chance(0.2)).collect();
+            let mut rocks = Grid::from_cells(width, cells);
+            let goal = Pos::new(rng.range(0..width), rng.range(0..height));
+            rocks[goal] = false;
+
+            let steps = search::distances([goal], |&pos| {
+                rocks
+                    .neighbours_4(pos)
+                    .filter(|&next| !rocks[next])
+                    .collect::<Vec<_>>()
+            });
+            let farthest = steps.values().copied().max().unwrap_or_default();
+            if farthest < 25 {
+                continue;
             }
 
-            open_set.remove(&current);
-
-            let neighbours = get_neighbours(&current);
+            // The elevation drops by one every `spacing` steps away from the goal
+            let spacing = rng.range(1..=farthest / 25);
+            let mut lowest: Vec<Pos> = steps
+                .iter()
+                .filter(|(_, &distance)| distance >= 25 * spacing)
+                .map(|(&pos, _)| pos)
+                .collect();
+            lowest.sort();
+            let start = *rng.choose(&lowest);
+
+            let cells = rocks
+                .iter()
+                .map(|(pos, _)| match steps.get(&pos) {
+                    _ if pos == start => 'S',
+                    _ if pos == goal => 'E',
+                    Some(steps) => (b'z' - (steps / spacing).min(25) as u8) as char,
+                    None => rng.range(b'a'..=b'z') as char,
+                })
+                .collect();
 
-            for neighbour in neighbours {
-                let tentative_distance_travelled = *distance_travelled.get(&current).unwrap() + 1;
+            return Some(Grid::from_cells(width, cells).to_string());
+        }
+    }
+}
 
-                let neigh_distance = distance_travelled.entry(neighbour).or_insert(usize::MAX);
-                if tentative_distance_travelled < *neigh_distance {
-                    came_from.insert(neighbour, current);
-                    *neigh_distance = tentative_distance_travelled;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day12::parse(EXAMPLE).unwrap();
+        assert_eq!(Day12::part_1(&input), Answer::Number(31));
+    }
 
-                    let estimated_total_distance =
-                        tentative_distance_travelled + h(neighbour, goal);
-                    estimated_distance.insert(neighbour, estimated_total_distance);
+    #[test]
+    fn part_2_example() {
+        let input = Day12::parse(EXAMPLE).unwrap();
+        assert_eq!(Day12::part_2(&input), Answer::Number(29));
+    }
 
-                    open_set.insert(neighbour);
+    /// Fewest steps from `start` to the goal, climbing forward one square at a time
+    fn reference_steps(input: &HeightMap, start: Pos) -> Option<usize> {
+        let map = &input.elevation_map;
+        let mut steps = map.map(|_| None);
+        steps[start] = Some(0);
+        let mut queue = std::collections::VecDeque::from([start]);
+
+        while let Some(pos) = queue.pop_front() {
+            let next_steps = steps[pos].map(|steps| steps + 1);
+            for next in map.neighbours_4(pos) {
+                if map[next] - map[pos] <= 1 && steps[next].is_none() {
+                    steps[next] = next_steps;
+                    queue.push_back(next);
                 }
             }
         }
 
-        None
-    };
-
-    let result = a_star(goal_pos, start_pos).unwrap();
-
-    // Here we cheat, only starting positions that can work are the ones on the left
-    let part2_estimations: Vec<_> = (0..line_count)
-        .map(|y| a_star(goal_pos, Pos { x: 0, y }).unwrap())
-        .collect();
-
-    let best_path = part2_estimations
-        .into_iter()
-        .min_by(|x, y| x.len().cmp(&y.len()))
-        .unwrap();
-
-    // -1 to remove starting node
-    println!("Part 1: {}", result.len() - 1);
+        steps[input.goal_pos]
+    }
 
-    println!("Part 2: {}", best_path.len() - 1);
+    #[test]
+    fn matches_reference() {
+        for seed in 0..20 {
+            let data = Day12::generate(&mut Rng::new(seed), 30, &Params::default()).unwrap();
+            let input = Day12::parse(&data).unwrap();
+
+            let expected = reference_steps(&input, input.start_pos).unwrap();
+            assert_eq!(Day12::part_1(&input), Answer::from(expected), "seed {seed}");
+
+            // From every square at elevation a
+            let expected = input
+                .elevation_map
+                .iter()
+                .filter(|&(_, &elevation)| elevation == 0)
+                .filter_map(|(pos, _)| reference_steps(&input, pos))
+                .min()
+                .unwrap();
+            assert_eq!(Day12::part_2(&input), Answer::from(expected), "seed {seed}");
+        }
+    }
 }
@@ -1,4 +1,8 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, Solution};
+use std::cmp::Ordering;
+use std::str::FromStr;
 
 /// --- Day 13: Distress Signal ---
 /// You climb the hill and again try contacting the Elves. However, you instead receive a signal you
@@ -165,192 +169,296 @@ use super::load_file;
 ///
 /// Organize all of the packets into the correct order. What is the decoder key for the distress
 /// signal?
-pub fn day_13() {
-    let data = load_file(13);
+pub struct Day13;
 
-    let packet_pairs_str = data.trim().split("\n\n");
-
-    use std::str::FromStr;
+#[derive(Debug, Clone)]
+pub enum MixedItem {
+    Integer(u32),
+    IntergerList(Vec<u32>),
+    NestedList(Vec<MixedItem>),
+}
 
-    #[derive(Debug, Clone)]
-    enum MixedItem {
-        Integer(u32),
-        IntergerList(Vec<u32>),
-        NestedList(Vec<MixedItem>),
+impl MixedItem {
+    pub fn is_integer(&self) -> bool {
+        matches!(self, MixedItem::Integer(_))
     }
+}
 
-    impl MixedItem {
-        pub fn is_integer(&self) -> bool {
-            matches!(self, MixedItem::Integer(_))
+impl FromStr for MixedItem {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "[]" {
+            return Ok(MixedItem::IntergerList(vec![]));
         }
-    }
 
-    impl FromStr for MixedItem {
-        type Err = String;
+        if !s.starts_with('[') {
+            return Ok(MixedItem::Integer(parse::number(s, s)?));
+        }
 
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            if s == "[]" {
-                return Ok(MixedItem::IntergerList(vec![]));
-            }
+        // Remove encasing []
+        let list_content = parse::strip_prefix(s, s, "[")?
+            .strip_suffix(']')
+            .ok_or_else(|| ParseError::at_end(s, "\"]\""))?;
 
-            let integer = s.parse();
-            if let Ok(integer) = integer {
-                return Ok(MixedItem::Integer(integer));
-            }
+        let mut bracket_depth = 0;
+        let mut past_previous_comma_or_start_byte_idx = 0;
+
+        let mut items_str: Vec<&str> = vec![];
 
-            // Remove encasing []
-            let list_content = s.strip_prefix('[').unwrap().strip_suffix(']').unwrap();
-
-            let mut bracket_depth = 0;
-            let mut past_previous_comma_or_start_byte_idx = 0;
-
-            let mut items_str: Vec<&str> = vec![];
-
-            for (byte_idx, c) in list_content.char_indices() {
-                match c {
-                    '[' => bracket_depth += 1,
-                    ']' => bracket_depth -= 1,
-                    ',' => {
-                        if bracket_depth == 0 {
-                            let byte_range = past_previous_comma_or_start_byte_idx..byte_idx;
-                            past_previous_comma_or_start_byte_idx = byte_idx + 1;
-                            items_str.push(&list_content[byte_range]);
-                        }
-                    }
-                    _ => (),
+        for (byte_idx, c) in list_content.char_indices() {
+            match c {
+                '[' => bracket_depth += 1,
+                ']' if bracket_depth == 0 => {
+                    let found = &list_content[byte_idx..byte_idx + 1];
+                    return Err(ParseError::at(s, found, "a list item"));
                 }
-                // Manage last part that could be skipeed
-                if byte_idx + 1 == list_content.len() {
-                    let byte_range = past_previous_comma_or_start_byte_idx..byte_idx + 1;
+                ']' => bracket_depth -= 1,
+                ',' if bracket_depth == 0 => {
+                    let byte_range = past_previous_comma_or_start_byte_idx..byte_idx;
+                    past_previous_comma_or_start_byte_idx = byte_idx + 1;
                     items_str.push(&list_content[byte_range]);
                 }
+                _ => (),
             }
+            // Manage last part that could be skipeed
+            if byte_idx + 1 == list_content.len() {
+                let byte_range = past_previous_comma_or_start_byte_idx..byte_idx + 1;
+                items_str.push(&list_content[byte_range]);
+            }
+        }
 
-            let items_str = items_str;
+        if bracket_depth != 0 {
+            return Err(ParseError::at_end(s, "\"]\""));
+        }
 
-            let nested_list_content: Vec<MixedItem> = items_str
-                .into_iter()
-                .map(|item_str| item_str.parse().unwrap())
-                .collect();
+        let items_str = items_str;
 
-            if nested_list_content.iter().all(|elt| elt.is_integer()) {
-                let integer_list_content: Vec<u32> = nested_list_content
-                    .into_iter()
-                    .map(|elt| match elt {
-                        MixedItem::Integer(inner) => inner,
-                        _ => unreachable!(),
-                    })
-                    .collect();
+        let nested_list_content: Vec<MixedItem> = items_str
+            .into_iter()
+            .map(|item_str| {
+                item_str
+                    .parse()
+                    .map_err(|err: ParseError| err.within(s, item_str))
+            })
+            .collect::<Result<_, _>>()?;
 
-                return Ok(MixedItem::IntergerList(integer_list_content));
-            }
+        if nested_list_content.iter().all(|elt| elt.is_integer()) {
+            let integer_list_content: Vec<u32> = nested_list_content
+                .into_iter()
+                .map(|elt| match elt {
+                    MixedItem::Integer(inner) => inner,
+                    _ => unreachable!(),
+                })
+                .collect();
 
-            Ok(MixedItem::NestedList(nested_list_content))
+            return Ok(MixedItem::IntergerList(integer_list_content));
         }
+
+        Ok(MixedItem::NestedList(nested_list_content))
     }
+}
 
-    use std::cmp::Ordering;
-
-    fn cmp_mixed_item(lhs: &MixedItem, rhs: &MixedItem) -> Ordering {
-        match (lhs, rhs) {
-            (MixedItem::Integer(lhs), MixedItem::Integer(rhs)) => lhs.cmp(rhs),
-            (MixedItem::IntergerList(lhs), MixedItem::IntergerList(rhs)) => {
-                let size_cmp = lhs.len().cmp(&rhs.len());
-                for (lhs, rhs) in lhs.iter().zip(rhs.iter()) {
-                    if lhs < rhs {
-                        return Ordering::Less;
-                    }
-
-                    if lhs > rhs {
-                        return Ordering::Greater;
-                    }
+pub fn cmp_mixed_item(lhs: &MixedItem, rhs: &MixedItem) -> Ordering {
+    match (lhs, rhs) {
+        (MixedItem::Integer(lhs), MixedItem::Integer(rhs)) => lhs.cmp(rhs),
+        (MixedItem::IntergerList(lhs), MixedItem::IntergerList(rhs)) => {
+            let size_cmp = lhs.len().cmp(&rhs.len());
+            for (lhs, rhs) in lhs.iter().zip(rhs.iter()) {
+                if lhs < rhs {
+                    return Ordering::Less;
                 }
 
-                size_cmp
-            }
-            (MixedItem::Integer(lhs), MixedItem::IntergerList(_)) => {
-                cmp_mixed_item(&MixedItem::IntergerList(vec![*lhs]), rhs)
-            }
-            (MixedItem::IntergerList(_), MixedItem::Integer(rhs)) => {
-                cmp_mixed_item(lhs, &MixedItem::IntergerList(vec![*rhs]))
-            }
-            (MixedItem::Integer(lhs), MixedItem::NestedList(_)) => {
-                cmp_mixed_item(&MixedItem::IntergerList(vec![*lhs]), rhs)
-            }
-            (MixedItem::NestedList(_), MixedItem::Integer(rhs)) => {
-                cmp_mixed_item(lhs, &MixedItem::IntergerList(vec![*rhs]))
-            }
-            (MixedItem::NestedList(lhs), MixedItem::NestedList(rhs)) => {
-                let size_cmp = lhs.len().cmp(&rhs.len());
-                for (lhs, rhs) in lhs.iter().zip(rhs.iter()) {
-                    let cmp = cmp_mixed_item(lhs, rhs);
-                    if cmp != Ordering::Equal {
-                        return cmp;
-                    }
+                if lhs > rhs {
+                    return Ordering::Greater;
                 }
-
-                size_cmp
             }
-            (MixedItem::IntergerList(lhs), MixedItem::NestedList(rhs)) => {
-                let size_cmp = lhs.len().cmp(&rhs.len());
-                for (&lhs, rhs) in lhs.iter().zip(rhs.iter()) {
-                    let cmp = cmp_mixed_item(&MixedItem::Integer(lhs), rhs);
-                    if cmp != Ordering::Equal {
-                        return cmp;
-                    }
-                }
 
-                size_cmp
+            size_cmp
+        }
+        (MixedItem::Integer(lhs), MixedItem::IntergerList(_)) => {
+            cmp_mixed_item(&MixedItem::IntergerList(vec![*lhs]), rhs)
+        }
+        (MixedItem::IntergerList(_), MixedItem::Integer(rhs)) => {
+            cmp_mixed_item(lhs, &MixedItem::IntergerList(vec![*rhs]))
+        }
+        (MixedItem::Integer(lhs), MixedItem::NestedList(_)) => {
+            cmp_mixed_item(&MixedItem::IntergerList(vec![*lhs]), rhs)
+        }
+        (MixedItem::NestedList(_), MixedItem::Integer(rhs)) => {
+            cmp_mixed_item(lhs, &MixedItem::IntergerList(vec![*rhs]))
+        }
+        (MixedItem::NestedList(lhs), MixedItem::NestedList(rhs)) => {
+            let size_cmp = lhs.len().cmp(&rhs.len());
+            for (lhs, rhs) in lhs.iter().zip(rhs.iter()) {
+                let cmp = cmp_mixed_item(lhs, rhs);
+                if cmp != Ordering::Equal {
+                    return cmp;
+                }
             }
-            (MixedItem::NestedList(lhs), MixedItem::IntergerList(rhs)) => {
-                let size_cmp = lhs.len().cmp(&rhs.len());
-                for (lhs, &rhs) in lhs.iter().zip(rhs.iter()) {
-                    let cmp = cmp_mixed_item(lhs, &MixedItem::Integer(rhs));
-                    if cmp != Ordering::Equal {
-                        return cmp;
-                    }
+
+            size_cmp
+        }
+        (MixedItem::IntergerList(lhs), MixedItem::NestedList(rhs)) => {
+            let size_cmp = lhs.len().cmp(&rhs.len());
+            for (&lhs, rhs) in lhs.iter().zip(rhs.iter()) {
+                let cmp = cmp_mixed_item(&MixedItem::Integer(lhs), rhs);
+                if cmp != Ordering::Equal {
+                    return cmp;
                 }
+            }
 
-                size_cmp
+            size_cmp
+        }
+        (MixedItem::NestedList(lhs), MixedItem::IntergerList(rhs)) => {
+            let size_cmp = lhs.len().cmp(&rhs.len());
+            for (lhs, &rhs) in lhs.iter().zip(rhs.iter()) {
+                let cmp = cmp_mixed_item(lhs, &MixedItem::Integer(rhs));
+                if cmp != Ordering::Equal {
+                    return cmp;
+                }
             }
+
+            size_cmp
         }
     }
+}
 
-    let mut ordered_pair_index_sum = 0;
+/// List of integers and lists, nested at most `depth` more levels
+fn random_packet(rng: &mut Rng, depth: usize) -> String {
+    let items: Vec<String> = (0..rng.range(0..=4))
+        .map(|_| match depth > 0 && rng.chance(0.3) {
+            true => random_packet(rng, depth - 1),
+            false => rng.range(0..=10).to_string(),
+        })
+        .collect();
 
-    let first_divider = MixedItem::from_str("[[2]]").unwrap();
-    let second_divider = MixedItem::from_str("[[6]]").unwrap();
+    format!("[{}]", items.join(","))
+}
 
-    let mut all_packets: Vec<MixedItem> = vec![first_divider.clone(), second_divider.clone()];
+impl Solution for Day13 {
+    const DAY: usize = 13;
 
-    for (pair_idx, packet_pair_str) in packet_pairs_str.enumerate() {
-        let (first_packet, second_packet) = packet_pair_str.trim().split_once('\n').unwrap();
+    type Input = Vec<(MixedItem, MixedItem)>;
 
-        let first_packet: MixedItem = first_packet.parse().unwrap();
-        let second_packet: MixedItem = second_packet.parse().unwrap();
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        let packet_pairs_str = data.trim().split("\n\n");
 
-        all_packets.push(first_packet.clone());
-        all_packets.push(second_packet.clone());
+        packet_pairs_str
+            .map(|packet_pair_str| {
+                let (first_packet, second_packet) =
+                    parse::split_once(data, packet_pair_str.trim(), "\n")?;
 
-        let cmp = cmp_mixed_item(&first_packet, &second_packet);
+                let first_packet: MixedItem = first_packet
+                    .parse()
+                    .map_err(|err: ParseError| err.within(data, first_packet))?;
+                let second_packet: MixedItem = second_packet
+                    .parse()
+                    .map_err(|err: ParseError| err.within(data, second_packet))?;
 
-        if cmp == Ordering::Less {
-            ordered_pair_index_sum += pair_idx + 1;
+                Ok((first_packet, second_packet))
+            })
+            .collect()
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        let mut ordered_pair_index_sum = 0;
+
+        for (pair_idx, (first_packet, second_packet)) in input.iter().enumerate() {
+            let cmp = cmp_mixed_item(first_packet, second_packet);
+
+            if cmp == Ordering::Less {
+                ordered_pair_index_sum += pair_idx + 1;
+            }
         }
+
+        ordered_pair_index_sum.into()
     }
 
-    all_packets.sort_by(cmp_mixed_item);
+    fn part_2(input: &Self::Input) -> Answer {
+        let first_divider = MixedItem::from_str("[[2]]").unwrap();
+        let second_divider = MixedItem::from_str("[[6]]").unwrap();
+
+        let mut all_packets: Vec<MixedItem> = vec![first_divider.clone(), second_divider.clone()];
 
-    let decoder_key = all_packets.iter().enumerate().fold(1, |acc, (idx, x)| {
-        if cmp_mixed_item(x, &first_divider) == Ordering::Equal
-            || cmp_mixed_item(x, &second_divider) == Ordering::Equal
-        {
-            acc * (idx + 1)
-        } else {
-            acc
+        for (first_packet, second_packet) in input {
+            all_packets.push(first_packet.clone());
+            all_packets.push(second_packet.clone());
         }
-    });
 
-    println!("Part 1: {ordered_pair_index_sum}");
-    println!("Part 2: {decoder_key}");
+        all_packets.sort_by(cmp_mixed_item);
+
+        let decoder_key = all_packets.iter().enumerate().fold(1, |acc, (idx, x)| {
+            if cmp_mixed_item(x, &first_divider) == Ordering::Equal
+                || cmp_mixed_item(x, &second_divider) == Ordering::Equal
+            {
+                acc * (idx + 1)
+            } else {
+                acc
+            }
+        });
+
+        decoder_key.into()
+    }
+
+    // `size` pairs of packets
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let pairs: Vec<String> = (0..size)
+            .map(|_| format!("{}\n{}\n", random_packet(rng, 4), random_packet(rng, 4)))
+            .collect();
+
+        Some(pairs.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
+
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day13::parse(EXAMPLE).unwrap();
+        assert_eq!(Day13::part_1(&input), Answer::Number(13));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day13::parse(EXAMPLE).unwrap();
+        assert_eq!(Day13::part_2(&input), Answer::Number(140));
+    }
+
+    #[test]
+    fn invalid_packet() {
+        let err = Day13::parse("[1,[2,3]\n[4]\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 9));
+
+        let err = Day13::parse("[1]\n[[4],x]\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 6, "x"));
+    }
 }
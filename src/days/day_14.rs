@@ -1,4 +1,10 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::parse;
+use super::utils::grid::{self, Grid};
+use super::utils::rng::Rng;
+use super::utils::vector::{Direction8, Vec2};
+use super::{Answer, Params, ParseError, RunOptions, Solution};
+use std::str::FromStr;
 
 /// --- Day 14: Regolith Reservoir ---
 /// The distress signal leads you to a giant waterfall! Actually, hang on - the signal seems like
@@ -181,320 +187,254 @@ use super::load_file;
 /// #########################
 /// Using your scan, simulate the falling sand until the source of the sand becomes blocked. How
 /// many units of sand come to rest?
-pub fn day_14() {
-    let data = load_file(14);
+pub struct Day14;
 
-    let data_as_lines = data.trim().split('\n');
+/// `x,y`
+fn parse_pos(s: &str, token: &str) -> Result<Vec2<i32>, ParseError> {
+    let (x, y) = parse::split_once(s, token.trim(), ",")?;
 
-    use std::str::FromStr;
+    Ok(Vec2::new(parse::number(s, x)?, parse::number(s, y)?))
+}
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    struct Pos {
-        x: i32,
-        y: i32,
-    }
+#[derive(Debug, Clone)]
+pub struct RockPath {
+    pub path: Vec<Vec2<i32>>,
+}
 
-    impl Pos {
-        pub fn get_idx(&self, w: i32) -> usize {
-            (self.x + self.y * w).try_into().unwrap()
-        }
+impl FromStr for RockPath {
+    type Err = ParseError;
 
-        pub fn inside(&self, w: i32, h: i32) -> bool {
-            self.x >= 0 && self.x < w && self.y >= 0 && self.y < h
-        }
-    }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut path: Vec<Vec2<i32>> = Vec::new();
 
-    impl FromStr for Pos {
-        type Err = Box<dyn std::error::Error>;
+        for pos_str in s.trim().split("->") {
+            let pos = parse_pos(s, pos_str)?;
 
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let (x, y) = s.trim().split_once(',').unwrap();
+            // Paths are only made of horizontal and vertical lines
+            if let Some(prev) = path.last() {
+                if prev.x != pos.x && prev.y != pos.y {
+                    let expected = format!("a point in line with {},{}", prev.x, prev.y);
+                    return Err(ParseError::at(s, pos_str.trim(), expected));
+                }
+            }
 
-            Ok(Self {
-                x: x.parse()?,
-                y: y.parse()?,
-            })
+            path.push(pos);
         }
-    }
-
-    #[derive(Debug, Clone)]
-    struct RockPath {
-        pub path: Vec<Pos>,
-    }
 
-    impl IntoIterator for RockPath {
-        type Item = Pos;
-        type IntoIter = <Vec<Pos> as IntoIterator>::IntoIter;
-        fn into_iter(self) -> Self::IntoIter {
-            self.path.into_iter()
-        }
+        Ok(Self { path })
     }
+}
 
-    impl FromStr for RockPath {
-        type Err = Box<dyn std::error::Error>;
+#[derive(Debug, Clone)]
+pub struct Cave {
+    /// Rocks drawn with `#`, sand at rest with `o`
+    pub map: Grid<char>,
+    /// Where the sand comes from
+    pub source: grid::Pos,
+}
 
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let path: Vec<Pos> = s.trim().split("->").map(|x| x.parse().unwrap()).collect();
+impl Cave {
+    /// Cave spanning from `min_x` to `max_x`, `height` rows deep
+    fn new(rock_paths: &[RockPath], min_x: i32, max_x: i32, height: i32) -> Self {
+        let to_usize = |coord: i32| -> usize { coord.try_into().unwrap() };
+        let mut map = Grid::new(to_usize(max_x - min_x + 1), to_usize(height), '.');
+
+        let origin = Vec2::new(min_x, 0);
+        let position = |pos: Vec2<i32>| -> grid::Pos { (pos - origin).try_cast().unwrap() };
+
+        for rock_path in rock_paths {
+            for (&start, &end) in rock_path.path.iter().zip(&rock_path.path[1..]) {
+                let step = (end - start).signum();
+                let mut pos = start;
+                map[position(pos)] = '#';
+                while pos != end {
+                    pos += step;
+                    map[position(pos)] = '#';
+                }
+            }
+        }
 
-            Ok(Self { path })
+        Cave {
+            source: position(Vec2::new(500, 0)),
+            map,
         }
     }
 
-    let mut rock_paths: Vec<RockPath> = data_as_lines.map(|x| x.parse().unwrap()).collect();
+    /// Pour sand until it flows out of the cave or blocks the source, returns the units at rest
+    fn fill(&mut self) -> usize {
+        let mut sand_count = 0;
 
-    let ((min_x, max_x), (_, max_y)) = rock_paths.clone().into_iter().flatten().fold(
-        ((i32::MAX, i32::MIN), (i32::MAX, i32::MIN)),
-        |acc, pos| {
-            let ((mut min_x, mut max_x), (mut min_y, mut max_y)) = acc;
+        'sand: loop {
+            let mut sand_pos = self.source;
 
-            if pos.x < min_x {
-                min_x = pos.x;
-            } else if pos.x > max_x {
-                max_x = pos.x;
+            // Down, then down left, then down right
+            'fall: loop {
+                for step in [Direction8::Down, Direction8::DownLeft, Direction8::DownRight] {
+                    match self.map.step(sand_pos, step.offset()) {
+                        None => break 'sand,
+                        Some(next_position) if self.map[next_position] == '.' => {
+                            sand_pos = next_position;
+                            continue 'fall;
+                        }
+                        Some(_) => (),
+                    }
+                }
+                break;
             }
 
-            if pos.y < min_y {
-                min_y = pos.y;
-            } else if pos.y > max_y {
-                max_y = pos.y;
-            }
+            self.map[sand_pos] = 'o';
+            sand_count += 1;
+            trace!("{}", self.map);
 
-            ((min_x, max_x), (min_y, max_y))
-        },
-    );
-
-    let start = Pos {
-        x: 500 - min_x,
-        y: 0,
-    };
-
-    let w = max_x - min_x + 1;
-    let h = max_y + 1;
-
-    let mut cave = vec!['.'; (w * h).try_into().unwrap()];
-    let mut cave2 = std::collections::HashMap::<Pos, char>::new();
-
-    rock_paths
-        .iter_mut()
-        .for_each(|path| path.path.iter_mut().for_each(|pos| pos.x -= min_x));
-
-    let rock_paths = rock_paths;
-
-    for rock_path in rock_paths {
-        let mut rock_path_iter = rock_path.into_iter();
-        let mut start = rock_path_iter.next().unwrap();
-
-        for next in rock_path_iter {
-            let diff_x = next.x - start.x;
-            let diff_y = next.y - start.y;
-
-            if diff_x == 0 {
-                let x = start.x;
-                let mut y = start.y;
-                let y_signum = diff_y.signum();
-                for _ in 0..=diff_y.abs() {
-                    let cave_idx: usize = (x + y * w).try_into().unwrap();
-                    cave[cave_idx] = '#';
-                    cave2.insert(Pos { x, y }, '#');
-                    // let newline = ['\n'];
-                    // let display: String = cave
-                    //     .chunks(w.try_into().unwrap())
-                    //     .flat_map(|x| x.iter().chain(newline.iter()))
-                    //     .collect();
-
-                    // println!("\n\n{display}\n\n");
-                    y += y_signum;
-                }
-            } else if diff_y == 0 {
-                let mut x = start.x;
-                let y = start.y;
-                let x_signum = diff_x.signum();
-                for _ in 0..=diff_x.abs() {
-                    let cave_idx: usize = (x + y * w).try_into().unwrap();
-                    cave[cave_idx] = '#';
-                    cave2.insert(Pos { x, y }, '#');
-                    // let newline = ['\n'];
-                    // let display: String = cave
-                    //     .chunks(w.try_into().unwrap())
-                    //     .flat_map(|x| x.iter().chain(newline.iter()))
-                    //     .collect();
-
-                    // println!("\n\n{display}\n\n");
-                    x += x_signum;
-                }
-            } else {
-                unreachable!()
+            if sand_pos == self.source {
+                break;
             }
-
-            start = next;
         }
-    }
 
-    // let newline = ['\n'];
-    // let display: String = cave
-    //     .chunks(w.try_into().unwrap())
-    //     .flat_map(|x| x.iter().chain(newline.iter()))
-    //     .collect();
+        debug!("{}", self.map);
 
-    // println!("\n\n{display}\n\n");
+        sand_count
+    }
+}
 
-    let mut sand_count = 0;
+pub struct Caves {
+    /// Part 1 cave, bounded by the rocks, `None` when only solving part 2
+    pub bounded: Option<Cave>,
+    /// Part 2 cave, with a floor wide enough for the sand to pile up on, `None` when only solving
+    /// part 1
+    pub floored: Option<Cave>,
+}
 
-    'outer: loop {
-        let mut sand_pos = start;
+impl Solution for Day14 {
+    const DAY: usize = 14;
 
-        loop {
-            // try to fall down
-            let next_position = Pos {
-                x: sand_pos.x,
-                y: sand_pos.y + 1,
-            };
+    type Input = Caves;
 
-            if !next_position.inside(w, h) {
-                break 'outer;
-            }
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
+    }
 
-            let next_position_idx = next_position.get_idx(w);
+    // Each part draws the rocks in its own cave, only draw the ones needed
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let parts = options.parts;
+        let rock_paths: Vec<RockPath> = parse::lines(data)?;
+
+        let rocks = || rock_paths.iter().flat_map(|rock_path| &rock_path.path);
+        let (Some(min_x), Some(max_x), Some(max_y)) = (
+            rocks().map(|pos| pos.x).min(),
+            rocks().map(|pos| pos.x).max(),
+            rocks().map(|pos| pos.y).max(),
+        ) else {
+            return Err(ParseError::at_end(data, "a rock path"));
+        };
+
+        // Wide enough for the source too, the rocks may all be on one side of it
+        let bounded = parts
+            .includes(1)
+            .then(|| Cave::new(&rock_paths, min_x.min(500), max_x.max(500), max_y + 1));
+        if let Some(cave) = &bounded {
+            debug!("{}", cave.map);
+        }
 
-            match cave[next_position_idx] {
-                '.' => {
-                    sand_pos = next_position;
-                }
-                _ => {
-                    let next_position = Pos {
-                        x: sand_pos.x - 1,
-                        y: sand_pos.y + 1,
-                    };
+        // The sand piles up in a triangle as tall as the floor is deep
+        let floored = parts.includes(2).then(|| {
+            let floor_y = max_y + 2;
+            let mut cave = Cave::new(
+                &rock_paths,
+                min_x.min(500 - floor_y),
+                max_x.max(500 + floor_y),
+                floor_y,
+            );
+            cave.map.push_row(vec!['#'; cave.map.width()]);
+            cave
+        });
+
+        Ok(Caves { bounded, floored })
+    }
 
-                    if !next_position.inside(w, h) {
-                        break 'outer;
-                    }
+    fn part_1(input: &Self::Input) -> Answer {
+        let mut cave = input.bounded.clone().expect("Cave parsed for part 1");
 
-                    let next_position_idx = next_position.get_idx(w);
-                    match cave[next_position_idx] {
-                        '.' => {
-                            sand_pos = next_position;
-                        }
-                        _ => {
-                            let next_position = Pos {
-                                x: sand_pos.x + 1,
-                                y: sand_pos.y + 1,
-                            };
-
-                            if !next_position.inside(w, h) {
-                                break 'outer;
-                            }
-
-                            let next_position_idx = next_position.get_idx(w);
-
-                            match cave[next_position_idx] {
-                                '.' => {
-                                    sand_pos = next_position;
-                                }
-                                _ => {
-                                    cave[sand_pos.get_idx(w)] = 'o';
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        cave.fill().into()
+    }
 
-        // let newline = ['\n'];
-        // let display: String = cave
-        //     .chunks(w.try_into().unwrap())
-        //     .flat_map(|x| x.iter().chain(newline.iter()))
-        //     .collect();
+    fn part_2(input: &Self::Input) -> Answer {
+        let mut cave = input.floored.clone().expect("Cave parsed for part 2");
 
-        // println!("\n\n{display}\n\n");
-        sand_count += 1;
+        cave.fill().into()
     }
 
-    println!("Part 1: {sand_count}");
+    // `size` rock paths
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let paths = (0..size)
+            .map(|_| {
+                // Below the first rows, so that the source is not blocked right away
+            let mut pos = Vec2::new(rng.range(460..=540), rng.range(10..=170));
+                let mut path = vec![format!("{},{}", pos.x, pos.y)];
+
+                for segment in 0..rng.range(1..=5) {
+                    let length = rng.range(1..=10);
+                    // Turning at each point, up or down and left or right alternating
+                    let length = length * rng.choose(&[-1, 1]);
+                    pos = match segment % 2 {
+                        0 => Vec2::new((pos.x + length).clamp(460, 540), pos.y),
+                        _ => Vec2::new(pos.x, (pos.y + length).clamp(10, 170)),
+                    };
+                    path.push(format!("{},{}", pos.x, pos.y));
+                }
 
-    let mut sand_count = 0;
+                path.join(" -> ") + "\n"
+            })
+            .collect();
 
-    let h = h + 2;
+        Some(paths)
+    }
+}
 
-    loop {
-        let mut sand_pos = start;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::PartSelection;
 
-        loop {
-            // try to fall down
-            let next_position = Pos {
-                x: sand_pos.x,
-                y: sand_pos.y + 1,
-            };
+    const EXAMPLE: &str = "\
+498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9
+";
 
-            match cave2.entry(next_position).or_insert_with(|| {
-                if next_position.y == h - 1 {
-                    '#'
-                } else {
-                    '.'
-                }
-            }) {
-                '.' => {
-                    sand_pos = next_position;
-                }
-                _ => {
-                    let next_position = Pos {
-                        x: sand_pos.x - 1,
-                        y: sand_pos.y + 1,
-                    };
-
-                    match cave2.entry(next_position).or_insert_with(|| {
-                        if next_position.y == h - 1 {
-                            '#'
-                        } else {
-                            '.'
-                        }
-                    }) {
-                        '.' => {
-                            sand_pos = next_position;
-                        }
-                        _ => {
-                            let next_position = Pos {
-                                x: sand_pos.x + 1,
-                                y: sand_pos.y + 1,
-                            };
-
-                            match cave2.entry(next_position).or_insert_with(|| {
-                                if next_position.y == h - 1 {
-                                    '#'
-                                } else {
-                                    '.'
-                                }
-                            }) {
-                                '.' => {
-                                    sand_pos = next_position;
-                                }
-                                _ => {
-                                    cave2.insert(sand_pos, 'o');
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    #[test]
+    fn part_1_example() {
+        let input = Day14::parse(EXAMPLE).unwrap();
+        assert_eq!(Day14::part_1(&input), Answer::Number(24));
+    }
 
-        // let newline = ['\n'];
-        // let display: String = cave2
-        //     .chunks(w.try_into().unwrap())
-        //     .flat_map(|x| x.iter().chain(newline.iter()))
-        //     .collect();
+    #[test]
+    fn part_2_example() {
+        let input = Day14::parse(EXAMPLE).unwrap();
+        assert_eq!(Day14::part_2(&input), Answer::Number(93));
+    }
 
-        // println!("\n\n{display}\n\n");
+    #[test]
+    fn parse_single_part() {
+        let options = |parts| RunOptions {
+            parts,
+            ..Default::default()
+        };
 
-        sand_count += 1;
+        let input = Day14::parse_with(EXAMPLE, &options(PartSelection::Only(1))).unwrap();
+        assert!(input.floored.is_none());
+        assert_eq!(Day14::part_1(&input), Answer::Number(24));
 
-        if sand_pos == start {
-            break;
-        }
+        let input = Day14::parse_with(EXAMPLE, &options(PartSelection::Only(2))).unwrap();
+        assert!(input.bounded.is_none());
+        assert_eq!(Day14::part_2(&input), Answer::Number(93));
     }
 
-    println!("Part 2: {sand_count}");
+    #[test]
+    fn rocks_on_one_side() {
+        let input = Day14::parse("531,79 -> 521,79\n").unwrap();
+        assert_eq!(Day14::part_1(&input), Answer::Number(0));
+
+        let input = Day14::parse("479,3 -> 479,5 -> 470,5\n").unwrap();
+        assert_eq!(Day14::part_1(&input), Answer::Number(0));
+    }
 }
@@ -1,4 +1,8 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::utils::vector::Vec2;
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
+use std::str::FromStr;
 
 /// --- Day 15: Beacon Exclusion Zone ---
 /// You feel the ground rumble again as the distress signal leads you to a large network of
@@ -131,108 +135,76 @@ use super::load_file;
 /// beacon: x=14, y=11. The tuning frequency for this distress beacon is 56000011.
 ///
 /// Find the only possible position for the distress beacon. What is its tuning frequency?
-pub fn day_15() {
-    let data = load_file(15);
+pub struct Day15;
 
-    #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-    struct Pos {
-        pub x: i64,
-        pub y: i64,
-    }
-
-    impl Pos {
-        pub fn distance_to(&self, other: &Pos) -> u64 {
-            self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
-        }
-    }
-
-    #[derive(Debug)]
-    struct Sensor {
-        pub pos: Pos,
-        pub closest_beacon: Pos,
-    }
+#[derive(Debug)]
+pub struct Sensor {
+    pub pos: Vec2<i64>,
+    pub closest_beacon: Vec2<i64>,
+}
 
-    impl Sensor {
-        pub fn distance_to_closest_beacon(&self) -> u64 {
-            self.pos.distance_to(&self.closest_beacon)
-        }
+impl Sensor {
+    pub fn distance_to_closest_beacon(&self) -> i64 {
+        self.pos.manhattan(self.closest_beacon)
     }
+}
 
-    use std::str::FromStr;
-
-    impl FromStr for Pos {
-        type Err = Box<dyn std::error::Error>;
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let (x, y) = s.trim().split_once(',').unwrap();
-            let (_, x) = x.split_once("x=").unwrap();
-            let (_, y) = y.split_once("y=").unwrap();
+/// `x=<x>, y=<y>`, `token` being a slice of `s`
+fn parse_pos(s: &str, token: &str) -> Result<Vec2<i64>, ParseError> {
+    let (x, y) = parse::split_once(s, token.trim(), ",")?;
+    let x = parse::strip_prefix(s, x, "x=")?;
+    let y = parse::strip_prefix(s, y.trim(), "y=")?;
 
-            Ok(Self {
-                x: x.parse().unwrap(),
-                y: y.parse().unwrap(),
-            })
-        }
-    }
+    Ok(Vec2::new(parse::number(s, x)?, parse::number(s, y)?))
+}
 
-    impl FromStr for Sensor {
-        type Err = Box<dyn std::error::Error>;
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let (sensor_coords_str, beacon_coords_str) = s.trim().split_once(':').unwrap();
+impl FromStr for Sensor {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sensor_coords_str, beacon_coords_str) = parse::split_once(s, s.trim(), ":")?;
 
-            let (_, sensor_coords_str) = sensor_coords_str.split_once("at").unwrap();
-            let sensor_pos: Pos = sensor_coords_str.parse().unwrap();
+        let sensor_coords_str = parse::strip_prefix(s, sensor_coords_str, "Sensor at ")?;
+        let sensor_pos = parse_pos(s, sensor_coords_str)?;
 
-            let (_, beacon_coords_str) = beacon_coords_str.split_once("at ").unwrap();
-            let beacon_pos: Pos = beacon_coords_str.parse().unwrap();
+        let beacon_coords_str =
+            parse::strip_prefix(s, beacon_coords_str, " closest beacon is at ")?;
+        let beacon_pos = parse_pos(s, beacon_coords_str)?;
 
-            Ok(Self {
-                pos: sensor_pos,
-                closest_beacon: beacon_pos,
-            })
-        }
+        Ok(Self {
+            pos: sensor_pos,
+            closest_beacon: beacon_pos,
+        })
     }
+}
 
-    let mut sensors: Vec<Sensor> = data
-        .trim()
-        .split('\n')
-        .into_iter()
-        .map(|x| x.parse().unwrap())
-        .collect();
-
-    // Sort by x
-    sensors.sort_by_key(|sensor| sensor.pos.x);
-
-    let sensors = sensors;
-
+/// Number of positions of row `y` where a beacon cannot be
+pub fn no_beacon_locations(sensors: &[Sensor], y: i64) -> i64 {
     let (min_x, max_x) = sensors.iter().fold((i64::MAX, i64::MIN), |acc, sensor| {
         let (mut min_x, mut max_x) = acc;
 
         min_x = std::cmp::min(
-            sensor.pos.x - sensor.distance_to_closest_beacon() as i64,
+            sensor.pos.x - sensor.distance_to_closest_beacon(),
             min_x,
         );
         max_x = std::cmp::max(
-            sensor.pos.x + sensor.distance_to_closest_beacon() as i64,
+            sensor.pos.x + sensor.distance_to_closest_beacon(),
             max_x,
         );
 
         (min_x, max_x)
     });
 
-    let y = 2_000_000;
     let mut no_beacon_locations = 0;
     let mut x = min_x;
     'outer: while x <= max_x {
-        let potential_beacon = Pos { x, y };
+        let potential_beacon = Vec2::new(x, y);
 
         for sensor in sensors.iter() {
             let distance_to_closest_beacon = sensor.distance_to_closest_beacon();
-            let distance_to_potential_beacon = sensor.pos.distance_to(&potential_beacon);
+            let distance_to_potential_beacon = sensor.pos.manhattan(potential_beacon);
 
             if distance_to_potential_beacon <= distance_to_closest_beacon {
-                let x_half_span: i64 = (distance_to_closest_beacon - y.abs_diff(sensor.pos.y))
-                    .try_into()
-                    .unwrap();
+                let x_half_span = distance_to_closest_beacon - (y - sensor.pos.y).abs();
                 let x_diff = sensor.pos.x - x;
                 let x_skip = x_diff + x_half_span + 1;
                 assert!(x_skip >= 1);
@@ -256,29 +228,31 @@ pub fn day_15() {
 
     no_beacon_locations -= beacons_at_y.len() as i64;
 
-    println!("Part 1: {no_beacon_locations}");
+    no_beacon_locations
+}
 
-    let beacons: std::collections::HashSet<Pos> =
+/// Tuning frequency of the only position not covered by a sensor, with both coordinates in
+/// `0..=max_coord`
+pub fn tuning_frequency(sensors: &[Sensor], max_coord: i64) -> i64 {
+    let beacons: std::collections::HashSet<Vec2<i64>> =
         sensors.iter().map(|sensor| sensor.closest_beacon).collect();
 
-    let (x_min, x_max) = (0, 4_000_000);
-    let (y_min, y_max) = (0, 4_000_000);
+    let (x_min, x_max) = (0, max_coord);
+    let (y_min, y_max) = (0, max_coord);
 
     let mut tuning_f = 0;
 
     'outer: for y in y_min..=y_max {
         let mut x = x_min;
         'x_outer: while x <= x_max {
-            let potential_beacon = Pos { x, y };
+            let potential_beacon = Vec2::new(x, y);
 
             for sensor in sensors.iter() {
                 let distance_to_closest_beacon = sensor.distance_to_closest_beacon();
-                let distance_to_potential_beacon = sensor.pos.distance_to(&potential_beacon);
+                let distance_to_potential_beacon = sensor.pos.manhattan(potential_beacon);
 
                 if distance_to_potential_beacon <= distance_to_closest_beacon {
-                    let x_half_span: i64 = (distance_to_closest_beacon - y.abs_diff(sensor.pos.y))
-                        .try_into()
-                        .unwrap();
+                    let x_half_span = distance_to_closest_beacon - (y - sensor.pos.y).abs();
                     let x_diff = sensor.pos.x - x;
                     let x_skip = x_diff + x_half_span + 1;
                     assert!(x_skip >= 1);
@@ -297,5 +271,190 @@ pub fn day_15() {
         }
     }
 
-    println!("Part 2: {tuning_f}");
+    tuning_f
+}
+
+const ROW: Param = Param {
+    name: "y",
+    default: 2_000_000,
+    min: -1_000_000_000,
+    max: 1_000_000_000,
+    doc: "row where part 1 counts the positions without a beacon",
+};
+
+const MAX_COORD: Param = Param {
+    name: "max",
+    default: 4_000_000,
+    min: 0,
+    max: 1_000_000_000,
+    doc: "part 2 searches the distress beacon with both coordinates in 0..=max",
+};
+
+#[derive(Debug)]
+pub struct SensorReport {
+    pub sensors: Vec<Sensor>,
+    pub row: i64,
+    pub max_coord: i64,
+}
+
+impl Solution for Day15 {
+    const DAY: usize = 15;
+
+    const PARAMS: &'static [Param] = &[ROW, MAX_COORD];
+
+    type Input = SensorReport;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
+    }
+
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let mut sensors: Vec<Sensor> = parse::lines(data)?;
+
+        // Sort by x
+        sensors.sort_by_key(|sensor| sensor.pos.x);
+
+        Ok(SensorReport {
+            sensors,
+            row: options.params.get(&ROW),
+            max_coord: options.params.get(&MAX_COORD),
+        })
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        no_beacon_locations(&input.sensors, input.row).into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        tuning_frequency(&input.sensors, input.max_coord).into()
+    }
+
+    // `size` sensors within the area part 2 searches, plus four far away ones
+    fn generate(rng: &mut Rng, size: usize, params: &Params) -> Option<String> {
+        let max_coord: i64 = params.get(&MAX_COORD);
+        let random_pos =
+            |rng: &mut Rng| Vec2::new(rng.range(0..=max_coord), rng.range(0..=max_coord));
+        let gap = random_pos(rng);
+
+        // All the beacons are next to the gap, each sensor reaching just short of it
+        let sensor = |pos: Vec2<i64>| {
+            let offset = pos - gap;
+            let beacon = match offset.x.abs() >= offset.y.abs() {
+                true => gap + Vec2::new(offset.x.signum(), 0),
+                false => gap + Vec2::new(0, offset.y.signum()),
+            };
+            format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+                pos.x, pos.y, beacon.x, beacon.y
+            )
+        };
+
+        // Sensors in each diagonal direction, far enough to cover their quadrant of the area
+        let reach = rng.range(max_coord.max(1)..=2 * max_coord.max(1));
+        let mut report: String = [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+            .map(|(x, y)| sensor(gap + Vec2::new(x, y) * reach))
+            .concat();
+
+        let mut sensor_count = 0;
+        while sensor_count < size {
+            let pos = random_pos(rng);
+            // Not so close that the sensor is on its beacon
+            if pos.manhattan(gap) >= 2 {
+                report += &sensor(pos);
+                sensor_count += 1;
+            }
+        }
+
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3
+";
+
+    #[test]
+    fn part_1_example() {
+        let mut options = RunOptions::default();
+        options.params.set("y", 10);
+
+        let input = Day15::parse_with(EXAMPLE, &options).unwrap();
+        assert_eq!(Day15::part_1(&input), Answer::Number(26));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let mut options = RunOptions::default();
+        options.params.set("max", 20);
+
+        let input = Day15::parse_with(EXAMPLE, &options).unwrap();
+        assert_eq!(Day15::part_2(&input), Answer::Number(56000011));
+    }
+
+    #[test]
+    fn invalid_sensor() {
+        let err =
+            Day15::parse("Sensor at x=2, y=18: closest beacon is at x=-2 y=15\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 43));
+        assert_eq!(err.expected, "\",\"");
+    }
+
+    /// Whether a beacon could be at `pos`, that is no sensor has a closer or as close beacon
+    fn reference_free(sensors: &[Sensor], pos: Vec2<i64>) -> bool {
+        sensors
+            .iter()
+            .all(|sensor| sensor.pos.manhattan(pos) > sensor.distance_to_closest_beacon())
+    }
+
+    #[test]
+    fn matches_reference() {
+        let (row, max_coord) = (20, 40);
+        let mut options = RunOptions::default();
+        options.params.set("y", row);
+        options.params.set("max", max_coord);
+
+        for seed in 0..20 {
+            let data = Day15::generate(&mut Rng::new(seed), 10, &options.params).unwrap();
+            let input = Day15::parse_with(&data, &options).unwrap();
+            let sensors = &input.sensors;
+            let is_beacon = |pos| sensors.iter().any(|sensor| sensor.closest_beacon == pos);
+
+            // Every position of the row that any sensor reaches
+            let reach = sensors
+                .iter()
+                .map(|sensor| sensor.pos.x.abs() + sensor.distance_to_closest_beacon())
+                .max()
+                .unwrap();
+            let expected = (-reach..=reach)
+                .map(|x| Vec2::new(x, row))
+                .filter(|&pos| !reference_free(sensors, pos) && !is_beacon(pos))
+                .count();
+            assert_eq!(Day15::part_1(&input), Answer::from(expected), "seed {seed}");
+
+            let distress_beacons: Vec<Vec2<i64>> = (0..=max_coord)
+                .flat_map(|y| (0..=max_coord).map(move |x| Vec2::new(x, y)))
+                .filter(|&pos| reference_free(sensors, pos) && !is_beacon(pos))
+                .collect();
+            assert_eq!(distress_beacons.len(), 1, "seed {seed}");
+            let expected = distress_beacons[0].x * 4_000_000 + distress_beacons[0].y;
+            assert_eq!(Day15::part_2(&input), Answer::from(expected), "seed {seed}");
+        }
+    }
 }
@@ -1,4 +1,9 @@
-use super::load_file;
+use super::log::trace;
+use super::parse;
+use super::utils::rng::Rng;
+use super::utils::search;
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 
 /// --- Day 16: Proboscidea Volcanium ---
 /// The sensors have led you to the origin of the distress signal: yet another handheld device, just
@@ -248,141 +253,208 @@ use super::load_file;
 ///
 /// With you and an elephant working together for 26 minutes, what is the most pressure you could
 /// release?
-pub fn day_16() {
-    let data = load_file(16);
+pub struct Day16;
 
-    use std::collections::{HashMap, HashSet, VecDeque};
+#[derive(Debug, Clone)]
+pub struct Valve {
+    pub name: String,
+    pub pressure_release_per_minute: u64,
+    pub tunnels_lead_to: HashMap<String, u64>,
+}
 
-    #[derive(Debug, Clone)]
-    struct Valve {
-        name: String,
+impl Valve {
+    pub fn new(
+        name: &str,
         pressure_release_per_minute: u64,
-        tunnels_lead_to: HashMap<String, u64>,
-    }
-
-    impl Valve {
-        pub fn new(
-            name: &str,
-            pressure_release_per_minute: u64,
-            tunnels: HashMap<String, u64>,
-        ) -> Self {
-            Self {
-                name: name.to_string(),
-                pressure_release_per_minute,
-                tunnels_lead_to: tunnels,
-            }
-        }
-    }
-
-    impl PartialEq for Valve {
-        fn eq(&self, other: &Self) -> bool {
-            self.name == other.name
+        tunnels: HashMap<String, u64>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            pressure_release_per_minute,
+            tunnels_lead_to: tunnels,
         }
     }
+}
 
-    impl Eq for Valve {}
-
-    impl std::str::FromStr for Valve {
-        type Err = Box<dyn std::error::Error>;
-
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let (name_flow, tunnels) = s.trim().split_once(';').unwrap();
-            let name_flow = name_flow.strip_prefix("Valve").unwrap();
-            let (name, flow) = name_flow.trim().split_once("has flow rate=").unwrap();
-            let name = name.trim();
-            let flow: u64 = flow.parse()?;
-            let (_, tunnels) = tunnels.split_once("valve").unwrap();
-            let tunnels: HashMap<String, u64> = tunnels
-                .trim_start_matches('s')
-                .split(',')
-                .into_iter()
-                .map(|x| (x.trim().to_string(), 1))
-                .collect();
-
-            Ok(Valve::new(name, flow, tunnels))
-        }
+impl PartialEq for Valve {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
     }
+}
 
-    let mut all_valves = HashMap::new();
-
-    let mut idx_to_valve: Vec<String> = vec![];
+impl Eq for Valve {}
+
+impl std::str::FromStr for Valve {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name_flow, tunnels) = parse::split_once(s, s.trim(), ";")?;
+        let name_flow = parse::strip_prefix(s, name_flow, "Valve")?;
+        let (name, flow) = parse::split_once(s, name_flow.trim(), "has flow rate=")?;
+        let name = name.trim();
+        let flow: u64 = parse::number(s, flow)?;
+        let (_, tunnels) = parse::split_once(s, tunnels, "valve")?;
+        let tunnels: HashMap<String, u64> = tunnels
+            .trim_start_matches('s')
+            .split(',')
+            .map(|x| (x.trim().to_string(), 1))
+            .collect();
 
-    for valve_str in data.trim().split('\n') {
-        let valve: Valve = valve_str.parse().unwrap();
-        idx_to_valve.push(valve.name.clone());
-        all_valves.insert(valve.name.clone(), valve);
+        Ok(Valve::new(name, flow, tunnels))
     }
+}
 
-    let valve_to_idx: HashMap<String, usize> = idx_to_valve
-        .iter()
-        .enumerate()
-        .map(|(idx, valve_name)| (valve_name.clone(), idx))
-        .collect();
+struct ProblemSolutionPart1 {
+    pub current_valve: String,
+    pub expected_release: u64,
+    pub current_time: u64,
+    pub opened_valves: HashSet<String>,
+}
 
-    let valve_count = valve_to_idx.len();
+impl ProblemSolutionPart1 {
+    pub fn is_complete(&self, time_limit: u64, non_zero_valves_sorted: &[&Valve]) -> bool {
+        assert!(self.current_time <= time_limit);
+        self.current_time == time_limit || self.opened_valves.len() == non_zero_valves_sorted.len()
+    }
 
-    let mut adjacency_matrix = vec![vec![u64::MAX; valve_count]; valve_count];
+    pub fn release_upper_bound(
+        &self,
+        all_valves: &HashMap<String, Valve>,
+        non_zero_valves_sorted: &[&Valve],
+        time_limit: u64,
+    ) -> u64 {
+        let mut release_upper_bound = self.expected_release;
+        let mut remaining_time = time_limit - self.current_time;
+
+        let current_valve = all_valves.get(&self.current_valve).unwrap();
+        if current_valve.pressure_release_per_minute != 0
+            && remaining_time > 1
+            && !self.opened_valves.contains(&self.current_valve)
+        {
+            remaining_time -= 1;
+            release_upper_bound += remaining_time * current_valve.pressure_release_per_minute;
+        }
 
-    for (src_valve_idx, src_valve_name) in idx_to_valve.iter().enumerate() {
-        let src_valve = all_valves.get(src_valve_name).unwrap();
+        for valve in non_zero_valves_sorted {
+            if remaining_time < 2 {
+                break;
+            }
+            if !self.opened_valves.contains(&valve.name) && valve.name != current_valve.name {
+                remaining_time -= 2;
+                release_upper_bound += remaining_time * valve.pressure_release_per_minute;
+            }
+        }
 
-        for dst_valve_name in src_valve.tunnels_lead_to.keys() {
-            let &dst_valve_idx = valve_to_idx.get(dst_valve_name).unwrap();
+        release_upper_bound
+    }
+}
 
-            adjacency_matrix[src_valve_idx][dst_valve_idx] = 1;
-            adjacency_matrix[dst_valve_idx][src_valve_idx] = 1;
-            adjacency_matrix[src_valve_idx][src_valve_idx] = 0;
-            adjacency_matrix[dst_valve_idx][dst_valve_idx] = 0;
+pub fn solve(
+    time_limit: u64,
+    all_valves: &HashMap<String, Valve>,
+    non_zero_valves_sorted: &[&Valve],
+) -> u64 {
+    let mut solutions_queue = VecDeque::new();
+    solutions_queue.push_back(ProblemSolutionPart1 {
+        current_valve: "AA".to_string(),
+        expected_release: 0,
+        current_time: 0,
+        opened_valves: HashSet::new(),
+    });
+
+    let mut best_complete_release = 0;
+
+    while let Some(current_solution) = solutions_queue.pop_front() {
+        if current_solution.is_complete(time_limit, non_zero_valves_sorted) {
+            best_complete_release =
+                std::cmp::max(best_complete_release, current_solution.expected_release);
+            continue;
         }
-    }
 
-    // Floyd warshall
-    for k in 0..valve_count {
-        for i in 0..valve_count {
-            for j in 0..valve_count {
-                let w_ik = adjacency_matrix[i][k];
-                let w_kj = adjacency_matrix[k][j];
-                let w_ij = &mut adjacency_matrix[i][j];
+        let current_valve = all_valves.get(&current_solution.current_valve).unwrap();
+
+        if current_valve.pressure_release_per_minute != 0
+            && (current_solution.current_time < time_limit - 1)
+            && !current_solution.opened_valves.contains(&current_valve.name)
+        {
+            // +1 as we open the valve
+            let new_time = current_solution.current_time + 1;
+            let mut new_opened_valves = current_solution.opened_valves.clone();
+            new_opened_valves.insert(current_valve.name.clone());
+
+            let new_release = current_solution.expected_release
+                + (time_limit - new_time) * current_valve.pressure_release_per_minute;
+            let open_valve_solution = ProblemSolutionPart1 {
+                current_valve: current_solution.current_valve.clone(),
+                expected_release: new_release,
+                current_time: new_time,
+                opened_valves: new_opened_valves,
+            };
+
+            let release_upper_bound = open_valve_solution.release_upper_bound(
+                all_valves,
+                non_zero_valves_sorted,
+                time_limit,
+            );
+
+            if release_upper_bound < best_complete_release {
+                continue;
+            }
 
-                if w_ik == u64::MAX || w_kj == u64::MAX {
-                    continue;
-                }
+            best_complete_release =
+                std::cmp::max(open_valve_solution.expected_release, best_complete_release);
 
-                *w_ij = std::cmp::min(*w_ij, w_ik + w_kj);
+            if let Some(next_potential_solution) = solutions_queue.front() {
+                if open_valve_solution.current_time >= next_potential_solution.current_time {
+                    solutions_queue.push_front(open_valve_solution);
+                } else {
+                    solutions_queue.push_back(open_valve_solution);
+                }
+            } else {
+                solutions_queue.push_back(open_valve_solution);
             }
         }
-    }
-
-    {
-        let tmp_all_valves = all_valves.clone();
 
-        for valve in all_valves.values_mut() {
-            if valve.pressure_release_per_minute != 0 || valve.name == "AA" {
-                let &valve_idx = valve_to_idx.get(&valve.name).unwrap();
+        for (destination_name, &distance) in current_valve.tunnels_lead_to.iter() {
+            if current_solution.opened_valves.contains(destination_name)
+                || (time_limit - current_solution.current_time) < distance
+                || !all_valves.contains_key(destination_name)
+            {
+                continue;
+            }
 
-                valve.tunnels_lead_to.clear();
+            let new_solution = ProblemSolutionPart1 {
+                current_valve: destination_name.to_string(),
+                expected_release: current_solution.expected_release,
+                current_time: current_solution.current_time + distance,
+                opened_valves: current_solution.opened_valves.clone(),
+            };
 
-                let valve_adjacencies = &adjacency_matrix[valve_idx];
+            let release_upper_bound =
+                new_solution.release_upper_bound(all_valves, non_zero_valves_sorted, time_limit);
 
-                for (dst_idx, &distance) in valve_adjacencies.iter().enumerate() {
-                    if valve_idx == dst_idx {
-                        continue;
-                    }
+            if release_upper_bound < best_complete_release {
+                continue;
+            }
 
-                    let dst_valve_name = &idx_to_valve[dst_idx];
-                    let dst_valve = tmp_all_valves.get(dst_valve_name).unwrap();
-                    if dst_valve.pressure_release_per_minute == 0 {
-                        continue;
-                    }
+            best_complete_release =
+                std::cmp::max(new_solution.expected_release, best_complete_release);
 
-                    valve
-                        .tunnels_lead_to
-                        .insert(dst_valve_name.clone(), distance);
+            if let Some(next_potential_solution) = solutions_queue.front() {
+                if new_solution.current_time >= next_potential_solution.current_time {
+                    solutions_queue.push_front(new_solution);
+                } else {
+                    solutions_queue.push_back(new_solution);
                 }
+            } else {
+                solutions_queue.push_back(new_solution);
             }
         }
     }
+    best_complete_release
+}
 
+pub fn sorted_non_zero_valves(all_valves: &HashMap<String, Valve>) -> Vec<&Valve> {
     let mut non_zero_valves_sorted: Vec<_> = all_valves
         .iter()
         .filter(|&x| x.1.pressure_release_per_minute != 0)
@@ -390,212 +462,312 @@ pub fn day_16() {
         .collect();
     non_zero_valves_sorted.sort_by_key(|x| x.pressure_release_per_minute);
     non_zero_valves_sorted.reverse();
-    let non_zero_valves_sorted = non_zero_valves_sorted;
+    non_zero_valves_sorted
+}
+
+const TIME_LIMIT_PART1: Param = Param {
+    name: "minutes_1",
+    default: 30,
+    min: 0,
+    max: 100,
+    doc: "minutes before the eruption in part 1",
+};
+
+const TIME_LIMIT_PART2: Param = Param {
+    name: "minutes_2",
+    default: 26,
+    min: 0,
+    max: 100,
+    doc: "minutes left in part 2, once the elephant is taught",
+};
+
+pub struct Volcano {
+    // Valves with their tunnels replaced by the distances to all non zero valves
+    pub valves: HashMap<String, Valve>,
+    pub time_limits: [u64; 2],
+}
 
-    const TIME_LIMIT_PART1: u64 = 30;
+impl Solution for Day16 {
+    const DAY: usize = 16;
 
-    struct ProblemSolutionPart1 {
-        pub current_valve: String,
-        pub expected_release: u64,
-        pub current_time: u64,
-        pub opened_valves: HashSet<String>,
+    const PARAMS: &'static [Param] = &[TIME_LIMIT_PART1, TIME_LIMIT_PART2];
+
+    type Input = Volcano;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
     }
 
-    impl ProblemSolutionPart1 {
-        pub fn is_complete(&self, time_limit: u64, non_zero_valves_sorted: &[&Valve]) -> bool {
-            assert!(self.current_time <= time_limit);
-            self.current_time == time_limit
-                || self.opened_valves.len() == non_zero_valves_sorted.len()
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let mut all_valves = HashMap::new();
+
+        for valve_str in data.trim().split('\n') {
+            let valve: Valve = valve_str
+                .parse()
+                .map_err(|err: ParseError| err.within(data, valve_str))?;
+            all_valves.insert(valve.name.clone(), valve);
         }
 
-        pub fn release_upper_bound(
-            &self,
-            all_valves: &HashMap<String, Valve>,
-            non_zero_valves_sorted: &[&Valve],
-            time_limit: u64,
-        ) -> u64 {
-            let mut release_upper_bound = self.expected_release;
-            let mut remaining_time = time_limit - self.current_time;
-
-            let current_valve = all_valves.get(&self.current_valve).unwrap();
-            if current_valve.pressure_release_per_minute != 0
-                && remaining_time > 1
-                && !self.opened_valves.contains(&self.current_valve)
-            {
-                remaining_time -= 1;
-                release_upper_bound += remaining_time * current_valve.pressure_release_per_minute;
-            }
+        if !all_valves.contains_key("AA") {
+            return Err(ParseError::at_end(data, "a valve AA"));
+        }
 
-            for valve in non_zero_valves_sorted {
-                if remaining_time < 2 {
-                    break;
-                }
-                if !self.opened_valves.contains(&valve.name) && valve.name != current_valve.name {
-                    remaining_time -= 2;
-                    release_upper_bound += remaining_time * valve.pressure_release_per_minute;
+        for valve_str in data.trim().split('\n') {
+            let (_, tunnels) = parse::split_once(data, valve_str, "valve")?;
+            for dst_valve_name in tunnels.trim_start_matches('s').split(',') {
+                if !all_valves.contains_key(dst_valve_name.trim()) {
+                    return Err(ParseError::at(data, dst_valve_name.trim(), "a known valve"));
                 }
             }
+        }
 
-            release_upper_bound
+        // Only the valves worth opening are visited, so the tunnels are replaced by the distances
+        // to them
+        let is_working = |name: &String| all_valves[name].pressure_release_per_minute != 0;
+        let distances: HashMap<String, HashMap<String, u64>> = all_valves
+            .keys()
+            .filter(|&name| is_working(name) || name == "AA")
+            .map(|name| {
+                let reachable = search::distances([name.clone()], |current| {
+                    all_valves[current]
+                        .tunnels_lead_to
+                        .keys()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                });
+                let working = reachable
+                    .into_iter()
+                    .filter(|(dst, _)| dst != name && is_working(dst))
+                    .map(|(dst, distance)| (dst, distance as u64))
+                    .collect();
+                (name.clone(), working)
+            })
+            .collect();
+
+        for (name, tunnels_lead_to) in distances {
+            all_valves.get_mut(&name).unwrap().tunnels_lead_to = tunnels_lead_to;
         }
+
+        Ok(Volcano {
+            valves: all_valves,
+            time_limits: [
+                options.params.get(&TIME_LIMIT_PART1),
+                options.params.get(&TIME_LIMIT_PART2),
+            ],
+        })
     }
 
-    fn solve(
-        time_limit: u64,
-        all_valves: &HashMap<String, Valve>,
-        non_zero_valves_sorted: &[&Valve],
-    ) -> u64 {
-        let mut solutions_queue = VecDeque::new();
-        solutions_queue.push_back(ProblemSolutionPart1 {
-            current_valve: "AA".to_string(),
-            expected_release: 0,
-            current_time: 0,
-            opened_valves: HashSet::new(),
-        });
-
-        let mut best_complete_release = 0;
-
-        while let Some(current_solution) = solutions_queue.pop_front() {
-            if current_solution.is_complete(time_limit, non_zero_valves_sorted) {
-                best_complete_release =
-                    std::cmp::max(best_complete_release, current_solution.expected_release);
-                continue;
-            }
+    fn part_1(input: &Self::Input) -> Answer {
+        let all_valves = &input.valves;
+        let non_zero_valves_sorted = sorted_non_zero_valves(all_valves);
 
-            let current_valve = all_valves.get(&current_solution.current_valve).unwrap();
+        let best_complete_release_part1 =
+            solve(input.time_limits[0], all_valves, &non_zero_valves_sorted);
 
-            if current_valve.pressure_release_per_minute != 0
-                && (current_solution.current_time < time_limit - 1)
-                && !current_solution.opened_valves.contains(&current_valve.name)
-            {
-                // +1 as we open the valve
-                let new_time = current_solution.current_time + 1;
-                let mut new_opened_valves = current_solution.opened_valves.clone();
-                new_opened_valves.insert(current_valve.name.clone());
-
-                let new_release = current_solution.expected_release
-                    + (time_limit - new_time) * current_valve.pressure_release_per_minute;
-                let open_valve_solution = ProblemSolutionPart1 {
-                    current_valve: current_solution.current_valve.clone(),
-                    expected_release: new_release,
-                    current_time: new_time,
-                    opened_valves: new_opened_valves,
-                };
+        best_complete_release_part1.into()
+    }
 
-                let release_upper_bound = open_valve_solution.release_upper_bound(
-                    all_valves,
-                    non_zero_valves_sorted,
-                    time_limit,
-                );
+    fn part_2(input: &Self::Input) -> Answer {
+        let all_valves = &input.valves;
+        let non_zero_valves_sorted = sorted_non_zero_valves(all_valves);
 
-                if release_upper_bound < best_complete_release {
-                    continue;
-                }
+        let mut best_solution_per_subset: Vec<u64> =
+            Vec::with_capacity(2_usize.pow(non_zero_valves_sorted.len() as u32));
 
-                best_complete_release =
-                    std::cmp::max(open_valve_solution.expected_release, best_complete_release);
+        for selector in 0..2usize.pow(non_zero_valves_sorted.len() as u32) {
+            trace!("Selector: {selector}");
+            let mut subset: HashMap<String, Valve> = HashMap::new();
+            subset.insert("AA".to_string(), all_valves.get("AA").unwrap().clone());
+            for (valve_idx, &valve) in non_zero_valves_sorted.iter().enumerate() {
+                let bit_selector = 1 << valve_idx;
 
-                if let Some(next_potential_solution) = solutions_queue.front() {
-                    if open_valve_solution.current_time >= next_potential_solution.current_time {
-                        solutions_queue.push_front(open_valve_solution);
-                    } else {
-                        solutions_queue.push_back(open_valve_solution);
-                    }
-                } else {
-                    solutions_queue.push_back(open_valve_solution);
+                if (selector & bit_selector) >> valve_idx == 1 {
+                    subset.insert(valve.name.clone(), valve.clone());
                 }
             }
 
-            for (destination_name, &distance) in current_valve.tunnels_lead_to.iter() {
-                if current_solution.opened_valves.contains(destination_name)
-                    || (time_limit - current_solution.current_time) < distance
-                    || !all_valves.contains_key(destination_name)
-                {
-                    continue;
-                }
-
-                let new_solution = ProblemSolutionPart1 {
-                    current_valve: destination_name.to_string(),
-                    expected_release: current_solution.expected_release,
-                    current_time: current_solution.current_time + distance,
-                    opened_valves: current_solution.opened_valves.clone(),
-                };
+            let subset_non_zero_valves_sorted = sorted_non_zero_valves(&subset);
 
-                let release_upper_bound = new_solution.release_upper_bound(
-                    all_valves,
-                    non_zero_valves_sorted,
-                    time_limit,
-                );
-
-                if release_upper_bound < best_complete_release {
-                    continue;
-                }
+            let best_subset_solution =
+                solve(input.time_limits[1], &subset, &subset_non_zero_valves_sorted);
+            best_solution_per_subset.push(best_subset_solution);
+        }
 
-                best_complete_release =
-                    std::cmp::max(new_solution.expected_release, best_complete_release);
+        let subset_count = best_solution_per_subset.len();
 
-                if let Some(next_potential_solution) = solutions_queue.front() {
-                    if new_solution.current_time >= next_potential_solution.current_time {
-                        solutions_queue.push_front(new_solution);
-                    } else {
-                        solutions_queue.push_back(new_solution);
-                    }
-                } else {
-                    solutions_queue.push_back(new_solution);
-                }
-            }
-        }
-        best_complete_release
-    }
+        let complementary_mask = subset_count - 1;
 
-    let best_complete_release_part1 = solve(TIME_LIMIT_PART1, &all_valves, &non_zero_valves_sorted);
+        let mut part_2_solution = 0;
 
-    println!("Part 1: {best_complete_release_part1}");
+        for subset in 0..subset_count {
+            let complementary_subset = (subset ^ complementary_mask) % subset_count;
 
-    const TIME_LIMIT_PART2: u64 = 26;
+            part_2_solution = std::cmp::max(
+                part_2_solution,
+                best_solution_per_subset[subset] + best_solution_per_subset[complementary_subset],
+            );
+        }
 
-    let mut best_solution_per_subset: Vec<u64> =
-        Vec::with_capacity(2_usize.pow(non_zero_valves_sorted.len() as u32));
+        part_2_solution.into()
+    }
 
-    for selector in 0..2usize.pow(non_zero_valves_sorted.len() as u32) {
-        // println!("Selector: {selector}");
-        let mut subset: HashMap<String, Valve> = HashMap::new();
-        subset.insert("AA".to_string(), all_valves.get("AA").unwrap().clone());
-        for (valve_idx, &valve) in non_zero_valves_sorted.iter().enumerate() {
-            let bit_selector = 1 << valve_idx;
+    // `size` valves, a quarter of them working up to the 15 of the puzzle inputs
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let mut names: Vec<String> = (b'A'..=b'Z')
+            .flat_map(|first| (b'A'..=b'Z').map(move |second| [first, second]))
+            .map(|letters| String::from_utf8(letters.to_vec()).unwrap())
+            .filter(|name| name != "AA")
+            .collect();
+        rng.shuffle(&mut names);
+        names.truncate(size.clamp(1, 676) - 1);
+        names.insert(0, "AA".to_string());
 
-            if (selector & bit_selector) >> valve_idx == 1 {
-                subset.insert(valve.name.clone(), valve.clone());
+        let mut flow_rates = vec![0; names.len()];
+        for flow_rate in flow_rates.iter_mut().skip(1).take((names.len() / 4).min(15)) {
+            *flow_rate = rng.range(3..=25);
+        }
+        rng.shuffle(&mut flow_rates[1..]);
+
+        // Connected by a random tree, then a few more tunnels
+        let mut tunnels = vec![BTreeSet::new(); names.len()];
+        let mut connect = |a: usize, b: usize| {
+            if a != b {
+                tunnels[a].insert(b);
+                tunnels[b].insert(a);
             }
+        };
+        for valve in 1..names.len() {
+            connect(valve, rng.range(0..valve));
+        }
+        for _ in 0..names.len() / 2 {
+            connect(rng.range(0..names.len()), rng.range(0..names.len()));
         }
 
-        let mut subset_non_zero_valves_sorted: Vec<_> = subset
-            .iter()
-            .filter(|&x| x.1.pressure_release_per_minute != 0)
-            .map(|x| x.1)
+        let scan = (0..names.len())
+            .map(|valve| {
+                let leads_to: Vec<&str> = tunnels[valve]
+                    .iter()
+                    .map(|&next| names[next].as_str())
+                    .collect();
+                let tunnels = match leads_to.len() {
+                    1 => format!("tunnel leads to valve {}", leads_to[0]),
+                    _ => format!("tunnels lead to valves {}", leads_to.join(", ")),
+                };
+                format!("Valve {} has flow rate={}; {tunnels}\n", names[valve], flow_rates[valve])
+            })
             .collect();
-        subset_non_zero_valves_sorted.sort_by_key(|x| x.pressure_release_per_minute);
-        subset_non_zero_valves_sorted.reverse();
-        let subset_non_zero_valves_sorted = subset_non_zero_valves_sorted;
 
-        let best_subset_solution = solve(TIME_LIMIT_PART2, &subset, &subset_non_zero_valves_sorted);
-        best_solution_per_subset.push(best_subset_solution);
+        Some(scan)
     }
+}
 
-    let subset_count = best_solution_per_subset.len();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day16::parse(EXAMPLE).unwrap();
+        assert_eq!(Day16::part_1(&input), Answer::Number(1651));
+    }
 
-    let complementary_mask = subset_count - 1;
+    #[test]
+    fn part_2_example() {
+        let input = Day16::parse(EXAMPLE).unwrap();
+        assert_eq!(Day16::part_2(&input), Answer::Number(1707));
+    }
 
-    let mut part_2_solution = 0;
+    /// Most pressure released with each set of opened valves, as bits of the working valves,
+    /// trying every order of opening them
+    fn reference_releases(data: &str, minutes: u64) -> HashMap<u64, u64> {
+        let valves: Vec<Vec<&str>> = data
+            .lines()
+            .map(|line| {
+                line.split([' ', '=', ';', ','])
+                    .filter(|word| !word.is_empty())
+                    .collect()
+            })
+            .collect();
+        let index = |name: &str| valves.iter().position(|words| words[1] == name).unwrap();
+
+        // Shortest distances between all the valves, Floyd-Warshall
+        let count = valves.len();
+        let mut distances = vec![vec![u64::MAX / 4; count]; count];
+        for (valve, words) in valves.iter().enumerate() {
+            distances[valve][valve] = 0;
+            for tunnel in &words[10..] {
+                distances[valve][index(tunnel)] = 1;
+            }
+        }
+        for via in 0..count {
+            for from in 0..count {
+                for to in 0..count {
+                    let through = distances[from][via] + distances[via][to];
+                    distances[from][to] = distances[from][to].min(through);
+                }
+            }
+        }
 
-    for subset in 0..subset_count {
-        let complementary_subset = (subset ^ complementary_mask) % subset_count;
+        let working: Vec<(usize, u64)> = valves
+            .iter()
+            .enumerate()
+            .map(|(valve, words)| (valve, words[5].parse().unwrap()))
+            .filter(|&(_, flow)| flow != 0)
+            .collect();
 
-        part_2_solution = std::cmp::max(
-            part_2_solution,
-            best_solution_per_subset[subset] + best_solution_per_subset[complementary_subset],
-        );
+        let mut releases = HashMap::new();
+        let mut stack = vec![(index("AA"), minutes, 0u64, 0)];
+        while let Some((valve, minutes_left, opened, released)) = stack.pop() {
+            let best = releases.entry(opened).or_insert(0);
+            *best = released.max(*best);
+
+            for (bit, &(next, flow)) in working.iter().enumerate() {
+                let cost = distances[valve][next] + 1;
+                if opened & 1 << bit == 0 && cost < minutes_left {
+                    let left = minutes_left - cost;
+                    stack.push((next, left, opened | 1 << bit, released + flow * left));
+                }
+            }
+        }
+
+        releases
     }
 
-    println!("Part 2: {part_2_solution}");
+    #[test]
+    fn matches_reference() {
+        for seed in 0..10 {
+            let data = Day16::generate(&mut Rng::new(seed), 24, &Params::default()).unwrap();
+            let input = Day16::parse(&data).unwrap();
+
+            let expected = *reference_releases(&data, 30).values().max().unwrap();
+            assert_eq!(Day16::part_1(&input), Answer::from(expected), "seed {seed}");
+
+            // The elephant and you open disjoint sets of valves
+            let releases = reference_releases(&data, 26);
+            let expected = releases
+                .iter()
+                .flat_map(|(&mine, &released)| {
+                    releases
+                        .iter()
+                        .filter(move |&(&elephant, _)| mine & elephant == 0)
+                        .map(move |(_, &elephant_released)| released + elephant_released)
+                })
+                .max()
+                .unwrap();
+            assert_eq!(Day16::part_2(&input), Answer::from(expected), "seed {seed}");
+        }
+    }
 }
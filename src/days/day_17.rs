@@ -1,4 +1,8 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::utils::grid::Grid;
+use super::utils::rng::Rng;
+use super::utils::vector::Vec2;
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
 
 /// --- Day 17: Pyroclastic Flow ---
 /// Your handheld device has located an alternative exit from the cave for you and the elephants.
@@ -353,580 +357,759 @@ use super::load_file;
 /// the tower of rocks will be 3068 units tall.
 ///
 /// How many units tall will the tower of rocks be after 2022 rocks have stopped falling?
-pub fn day_17() {
-    let data = load_file(17);
-
-    let jet_iterator = data.trim().char_indices().into_iter().cycle();
-
-    const HORIZONTAL_LINE_CONTENT: [[bool; 4]; 1] = [[true, true, true, true]];
-    const CROSS_CONTENT: [[bool; 3]; 3] = [
-        [false, true, false],
-        [true, true, true],
-        [false, true, false],
-    ];
-    // Content inverted vertically as y represents the bottow corner
-    const INVERTED_L_CONTENT: [[bool; 3]; 3] = [
-        [true, true, true],
-        [false, false, true],
-        [false, false, true],
-    ];
-    const VERTICAL_LINE_CONTENT: [[bool; 1]; 4] = [[true], [true], [true], [true]];
-    const SQUARE_CONTENT: [[bool; 2]; 2] = [[true, true], [true, true]];
-
-    const WORLD_WIDTH: usize = 7;
-
-    pub trait Shape {
-        fn new(x: usize, y: usize) -> Self
-        where
-            Self: Sized;
-
-        fn x(&self) -> usize;
-        fn y(&self) -> usize;
-
-        fn w(&self) -> usize;
-        fn h(&self) -> usize;
-
-        fn right(&self) -> usize {
-            self.x() + self.w()
-        }
+pub struct Day17;
+
+pub const HORIZONTAL_LINE_CONTENT: [[bool; 4]; 1] = [[true, true, true, true]];
+pub const CROSS_CONTENT: [[bool; 3]; 3] = [
+    [false, true, false],
+    [true, true, true],
+    [false, true, false],
+];
+// Content inverted vertically as y represents the bottow corner
+pub const INVERTED_L_CONTENT: [[bool; 3]; 3] = [
+    [true, true, true],
+    [false, false, true],
+    [false, false, true],
+];
+pub const VERTICAL_LINE_CONTENT: [[bool; 1]; 4] = [[true], [true], [true], [true]];
+pub const SQUARE_CONTENT: [[bool; 2]; 2] = [[true, true], [true, true]];
+
+pub const WORLD_WIDTH: usize = 7;
+
+/// Depth below which the columns are not compared when looking for a cycle, a column the jets
+/// never push a rock into would otherwise get deeper with each rock and no state would repeat
+const PROFILE_DEPTH: usize = 64;
+
+pub trait Shape {
+    fn new(x: usize, y: usize) -> Self
+    where
+        Self: Sized;
+
+    fn x(&self) -> usize;
+    fn y(&self) -> usize;
+
+    fn w(&self) -> usize;
+    fn h(&self) -> usize;
+
+    fn right(&self) -> usize {
+        self.x() + self.w()
+    }
+
+    fn top(&self) -> usize {
+        self.y() + self.h()
+    }
+
+    fn set_y(&mut self, new_y: usize);
+    fn set_x(&mut self, new_x: usize);
+
+    fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>>;
 
-        fn top(&self) -> usize {
-            self.y() + self.h()
+    fn move_down(&mut self, world: &Grid<bool>) -> bool {
+        if self.y() == 0 {
+            return false;
         }
 
-        fn set_y(&mut self, new_y: usize);
-        fn set_x(&mut self, new_x: usize);
+        let new_y = self.y() - 1;
 
-        fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>>;
+        let content_iter = self.content_iter();
 
-        fn move_down(&mut self, world: &[Vec<bool>]) -> bool {
-            if self.y() == 0 {
+        for (diff_y, diff_x, is_present) in content_iter {
+            if !is_present {
+                continue;
+            }
+
+            let dst_x = self.x() + diff_x;
+            let dst_y = new_y + diff_y;
+
+            // If something is present, we can't move
+            if world[Vec2::new(dst_x, dst_y)] {
                 return false;
             }
+        }
 
-            let new_y = self.y() - 1;
+        self.set_y(new_y);
+        true
+    }
 
-            let content_iter = self.content_iter();
+    fn move_left(&mut self, world: &Grid<bool>) {
+        if self.x() == 0 {
+            return;
+        }
 
-            for (diff_y, diff_x, is_present) in content_iter {
-                if !is_present {
-                    continue;
-                }
+        let new_x = self.x() - 1;
 
-                let dst_x = self.x() + diff_x;
-                let dst_y = new_y + diff_y;
+        let content_iter = self.content_iter();
 
-                // If something is present, we can't move
-                if world[dst_y][dst_x] {
-                    return false;
-                }
+        for (diff_y, diff_x, is_present) in content_iter {
+            if !is_present {
+                continue;
             }
 
-            self.set_y(new_y);
-            true
-        }
+            let dst_x = new_x + diff_x;
+            let dst_y = self.y() + diff_y;
 
-        fn move_left(&mut self, world: &[Vec<bool>]) {
-            if self.x() == 0 {
+            // If something is present, we can't move
+            if world[Vec2::new(dst_x, dst_y)] {
                 return;
             }
+        }
 
-            let new_x = self.x() - 1;
+        self.set_x(new_x);
+    }
 
-            let content_iter = self.content_iter();
+    fn move_right(&mut self, world: &Grid<bool>) {
+        if self.right() >= WORLD_WIDTH {
+            return;
+        }
 
-            for (diff_y, diff_x, is_present) in content_iter {
-                if !is_present {
-                    continue;
-                }
+        let new_x = self.x() + 1;
 
-                let dst_x = new_x + diff_x;
-                let dst_y = self.y() + diff_y;
+        let content_iter = self.content_iter();
 
-                // If something is present, we can't move
-                if world[dst_y][dst_x] {
-                    return;
-                }
+        for (diff_y, diff_x, is_present) in content_iter {
+            if !is_present {
+                continue;
             }
 
-            self.set_x(new_x);
-        }
+            let dst_x = new_x + diff_x;
+            let dst_y = self.y() + diff_y;
 
-        fn move_right(&mut self, world: &[Vec<bool>]) {
-            if self.right() >= WORLD_WIDTH {
+            // If something is present, we can't move
+            if world[Vec2::new(dst_x, dst_y)] {
                 return;
             }
+        }
 
-            let new_x = self.x() + 1;
-
-            let content_iter = self.content_iter();
-
-            for (diff_y, diff_x, is_present) in content_iter {
-                if !is_present {
-                    continue;
-                }
+        self.set_x(new_x);
+    }
 
-                let dst_x = new_x + diff_x;
-                let dst_y = self.y() + diff_y;
+    fn draw(&self, world: &mut Grid<bool>) {
+        let content_iter = self.content_iter();
 
-                // If something is present, we can't move
-                if world[dst_y][dst_x] {
-                    return;
-                }
+        for (diff_y, diff_x, is_present) in content_iter {
+            if !is_present {
+                continue;
             }
 
-            self.set_x(new_x);
+            let dst_x = self.x() + diff_x;
+            let dst_y = self.y() + diff_y;
+
+            world[Vec2::new(dst_x, dst_y)] = true;
         }
+    }
+}
 
-        fn draw(&self, world: &mut [Vec<bool>]) {
-            let content_iter = self.content_iter();
+pub struct HorizontalLine {
+    x: usize,
+    y: usize,
+}
 
-            for (diff_y, diff_x, is_present) in content_iter {
-                if !is_present {
-                    continue;
-                }
+impl Shape for HorizontalLine {
+    fn new(x: usize, y: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self { x, y }
+    }
 
-                let dst_x = self.x() + diff_x;
-                let dst_y = self.y() + diff_y;
+    fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
+        Box::new(
+            HORIZONTAL_LINE_CONTENT
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(idx, is_present)| {
+                    let row_content_len = HORIZONTAL_LINE_CONTENT[0].len();
+                    let y = idx / row_content_len;
+                    let x = idx % row_content_len;
+                    (y, x, is_present)
+                }),
+        )
+    }
 
-                world[dst_y][dst_x] = true;
-            }
-        }
+    fn set_x(&mut self, new_x: usize) {
+        self.x = new_x
     }
 
-    struct HorizontalLine {
-        x: usize,
-        y: usize,
+    fn set_y(&mut self, new_y: usize) {
+        self.y = new_y
     }
 
-    impl Shape for HorizontalLine {
-        fn new(x: usize, y: usize) -> Self
-        where
-            Self: Sized,
-        {
-            Self { x, y }
-        }
+    fn x(&self) -> usize {
+        self.x
+    }
 
-        fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
-            Box::new(
-                HORIZONTAL_LINE_CONTENT
-                    .into_iter()
-                    .flatten()
-                    .enumerate()
-                    .map(|(idx, is_present)| {
-                        let row_content_len = HORIZONTAL_LINE_CONTENT[0].len();
-                        let y = idx / row_content_len;
-                        let x = idx % row_content_len;
-                        (y, x, is_present)
-                    }),
-            )
-        }
+    fn y(&self) -> usize {
+        self.y
+    }
 
-        fn set_x(&mut self, new_x: usize) {
-            self.x = new_x
-        }
+    fn h(&self) -> usize {
+        1
+    }
 
-        fn set_y(&mut self, new_y: usize) {
-            self.y = new_y
-        }
+    fn w(&self) -> usize {
+        4
+    }
+}
 
-        fn x(&self) -> usize {
-            self.x
-        }
+pub struct Cross {
+    x: usize,
+    y: usize,
+}
 
-        fn y(&self) -> usize {
-            self.y
-        }
+impl Shape for Cross {
+    fn new(x: usize, y: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self { x, y }
+    }
 
-        fn h(&self) -> usize {
-            1
-        }
+    fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
+        Box::new(
+            CROSS_CONTENT
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(idx, is_present)| {
+                    let row_content_len = CROSS_CONTENT[0].len();
+                    let y = idx / row_content_len;
+                    let x = idx % row_content_len;
+                    (y, x, is_present)
+                }),
+        )
+    }
 
-        fn w(&self) -> usize {
-            4
-        }
+    fn set_x(&mut self, new_x: usize) {
+        self.x = new_x
     }
 
-    struct Cross {
-        x: usize,
-        y: usize,
+    fn set_y(&mut self, new_y: usize) {
+        self.y = new_y
     }
 
-    impl Shape for Cross {
-        fn new(x: usize, y: usize) -> Self
-        where
-            Self: Sized,
-        {
-            Self { x, y }
-        }
+    fn x(&self) -> usize {
+        self.x
+    }
 
-        fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
-            Box::new(
-                CROSS_CONTENT
-                    .into_iter()
-                    .flatten()
-                    .enumerate()
-                    .map(|(idx, is_present)| {
-                        let row_content_len = CROSS_CONTENT[0].len();
-                        let y = idx / row_content_len;
-                        let x = idx % row_content_len;
-                        (y, x, is_present)
-                    }),
-            )
-        }
+    fn y(&self) -> usize {
+        self.y
+    }
 
-        fn set_x(&mut self, new_x: usize) {
-            self.x = new_x
-        }
+    fn h(&self) -> usize {
+        3
+    }
 
-        fn set_y(&mut self, new_y: usize) {
-            self.y = new_y
-        }
+    fn w(&self) -> usize {
+        3
+    }
+}
 
-        fn x(&self) -> usize {
-            self.x
-        }
+pub struct InvertedL {
+    x: usize,
+    y: usize,
+}
 
-        fn y(&self) -> usize {
-            self.y
-        }
+impl Shape for InvertedL {
+    fn new(x: usize, y: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self { x, y }
+    }
 
-        fn h(&self) -> usize {
-            3
-        }
+    fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
+        Box::new(
+            INVERTED_L_CONTENT
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(idx, is_present)| {
+                    let row_content_len = INVERTED_L_CONTENT[0].len();
+                    let y = idx / row_content_len;
+                    let x = idx % row_content_len;
+                    (y, x, is_present)
+                }),
+        )
+    }
 
-        fn w(&self) -> usize {
-            3
-        }
+    fn set_x(&mut self, new_x: usize) {
+        self.x = new_x
     }
 
-    struct InvertedL {
-        x: usize,
-        y: usize,
+    fn set_y(&mut self, new_y: usize) {
+        self.y = new_y
     }
 
-    impl Shape for InvertedL {
-        fn new(x: usize, y: usize) -> Self
-        where
-            Self: Sized,
-        {
-            Self { x, y }
-        }
+    fn x(&self) -> usize {
+        self.x
+    }
 
-        fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
-            Box::new(INVERTED_L_CONTENT.into_iter().flatten().enumerate().map(
-                |(idx, is_present)| {
-                    let row_content_len = INVERTED_L_CONTENT[0].len();
-                    let y = idx / row_content_len;
-                    let x = idx % row_content_len;
-                    (y, x, is_present)
-                },
-            ))
-        }
+    fn y(&self) -> usize {
+        self.y
+    }
 
-        fn set_x(&mut self, new_x: usize) {
-            self.x = new_x
-        }
+    fn h(&self) -> usize {
+        3
+    }
 
-        fn set_y(&mut self, new_y: usize) {
-            self.y = new_y
-        }
+    fn w(&self) -> usize {
+        3
+    }
+}
 
-        fn x(&self) -> usize {
-            self.x
-        }
+pub struct VerticalLine {
+    x: usize,
+    y: usize,
+}
 
-        fn y(&self) -> usize {
-            self.y
-        }
+impl Shape for VerticalLine {
+    fn new(x: usize, y: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self { x, y }
+    }
 
-        fn h(&self) -> usize {
-            3
-        }
+    fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
+        Box::new(VERTICAL_LINE_CONTENT.into_iter().flatten().enumerate().map(
+            |(idx, is_present)| {
+                let row_content_len = VERTICAL_LINE_CONTENT[0].len();
+                let y = idx / row_content_len;
+                let x = idx % row_content_len;
+                (y, x, is_present)
+            },
+        ))
+    }
 
-        fn w(&self) -> usize {
-            3
-        }
+    fn set_x(&mut self, new_x: usize) {
+        self.x = new_x
     }
 
-    struct VerticalLine {
-        x: usize,
-        y: usize,
+    fn set_y(&mut self, new_y: usize) {
+        self.y = new_y
     }
 
-    impl Shape for VerticalLine {
-        fn new(x: usize, y: usize) -> Self
-        where
-            Self: Sized,
-        {
-            Self { x, y }
-        }
+    fn x(&self) -> usize {
+        self.x
+    }
+
+    fn y(&self) -> usize {
+        self.y
+    }
+
+    fn h(&self) -> usize {
+        4
+    }
+
+    fn w(&self) -> usize {
+        1
+    }
+}
+
+pub struct Square {
+    x: usize,
+    y: usize,
+}
+
+impl Shape for Square {
+    fn new(x: usize, y: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self { x, y }
+    }
 
-        fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
-            Box::new(VERTICAL_LINE_CONTENT.into_iter().flatten().enumerate().map(
-                |(idx, is_present)| {
-                    let row_content_len = VERTICAL_LINE_CONTENT[0].len();
+    fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
+        Box::new(
+            SQUARE_CONTENT
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(idx, is_present)| {
+                    let row_content_len = SQUARE_CONTENT[0].len();
                     let y = idx / row_content_len;
                     let x = idx % row_content_len;
                     (y, x, is_present)
-                },
-            ))
-        }
-
-        fn set_x(&mut self, new_x: usize) {
-            self.x = new_x
-        }
+                }),
+        )
+    }
 
-        fn set_y(&mut self, new_y: usize) {
-            self.y = new_y
-        }
+    fn set_x(&mut self, new_x: usize) {
+        self.x = new_x
+    }
 
-        fn x(&self) -> usize {
-            self.x
-        }
+    fn set_y(&mut self, new_y: usize) {
+        self.y = new_y
+    }
 
-        fn y(&self) -> usize {
-            self.y
-        }
+    fn x(&self) -> usize {
+        self.x
+    }
 
-        fn h(&self) -> usize {
-            4
-        }
+    fn y(&self) -> usize {
+        self.y
+    }
 
-        fn w(&self) -> usize {
-            1
-        }
+    fn h(&self) -> usize {
+        2
     }
 
-    struct Square {
-        x: usize,
-        y: usize,
+    fn w(&self) -> usize {
+        2
     }
+}
 
-    impl Shape for Square {
-        fn new(x: usize, y: usize) -> Self
-        where
-            Self: Sized,
-        {
-            Self { x, y }
-        }
+/// Chamber drawn top down as in the puzzle statement, `@` for the falling rock
+fn render(world: &Grid<bool>, shape: &dyn Shape) -> String {
+    let mut falling = Grid::new(world.width(), world.height(), false);
+    shape.draw(&mut falling);
+
+    world
+        .rows()
+        .zip(falling.rows())
+        .rev()
+        .map(|(row, falling_row)| {
+            row.iter()
+                .zip(falling_row)
+                .map(|(&rock, &falling)| match (rock, falling) {
+                    (_, true) => '@',
+                    (true, false) => '#',
+                    (false, false) => '.',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-        fn content_iter(&self) -> Box<dyn Iterator<Item = (usize, usize, bool)>> {
-            Box::new(
-                SQUARE_CONTENT
-                    .into_iter()
-                    .flatten()
-                    .enumerate()
-                    .map(|(idx, is_present)| {
-                        let row_content_len = SQUARE_CONTENT[0].len();
-                        let y = idx / row_content_len;
-                        let x = idx % row_content_len;
-                        (y, x, is_present)
-                    }),
-            )
+pub type StateChangeMap =
+    std::collections::HashMap<(usize, usize, usize, [usize; WORLD_WIDTH]), (usize, usize)>;
+
+pub fn solve<const TRACK_CYCLES: bool>(
+    number_of_shapes: usize,
+    mut jet_iterator: std::iter::Cycle<std::str::CharIndices>,
+) -> (usize, StateChangeMap, (usize, usize, usize, usize)) {
+    let mut lowest_empty_location = 0usize;
+    let mut world = Grid::new(WORLD_WIDTH, 0, false);
+
+    // (shape_idx % 5, jet_idx, height_change), (shape_idx, height)
+    let mut state_change: StateChangeMap = std::collections::HashMap::new();
+    let mut cycle_len = 0;
+    let mut cycle_start_idx = 0;
+    let mut cycle_height = 0;
+    let mut cycle_start_height = 0;
+
+    'outer: for shape_index in 0..number_of_shapes {
+        // x, y represent the bottom left corner of the shape
+        let shape_x = 2usize;
+        let shape_y = lowest_empty_location + 3;
+
+        let mut current_shape: Box<dyn Shape> = match shape_index % 5 {
+            0 => Box::new(HorizontalLine::new(shape_x, shape_y)),
+            1 => Box::new(Cross::new(shape_x, shape_y)),
+            2 => Box::new(InvertedL::new(shape_x, shape_y)),
+            3 => Box::new(VerticalLine::new(shape_x, shape_y)),
+            4 => Box::new(Square::new(shape_x, shape_y)),
+            _ => unreachable!(),
+        };
+        let current_shape = current_shape.as_mut();
+
+        let current_world_height = world.height();
+        let top_y = current_shape.top();
+
+        if top_y > current_world_height {
+            for _ in current_world_height..top_y {
+                world.push_row([false; WORLD_WIDTH]);
+            }
         }
 
-        fn set_x(&mut self, new_x: usize) {
-            self.x = new_x
-        }
+        trace!("{}", render(&world, current_shape));
 
-        fn set_y(&mut self, new_y: usize) {
-            self.y = new_y
-        }
+        loop {
+            let (jet_idx, next_jet) = jet_iterator.next().unwrap();
 
-        fn x(&self) -> usize {
-            self.x
-        }
+            match next_jet {
+                '>' => current_shape.move_right(&world),
+                '<' => current_shape.move_left(&world),
+                _ => unreachable!(),
+            }
 
-        fn y(&self) -> usize {
-            self.y
-        }
+            trace!("{}", render(&world, current_shape));
+
+            let moved = current_shape.move_down(&world);
+
+            trace!("{}", render(&world, current_shape));
+
+            if !moved {
+                current_shape.draw(&mut world);
+                let previous_height = lowest_empty_location;
+                lowest_empty_location = lowest_empty_location.max(current_shape.top());
+
+                if TRACK_CYCLES {
+                    // Depth of the highest rock of each column, up to the profile depth
+                    let depth_or_limit: [usize; WORLD_WIDTH] = std::array::from_fn(|x| {
+                        world
+                            .column(x)
+                            .take(lowest_empty_location)
+                            .rev()
+                            .take(PROFILE_DEPTH)
+                            .position(|&rock| rock)
+                            .unwrap_or(PROFILE_DEPTH)
+                    });
+
+                    let k = (
+                        shape_index % 5,
+                        jet_idx,
+                        lowest_empty_location - previous_height,
+                        depth_or_limit,
+                    );
+                    let v = (shape_index, lowest_empty_location);
+
+                    // The start of the cycle stays in the map, part 2 looks its height up when
+                    // no rock remains after the last full cycle
+                    if let Some(&previous) = state_change.get(&k) {
+                        cycle_len = v.0 - previous.0;
+                        cycle_start_idx = previous.0;
+                        cycle_height = v.1 - previous.1;
+                        cycle_start_height = previous.1;
+                        debug!("Cycle found, previous: {previous:?}, new: {v:?}");
+                        break 'outer;
+                    }
+                    state_change.insert(k, v);
+                }
 
-        fn h(&self) -> usize {
-            2
+                break;
+            }
         }
+    }
 
-        fn w(&self) -> usize {
-            2
-        }
+    (
+        lowest_empty_location,
+        state_change,
+        (cycle_len, cycle_start_idx, cycle_start_height, cycle_height),
+    )
+}
+
+const ROCKS_PART_1: Param = Param {
+    name: "rocks_1",
+    default: 2022,
+    min: 0,
+    max: 1_000_000,
+    doc: "rocks fallen when measuring the tower in part 1",
+};
+
+const ROCKS_PART_2: Param = Param {
+    name: "rocks_2",
+    default: 1_000_000_000_000,
+    min: 0,
+    max: 1_000_000_000_000_000,
+    doc: "rocks fallen when measuring the tower in part 2",
+};
+
+pub struct Chamber {
+    pub jets: String,
+    pub rocks: [usize; 2],
+}
+
+impl Solution for Day17 {
+    const DAY: usize = 17;
+
+    const PARAMS: &'static [Param] = &[ROCKS_PART_1, ROCKS_PART_2];
+
+    type Input = Chamber;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
     }
 
-    type StateChangeMap =
-        std::collections::HashMap<(usize, usize, usize, [usize; WORLD_WIDTH]), (usize, usize)>;
-
-    fn solve<const TRACK_CYCLES: bool>(
-        number_of_shapes: usize,
-        mut jet_iterator: std::iter::Cycle<std::str::CharIndices>,
-    ) -> (usize, StateChangeMap, (usize, usize, usize, usize)) {
-        let mut lowest_empty_location = 0usize;
-        let mut world: Vec<Vec<bool>> = vec![];
-
-        // (shape_idx % 5, jet_idx, height_change), (shape_idx, height)
-        let mut state_change: StateChangeMap = std::collections::HashMap::new();
-        let mut cycle_len = 0;
-        let mut cycle_start_idx = 0;
-        let mut cycle_height = 0;
-        let mut cycle_start_height = 0;
-
-        'outer: for shape_index in 0..number_of_shapes {
-            // x, y represent the bottom left corner of the shape
-            let shape_x = 2usize;
-            let shape_y = lowest_empty_location + 3;
-
-            let mut current_shape: Box<dyn Shape> = match shape_index % 5 {
-                0 => Box::new(HorizontalLine::new(shape_x, shape_y)),
-                1 => Box::new(Cross::new(shape_x, shape_y)),
-                2 => Box::new(InvertedL::new(shape_x, shape_y)),
-                3 => Box::new(VerticalLine::new(shape_x, shape_y)),
-                4 => Box::new(Square::new(shape_x, shape_y)),
-                _ => unreachable!(),
-            };
-            let current_shape = current_shape.as_mut();
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let jets = data.trim();
 
-            let current_world_height = world.len();
-            let top_y = current_shape.top();
+        if jets.is_empty() {
+            return Err(ParseError::at(data, jets, "jets"));
+        }
+        if let Some(idx) = jets.find(|c| c != '<' && c != '>') {
+            let c = jets[idx..].chars().next().unwrap_or_default();
+            return Err(ParseError::at(
+                data,
+                &jets[idx..idx + c.len_utf8()],
+                "< or >",
+            ));
+        }
 
-            if top_y > current_world_height {
-                for _ in current_world_height..top_y {
-                    world.push(vec![false; WORLD_WIDTH]);
-                }
-            }
+        Ok(Chamber {
+            jets: jets.to_string(),
+            rocks: [
+                options.params.get(&ROCKS_PART_1),
+                options.params.get(&ROCKS_PART_2),
+            ],
+        })
+    }
 
-            // let mut cloned_world = world.clone();
-            // current_shape.draw(&mut cloned_world);
-            // cloned_world.reverse();
+    fn part_1(input: &Self::Input) -> Answer {
+        let jet_iterator = input.jets.char_indices().cycle();
 
-            // let newline = ['\n'];
-            // let visual: String = cloned_world
-            //     .iter()
-            //     .flat_map(|x| x.iter().map(|&y| if y { '@' } else { '.' }).chain(newline))
-            //     .collect();
+        let (part_1_solution, _, _) = solve::<false>(input.rocks[0], jet_iterator);
 
-            // println!("{visual}");
+        part_1_solution.into()
+    }
 
-            loop {
-                let (jet_idx, next_jet) = jet_iterator.next().unwrap();
+    fn part_2(input: &Self::Input) -> Answer {
+        let jet_iterator = input.jets.char_indices().cycle();
 
-                match next_jet {
-                    '>' => current_shape.move_right(&world),
-                    '<' => current_shape.move_left(&world),
-                    _ => unreachable!(),
-                }
+        let (_, state_change, (cycle_len, cycle_start_idx, cycle_start_height, cycle_height)) =
+            solve::<true>(100_000_000, jet_iterator);
 
-                // let mut cloned_world = world.clone();
-                // current_shape.draw(&mut cloned_world);
-                // cloned_world.reverse();
-
-                // let newline = ['\n'];
-                // let visual: String = cloned_world
-                //     .iter()
-                //     .flat_map(|x| x.iter().map(|&y| if y { '@' } else { '.' }).chain(newline))
-                //     .collect();
-
-                // println!("{visual}");
-
-                let moved = current_shape.move_down(&world);
-
-                // let mut cloned_world = world.clone();
-                // current_shape.draw(&mut cloned_world);
-                // cloned_world.reverse();
-
-                // let newline = ['\n'];
-                // let visual: String = cloned_world
-                //     .iter()
-                //     .flat_map(|x| x.iter().map(|&y| if y { '@' } else { '.' }).chain(newline))
-                //     .collect();
-
-                // println!("{visual}");
-
-                if !moved {
-                    current_shape.draw(&mut world);
-                    let previous_height = lowest_empty_location;
-                    lowest_empty_location = lowest_empty_location.max(current_shape.top());
-
-                    if TRACK_CYCLES {
-                        let mut depth_or_limit: [usize; WORLD_WIDTH] =
-                            [lowest_empty_location; WORLD_WIDTH];
-
-                        // Ugly, but I just want a solution
-                        'depth_record: for (depth_record_idx, depth_record) in
-                            depth_or_limit.iter_mut().enumerate()
-                        {
-                            for (depth_idx, row) in
-                                world[..lowest_empty_location].iter().rev().enumerate()
-                            {
-                                // Blocked
-                                if row[depth_record_idx] {
-                                    *depth_record = depth_idx;
-                                    continue 'depth_record;
-                                }
-                            }
-                        }
-
-                        let k = (
-                            shape_index % 5,
-                            jet_idx,
-                            lowest_empty_location - previous_height,
-                            depth_or_limit,
-                        );
-                        let v = (shape_index, lowest_empty_location);
-
-                        if let Some(previous) = state_change.insert(k, v) {
-                            cycle_len = v.0 - previous.0;
-                            cycle_start_idx = previous.0;
-                            cycle_height = v.1 - previous.1;
-                            cycle_start_height = previous.1;
-                            // println!("New: {v:?}");
-                            // println!("Previous: {previous:?}");
-                            break 'outer;
-                        }
-                    }
+        debug!("Cycle len: {cycle_len}, start_idx: {cycle_start_idx}, height: {cycle_height}");
 
-                    break;
-                }
-            }
+        let target = input.rocks[1];
+
+        let idx_height: std::collections::HashMap<usize, usize> =
+            state_change.into_values().collect();
+
+        // Rocks fallen before the cycle repeats were all dropped, their heights are known
+        let Some(last_rock) = target.checked_sub(1) else {
+            return 0.into();
+        };
+        if let Some(&height) = idx_height.get(&last_rock) {
+            return height.into();
+        }
+        assert!(cycle_len != 0, "The tower does not repeat");
+        if last_rock == cycle_start_idx + cycle_len {
+            return (cycle_start_height + cycle_height).into();
         }
 
-        (
-            lowest_empty_location,
-            state_change,
-            (cycle_len, cycle_start_idx, cycle_start_height, cycle_height),
-        )
-    }
+        let start_rock_count = cycle_start_idx + 1;
+        let number_of_rocks_without_start = target - start_rock_count;
+        let number_of_cycles = number_of_rocks_without_start / cycle_len;
+        let remainder_rock = number_of_rocks_without_start % cycle_len;
+
+        debug!("Remainder: {remainder_rock}");
+
+        let height_without_remainder = cycle_start_height + number_of_cycles * cycle_height;
 
-    let (part_1_solution, _, _) = solve::<false>(2022, jet_iterator.clone());
+        debug!("Height without remainder: {height_without_remainder}");
 
-    println!("Part 1: {part_1_solution}");
+        let remainder_position_in_cycle = remainder_rock + cycle_start_idx;
 
-    let (_, state_change, (cycle_len, cycle_start_idx, cycle_start_height, cycle_height)) =
-        solve::<true>(100_000_000, jet_iterator.clone());
+        let remainder_height_diff =
+            idx_height.get(&remainder_position_in_cycle).unwrap() - cycle_start_height;
 
-    // println!("len: {cycle_len}, start_idx: {cycle_start_idx}, height: {cycle_height}");
+        let part_2_solution = height_without_remainder + remainder_height_diff;
 
-    // let target = 2022;
-    let target = 1_000_000_000_000;
+        part_2_solution.into()
+    }
 
-    let start_rock_count = cycle_start_idx + 1;
-    let number_of_rocks_without_start = target - start_rock_count;
-    let number_of_cycles = number_of_rocks_without_start / cycle_len;
-    let remainder_rock = number_of_rocks_without_start % cycle_len;
+    // `size` jets
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let jets: String = (0..size.max(1)).map(|_| *rng.choose(&['<', '>'])).collect();
 
-    // println!("remainder: {remainder_rock}");
+        Some(jets + "\n")
+    }
+}
 
-    let height_without_remainder = cycle_start_height + number_of_cycles * cycle_height;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // println!("Height without remainder: {height_without_remainder}");
+    const EXAMPLE: &str = "\
+>>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
+";
 
-    let remainder_position_in_cycle = remainder_rock + cycle_start_idx;
+    #[test]
+    fn part_1_example() {
+        let input = Day17::parse(EXAMPLE).unwrap();
+        assert_eq!(Day17::part_1(&input), Answer::Number(3068));
+    }
 
-    let idx_height: std::collections::HashMap<usize, usize> = state_change.into_values().collect();
+    #[test]
+    fn part_2_example() {
+        let input = Day17::parse(EXAMPLE).unwrap();
+        assert_eq!(Day17::part_2(&input), Answer::Number(1514285714288));
 
-    let remainder_height_diff =
-        idx_height.get(&remainder_position_in_cycle).unwrap() - cycle_start_height;
+        // The cycle extrapolation agrees with the simulation of part 1
+        let mut options = RunOptions::default();
+        options.params.set("rocks_2", 2022);
+        let input = Day17::parse_with(EXAMPLE, &options).unwrap();
+        assert_eq!(Day17::part_2(&input), Answer::Number(3068));
+    }
 
-    let part_2_solution = height_without_remainder + remainder_height_diff;
+    /// Height of the tower after `rock_count` rocks, dropping every single one of them
+    fn reference_height(jets: &str, rock_count: usize) -> usize {
+        // Cells of each rock from its bottom left corner, y going up
+        let rocks: [&[(usize, usize)]; 5] = [
+            &[(0, 0), (1, 0), (2, 0), (3, 0)],
+            &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)],
+            &[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
+            &[(0, 0), (0, 1), (0, 2), (0, 3)],
+            &[(0, 0), (1, 0), (0, 1), (1, 1)],
+        ];
+        let mut jets = jets.trim().chars().cycle();
+        let mut settled = std::collections::HashSet::new();
+        let mut height = 0;
+
+        for rock in rocks.iter().cycle().take(rock_count) {
+            let fits = |settled: &std::collections::HashSet<_>, x: usize, y: usize| {
+                rock.iter()
+                    .all(|&(dx, dy)| x + dx < WORLD_WIDTH && !settled.contains(&(x + dx, y + dy)))
+            };
 
-    // println!("Height {height}");
+            let (mut x, mut y): (usize, usize) = (2, height + 3);
+            loop {
+                let pushed = match jets.next() {
+                    Some('<') => x.checked_sub(1),
+                    _ => Some(x + 1),
+                };
+                if let Some(pushed) = pushed.filter(|&pushed| fits(&settled, pushed, y)) {
+                    x = pushed;
+                }
 
-    println!("Part 2: {part_2_solution}");
+                if y == 0 || !fits(&settled, x, y - 1) {
+                    break;
+                }
+                y -= 1;
+            }
+
+            for &(dx, dy) in rock.iter() {
+                settled.insert((x + dx, y + dy));
+                height = height.max(y + dy + 1);
+            }
+        }
+
+        height
+    }
+
+    #[test]
+    fn matches_reference() {
+        assert_eq!(reference_height(EXAMPLE, 2022), 3068);
+
+        let mut options = RunOptions::default();
+        options.params.set("rocks_2", 5000);
+
+        for seed in 0..10 {
+            let data = Day17::generate(&mut Rng::new(seed), 40, &options.params).unwrap();
+            let input = Day17::parse_with(&data, &options).unwrap();
+
+            let expected = reference_height(&data, 2022);
+            assert_eq!(Day17::part_1(&input), Answer::from(expected), "seed {seed}");
+            // Extrapolated from the cycle of the tower
+            let expected = reference_height(&data, 5000);
+            assert_eq!(Day17::part_2(&input), Answer::from(expected), "seed {seed}");
+
+            // Before, at and around the start and the end of the first cycle
+            let jets = data.trim().char_indices().cycle();
+            let (_, _, (cycle_len, cycle_start_idx, _, _)) = solve::<true>(100_000_000, jets);
+            let (start, end) = (cycle_start_idx, cycle_start_idx + cycle_len);
+            for rocks in [0, 1, 20, start, start + 1, start + 2, end, end + 1, end + 2] {
+                let mut options = RunOptions::default();
+                options.params.set("rocks_2", rocks as i64);
+                let input = Day17::parse_with(&data, &options).unwrap();
+
+                let expected = reference_height(&data, rocks);
+                let message = format!("seed {seed}, {rocks} rocks");
+                assert_eq!(Day17::part_2(&input), Answer::from(expected), "{message}");
+            }
+        }
+    }
 }
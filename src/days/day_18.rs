@@ -1,4 +1,9 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::utils::search;
+use super::utils::vector::Vec3;
+use super::{Answer, Params, ParseError, Solution};
+use std::collections::HashSet;
 
 /// --- Day 18: Boiling Boulders ---
 /// You and the elephants finally reach fresh air. You've emerged near the base of a large volcano
@@ -54,151 +59,142 @@ use super::load_file;
 /// 2,2,5), so the exterior surface area of the lava droplet is 58.
 ///
 /// What is the exterior surface area of your scanned lava droplet?
-pub fn day_18() {
-    let data = load_file(18);
-
-    #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-    struct Pos3D {
-        pub x: i32,
-        pub y: i32,
-        pub z: i32,
-    }
+pub struct Day18;
+
+/// `x,y,z`
+fn parse_cube(line: &str) -> Result<Vec3<i32>, ParseError> {
+    let (x, yz) = parse::split_once(line, line.trim(), ",")?;
+    let (y, z) = parse::split_once(line, yz.trim(), ",")?;
+
+    Ok(Vec3::new(
+        parse::number(line, x)?,
+        parse::number(line, y)?,
+        parse::number(line, z)?,
+    ))
+}
 
-    use std::str::FromStr;
+impl Solution for Day18 {
+    const DAY: usize = 18;
 
-    impl FromStr for Pos3D {
-        type Err = Box<dyn std::error::Error>;
+    type Input = HashSet<Vec3<i32>>;
 
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let (x, yz) = s.trim().split_once(',').unwrap();
-            let (y, z) = yz.trim().split_once(',').unwrap();
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        data.lines()
+            .map(|line| parse_cube(line).map_err(|err| err.within(data, line)))
+            .collect()
+    }
 
-            let x: i32 = x.parse().unwrap();
-            let y: i32 = y.parse().unwrap();
-            let z: i32 = z.parse().unwrap();
+    fn part_1(input: &Self::Input) -> Answer {
+        let lava_cubes = input;
 
-            Ok(Self { x, y, z })
-        }
-    }
+        let total_free_surface = lava_cubes
+            .iter()
+            .flat_map(|cube| cube.neighbours_6())
+            .filter(|neighbour| !lava_cubes.contains(neighbour))
+            .count();
 
-    use std::collections::HashSet;
-
-    let lava_cubes: HashSet<Pos3D> = data
-        .trim()
-        .split('\n')
-        .into_iter()
-        .map(|x| Pos3D::from_str(x).unwrap())
-        .collect();
-
-    let total_free_surface: u64 = lava_cubes.iter().fold(0, |mut acc, pos| {
-        for diff_x in [-1, 1] {
-            let neighbour = Pos3D {
-                x: pos.x + diff_x,
-                y: pos.y,
-                z: pos.z,
-            };
-            if !lava_cubes.contains(&neighbour) {
-                acc += 1;
-            }
-        }
+        total_free_surface.into()
+    }
 
-        for diff_y in [-1, 1] {
-            let neighbour = Pos3D {
-                x: pos.x,
-                y: pos.y + diff_y,
-                z: pos.z,
-            };
-            if !lava_cubes.contains(&neighbour) {
-                acc += 1;
-            }
-        }
+    fn part_2(input: &Self::Input) -> Answer {
+        let lava_cubes = input;
+
+        let (min_x, max_x, min_y, max_y, min_z, max_z) = lava_cubes.iter().fold(
+            (i32::MAX, i32::MIN, i32::MAX, i32::MIN, i32::MAX, i32::MIN),
+            |acc, pos| {
+                let (min_x, max_x, min_y, max_y, min_z, max_z) = acc;
+
+                (
+                    min_x.min(pos.x),
+                    max_x.max(pos.x),
+                    min_y.min(pos.y),
+                    max_y.max(pos.y),
+                    min_z.min(pos.z),
+                    max_z.max(pos.z),
+                )
+            },
+        );
+
+        // One cube of air around the droplet so that the steam can go all the way around it
+        let world_min = Vec3::new(min_x - 1, min_y - 1, min_z - 1);
+        let world_max = Vec3::new(max_x + 1, max_y + 1, max_z + 1);
+        let inside_world = |cube: &Vec3<i32>| {
+            (world_min.x..=world_max.x).contains(&cube.x)
+                && (world_min.y..=world_max.y).contains(&cube.y)
+                && (world_min.z..=world_max.z).contains(&cube.z)
+        };
+
+        let steam = search::distances([world_min], |cube| {
+            cube.neighbours_6()
+                .filter(|neighbour| inside_world(neighbour) && !lava_cubes.contains(neighbour))
+                .collect::<Vec<_>>()
+        });
+
+        let exterior_surface = steam
+            .keys()
+            .flat_map(|cube| cube.neighbours_6())
+            .filter(|neighbour| lava_cubes.contains(neighbour))
+            .count();
+
+        exterior_surface.into()
+    }
 
-        for diff_z in [-1, 1] {
-            let neighbour = Pos3D {
-                x: pos.x,
-                y: pos.y,
-                z: pos.z + diff_z,
-            };
-            if !lava_cubes.contains(&neighbour) {
-                acc += 1;
+    // `size` cubes, at most 8000 to fit in the 20 by 20 by 20 scanned area
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        // A random walk, so that the cubes form a droplet with pockets of air
+        let mut pos = Vec3::new(10, 10, 10);
+        let mut cubes = vec![pos];
+        let mut seen = HashSet::from([pos]);
+
+        while cubes.len() < size.min(8000) {
+            let neighbours: Vec<_> = pos.neighbours_6().collect();
+            let next = *rng.choose(&neighbours);
+            if (0..20).contains(&next.x) && (0..20).contains(&next.y) && (0..20).contains(&next.z) {
+                pos = next;
+                if seen.insert(pos) {
+                    cubes.push(pos);
+                }
             }
         }
 
-        acc
-    });
-
-    println!("Part 1: {total_free_surface}");
-
-    let (min_x, max_x, min_y, max_y, min_z, max_z) = lava_cubes.iter().fold(
-        (i32::MAX, i32::MIN, i32::MAX, i32::MIN, i32::MAX, i32::MIN),
-        |acc, pos| {
-            let (min_x, max_x, min_y, max_y, min_z, max_z) = acc;
-
-            (
-                min_x.min(pos.x),
-                max_x.max(pos.x),
-                min_y.min(pos.y),
-                max_y.max(pos.y),
-                min_z.min(pos.z),
-                max_z.max(pos.z),
-            )
-        },
-    );
-
-    // Safety
-    let world_min_x = min_x - 1;
-    let world_min_y = min_y - 1;
-    let world_min_z = min_z - 1;
-    let world_max_x = max_x + 1;
-    let world_max_y = max_y + 1;
-    let world_max_z = max_z + 1;
-
-    let world_l = world_max_x - world_min_x + 1;
-    let world_w = world_max_y - world_min_y + 1;
-    let world_h = world_max_z - world_min_z + 1;
-
-    let mut exterior_surface = 0;
-
-    let mut visited: HashSet<Pos3D> = HashSet::new();
-    let mut edge_cubes = std::collections::VecDeque::<Pos3D>::new();
-    edge_cubes.push_back(Pos3D { x: 0, y: 0, z: 0 });
-
-    while !edge_cubes.is_empty() {
-        let current_cube = edge_cubes.pop_front().unwrap();
-
-        if visited.contains(&current_cube) {
-            continue;
-        }
+        let scan = cubes
+            .iter()
+            .map(|cube| format!("{},{},{}\n", cube.x, cube.y, cube.z))
+            .collect();
 
-        visited.insert(current_cube);
-
-        for (diff_x, diff_y, diff_z) in [
-            (-1, 0, 0),
-            (1, 0, 0),
-            (0, -1, 0),
-            (0, 1, 0),
-            (0, 0, -1),
-            (0, 0, 1),
-        ] {
-            let (x, y, z) = (
-                current_cube.x + diff_x,
-                current_cube.y + diff_y,
-                current_cube.z + diff_z,
-            );
-
-            if x < -1 || x >= world_l || y < -1 || y >= world_w || z < -1 || z >= world_h {
-                continue;
-            }
-
-            let neighbour = Pos3D { x, y, z };
+        Some(scan)
+    }
+}
 
-            if lava_cubes.contains(&neighbour) {
-                exterior_surface += 1;
-            } else {
-                edge_cubes.push_back(neighbour);
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day18::parse(EXAMPLE).unwrap();
+        assert_eq!(Day18::part_1(&input), Answer::Number(64));
     }
 
-    println!("Part 2: {exterior_surface}");
+    #[test]
+    fn part_2_example() {
+        let input = Day18::parse(EXAMPLE).unwrap();
+        assert_eq!(Day18::part_2(&input), Answer::Number(58));
+    }
 }
@@ -1,4 +1,8 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
+use std::str::FromStr;
 
 /// --- Day 19: Not Enough Minerals ---
 /// Your scans show that the lava did indeed form obsidian!
@@ -188,8 +192,315 @@ use super::load_file;
 /// Determine the quality level of each blueprint using the largest number of geodes it could
 /// produce in 24 minutes. What do you get if you add up the quality level of all of the blueprints
 /// in your list?
-pub fn day_19() {
-    let _data = load_file(19);
+pub struct Day19;
 
-    todo!();
+/// Resources, in the order of the robots collecting them, of the costs and of the inventory
+const RESOURCES: [&str; 4] = ["ore", "clay", "obsidian", "geode"];
+const GEODE: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blueprint {
+    pub id: u32,
+    /// Resources each robot costs, the robots and the resources in the order of [`RESOURCES`]
+    pub robot_costs: [[u32; 4]; 4],
+}
+
+impl FromStr for Blueprint {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, robots) = parse::split_once(s, s.trim(), ":")?;
+        let id = parse::number(s, parse::strip_prefix(s, id, "Blueprint ")?)?;
+
+        // `Each <resource> robot costs <n> <resource> and <n> <resource>.` for each resource
+        let mut robot_costs = [[0; 4]; 4];
+        let mut sentences = robots.split_terminator('.');
+        for (robot, resource) in RESOURCES.iter().enumerate() {
+            let end = &robots[robots.len()..];
+            let sentence = sentences
+                .next()
+                .ok_or_else(|| ParseError::at(s, end, format!("a {resource} robot")))?;
+
+            let robot_str = parse::strip_prefix(s, sentence.trim(), "Each ")?;
+            let (robot_name, costs) = parse::split_once(s, robot_str, " robot costs ")?;
+            if robot_name != *resource {
+                return Err(ParseError::at(s, robot_name, *resource));
+            }
+
+            for cost in costs.split(" and ") {
+                let (amount, cost_resource) = parse::split_once(s, cost, " ")?;
+                let Some(cost_idx) = RESOURCES[..GEODE].iter().position(|r| *r == cost_resource)
+                else {
+                    return Err(ParseError::at(s, cost_resource, "ore, clay or obsidian"));
+                };
+                robot_costs[robot][cost_idx] = parse::number(s, amount)?;
+            }
+        }
+
+        if let Some(extra) = sentences.find(|sentence| !sentence.trim().is_empty()) {
+            return Err(ParseError::at(s, extra.trim(), "the end of the blueprint"));
+        }
+
+        Ok(Blueprint { id, robot_costs })
+    }
+}
+
+/// Robots and resources at some point of the search
+#[derive(Debug, Clone, Copy)]
+struct Inventory {
+    minutes_left: u32,
+    robots: [u32; 4],
+    resources: [u32; 4],
+}
+
+impl Blueprint {
+    /// Most geodes that can be opened in `minutes`, starting with a single ore robot
+    pub fn max_geodes(&self, minutes: u32) -> u32 {
+        // A single robot can be built each minute, more robots collecting a resource than the
+        // most any robot costs of it would never be used
+        let max_robots: [u32; 4] = std::array::from_fn(|resource| match resource {
+            GEODE => u32::MAX,
+            _ => self.robot_costs.iter().map(|costs| costs[resource]).max().unwrap(),
+        });
+
+        let start = Inventory {
+            minutes_left: minutes,
+            robots: [1, 0, 0, 0],
+            resources: [0; 4],
+        };
+        let mut best = 0;
+        self.search(start, &max_robots, &mut best);
+
+        debug!("Blueprint {}: {best} geodes in {minutes} minutes", self.id);
+        best
+    }
+
+    /// Depth first over the next robot to build, waiting as long as needed to afford it
+    fn search(&self, inventory: Inventory, max_robots: &[u32; 4], best: &mut u32) {
+        let minutes = inventory.minutes_left;
+        let geodes = inventory.resources[GEODE] + inventory.robots[GEODE] * minutes;
+        *best = geodes.max(*best);
+
+        // Even with a new geode robot every minute from now on, the best cannot be beaten
+        if geodes + minutes * minutes.saturating_sub(1) / 2 <= *best {
+            return;
+        }
+
+        // Geode robots first, so that the best found early prunes most
+        for robot in (0..4).rev() {
+            if inventory.robots[robot] >= max_robots[robot] {
+                continue;
+            }
+
+            // Minutes collecting before affording the robot, `None` without a robot collecting
+            // a resource it needs
+            let wait = (0..GEODE)
+                .map(|resource| {
+                    let missing = self.robot_costs[robot][resource]
+                        .saturating_sub(inventory.resources[resource]);
+                    match (missing, inventory.robots[resource]) {
+                        (0, _) => Some(0),
+                        (_, 0) => None,
+                        (missing, robots) => Some(missing.div_ceil(robots)),
+                    }
+                })
+                .try_fold(0, |wait, resource_wait| Some(wait.max(resource_wait?)));
+
+            // Built at the end of the minute, it has to collect for at least one minute
+            let Some(wait) = wait.filter(|&wait| wait + 1 < minutes) else {
+                continue;
+            };
+
+            let mut next = inventory;
+            next.minutes_left -= wait + 1;
+            for resource in 0..4 {
+                next.resources[resource] += inventory.robots[resource] * (wait + 1);
+                next.resources[resource] -= self.robot_costs[robot][resource];
+            }
+            next.robots[robot] += 1;
+            trace!("{} minutes left: {next:?}", next.minutes_left);
+
+            self.search(next, max_robots, best);
+        }
+    }
+}
+
+const MINUTES_PART_1: Param = Param {
+    name: "minutes_1",
+    default: 24,
+    min: 0,
+    max: 40,
+    doc: "minutes to open geodes in part 1",
+};
+
+const MINUTES_PART_2: Param = Param {
+    name: "minutes_2",
+    default: 32,
+    min: 0,
+    max: 40,
+    doc: "minutes to open geodes in part 2, with the first three blueprints",
+};
+
+#[derive(Debug)]
+pub struct Blueprints {
+    pub blueprints: Vec<Blueprint>,
+    pub minutes: [u32; 2],
+}
+
+impl Solution for Day19 {
+    const DAY: usize = 19;
+
+    const PARAMS: &'static [Param] = &[MINUTES_PART_1, MINUTES_PART_2];
+
+    type Input = Blueprints;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
+    }
+
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        Ok(Blueprints {
+            blueprints: parse::lines(data)?,
+            minutes: [
+                options.params.get(&MINUTES_PART_1),
+                options.params.get(&MINUTES_PART_2),
+            ],
+        })
+    }
+
+    // Sum of the quality levels, the id times the geodes of each blueprint
+    fn part_1(input: &Self::Input) -> Answer {
+        let quality_levels: u32 = input
+            .blueprints
+            .iter()
+            .map(|blueprint| blueprint.id * blueprint.max_geodes(input.minutes[0]))
+            .sum();
+
+        quality_levels.into()
+    }
+
+    // Product of the geodes of the first three blueprints
+    fn part_2(input: &Self::Input) -> Answer {
+        let geodes: u32 = input
+            .blueprints
+            .iter()
+            .take(3)
+            .map(|blueprint| blueprint.max_geodes(input.minutes[1]))
+            .product();
+
+        geodes.into()
+    }
+
+    // `size` blueprints
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let blueprints = (1..=size.max(1))
+            .map(|id| {
+                format!(
+                    "Blueprint {id}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+                     Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore \
+                     and {} obsidian.\n",
+                    rng.range(2..=4),
+                    rng.range(2..=4),
+                    rng.range(2..=4),
+                    rng.range(5..=20),
+                    rng.range(2..=4),
+                    rng.range(5..=20),
+                )
+            })
+            .collect();
+
+        Some(blueprints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const EXAMPLE: &str = "\
+Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. \
+        Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. \
+        Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day19::parse(EXAMPLE).unwrap();
+        assert_eq!(Day19::part_1(&input), Answer::Number(33));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day19::parse(EXAMPLE).unwrap();
+        assert_eq!(Day19::part_2(&input), Answer::Number(3472));
+    }
+
+    /// Most geodes, trying every choice each minute
+    fn reference_max_geodes(blueprint: &Blueprint, minutes: u32) -> u32 {
+        // Robots and resources
+        let mut inventories = HashSet::from([([1, 0, 0, 0], [0; 4])]);
+
+        for _ in 0..minutes {
+            let mut next_inventories = HashSet::new();
+            for (robots, resources) in inventories {
+                let collect = |resources: [u32; 4]| -> [u32; 4] {
+                    std::array::from_fn(|resource| resources[resource] + robots[resource])
+                };
+                next_inventories.insert((robots, collect(resources)));
+
+                for (robot, costs) in blueprint.robot_costs.iter().enumerate() {
+                    if (0..4).all(|resource| resources[resource] >= costs[resource]) {
+                        let left: [u32; 4] =
+                            std::array::from_fn(|resource| resources[resource] - costs[resource]);
+                        let mut robots = robots;
+                        robots[robot] += 1;
+                        next_inventories.insert((robots, collect(left)));
+                    }
+                }
+            }
+            inventories = next_inventories;
+        }
+
+        inventories
+            .iter()
+            .map(|(_, resources)| resources[GEODE])
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn matches_reference() {
+        // Cheap robots, so that geodes get opened in the few minutes the reference can afford
+        let mut rng = Rng::new(19);
+        let mut cost = |max| rng.range(1..=max);
+
+        for id in 1..=8 {
+            let blueprint = Blueprint {
+                id,
+                robot_costs: [
+                    [cost(3), 0, 0, 0],
+                    [cost(3), 0, 0, 0],
+                    [cost(3), cost(4), 0, 0],
+                    [cost(3), 0, cost(4), 0],
+                ],
+            };
+            let expected = reference_max_geodes(&blueprint, 12);
+            assert_eq!(blueprint.max_geodes(12), expected, "{blueprint:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_blueprint() {
+        let err = Day19::parse(
+            "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 diamonds.\n",
+        )
+        .unwrap_err();
+        assert_eq!((err.line, err.column), (1, 66));
+        assert_eq!(err.expected, "ore, clay or obsidian");
+
+        let err = Day19::parse("Blueprint 1: Each ore robot costs 4 ore.\n").unwrap_err();
+        assert_eq!(err.expected, "a clay robot");
+    }
 }
@@ -1,4 +1,8 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::parse;
+use super::utils::order_list::OrderList;
+use super::utils::rng::Rng;
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
 
 /// --- Day 20: Grove Positioning System ---
 /// It's finally time to meet back up with the Elves. When you try to contact them, however, you get
@@ -121,112 +125,194 @@ use super::load_file;
 ///
 /// Apply the decryption key and mix your encrypted file ten times. What is the sum of the three
 /// numbers that form the grove coordinates?
-pub fn day_20() {
-    let data = load_file(20);
+pub struct Day20;
 
-    let data_as_lines = data.trim().split('\n');
-
-    let input_order_numbers: Vec<i64> = data_as_lines
-        .clone()
+/// Numbers in their current order, out of the indices of the numbers in the input order
+fn arrangement(order: &OrderList, input_order_numbers: &[i64]) -> Vec<i64> {
+    order
+        .items()
         .into_iter()
-        .map(|s| s.parse().unwrap())
+        .map(|idx| input_order_numbers[idx])
+        .collect()
+}
+
+pub fn solve(input_order_numbers: &[i64], decryption_key: i64, rounds: usize) -> i64 {
+    let input_order_numbers: Vec<_> = input_order_numbers
+        .iter()
+        .map(|&x| x * decryption_key)
         .collect();
 
-    use std::collections::VecDeque;
+    // Indices of the numbers in the input order, in their current order
+    let mut decrypted_order = OrderList::new(input_order_numbers.len());
 
-    fn solve(input_order_numbers: &[i64], decryption_key: i64, rounds: usize) -> i64 {
-        let input_order_numbers: Vec<_> = input_order_numbers
-            .iter()
-            .map(|&x| x * decryption_key)
-            .collect();
+    let buffer_len = input_order_numbers.len() as i64;
 
-        let mut decrypted_numbers_indices: VecDeque<usize> =
-            (0..input_order_numbers.len()).collect();
+    debug!(
+        "Initial arrangement: {:?}",
+        arrangement(&decrypted_order, &input_order_numbers)
+    );
 
-        let buffer_len = input_order_numbers.len() as i64;
+    for round in 0..rounds {
+        for (input_idx, &number) in input_order_numbers.iter().enumerate() {
+            let current_decryption_position = decrypted_order.position(input_idx) as i64;
 
-        // let display: Vec<i64> = decrypted_numbers_indices
-        //     .iter()
-        //     .map(|&idx| input_order_numbers[idx])
-        //     .collect();
+            let new_decryption_position = current_decryption_position + number;
 
-        // println!("{display:?}");
+            let new_decryption_position_modulus =
+                new_decryption_position.rem_euclid(buffer_len - 1);
 
-        for _ in 0..rounds {
-            for (input_idx, &number) in input_order_numbers.iter().enumerate() {
-                let current_decryption_position = {
-                    let mut current_decryption_position = 0;
-                    for (current_pos_idx, &number_index) in
-                        decrypted_numbers_indices.iter().enumerate()
-                    {
-                        if number_index == input_idx {
-                            current_decryption_position = current_pos_idx;
-                            break;
-                        }
-                    }
-                    current_decryption_position as i64
-                };
-
-                let new_decryption_position = current_decryption_position + number;
-
-                let new_decryption_position_modulus =
-                    new_decryption_position.rem_euclid(buffer_len - 1);
-
-                let poped_val = decrypted_numbers_indices
-                    .remove(current_decryption_position as usize)
-                    .unwrap();
-                decrypted_numbers_indices
-                    .insert(new_decryption_position_modulus as usize, poped_val);
-
-                // let display: Vec<i64> = decrypted_numbers_indices
-                //     .iter()
-                //     .map(|&idx| input_order_numbers[idx])
-                //     .collect();
-
-                // println!("{display:?}");
-            }
+            decrypted_order.remove(input_idx);
+            decrypted_order.insert_at(new_decryption_position_modulus as usize, input_idx);
+
+            trace!(
+                "{number} moves: {:?}",
+                arrangement(&decrypted_order, &input_order_numbers)
+            );
         }
 
-        let zero_idx = {
-            let mut zero_idx = 0;
-            for (idx, &number) in input_order_numbers.iter().enumerate() {
-                if number == 0 {
-                    zero_idx = idx;
-                    break;
-                }
-            }
-            zero_idx
-        };
-
-        let zero_position = {
-            let mut zero_position = 0;
-            for (position, &number_idx) in decrypted_numbers_indices.iter().enumerate() {
-                if number_idx == zero_idx {
-                    zero_position = position;
-                    break;
-                }
-            }
-            zero_position
-        };
+        debug!(
+            "After {} round(s) of mixing: {:?}",
+            round + 1,
+            arrangement(&decrypted_order, &input_order_numbers)
+        );
+    }
 
-        let buffer_len_usize = input_order_numbers.len();
+    let zero_idx = input_order_numbers
+        .iter()
+        .position(|&number| number == 0)
+        .unwrap_or_default();
+    let zero_position = decrypted_order.position(zero_idx);
 
-        let thousand_n_idx = (1000 + zero_position).rem_euclid(buffer_len_usize);
-        let two_thousand_n_idx = (2000 + zero_position).rem_euclid(buffer_len_usize);
-        let three_thousand_n_idx = (3000 + zero_position).rem_euclid(buffer_len_usize);
+    let buffer_len_usize = input_order_numbers.len();
 
-        let thousand_n = input_order_numbers[decrypted_numbers_indices[thousand_n_idx]];
-        let two_thousand_n = input_order_numbers[decrypted_numbers_indices[two_thousand_n_idx]];
-        let three_thousand_n = input_order_numbers[decrypted_numbers_indices[three_thousand_n_idx]];
+    [1000, 2000, 3000]
+        .map(|offset| {
+            let position = (offset + zero_position).rem_euclid(buffer_len_usize);
+            input_order_numbers[decrypted_order.get(position)]
+        })
+        .iter()
+        .sum()
+}
 
-        thousand_n + two_thousand_n + three_thousand_n
+const DECRYPTION_KEY: Param = Param {
+    name: "key",
+    default: 811589153,
+    min: -1_000_000_000_000,
+    max: 1_000_000_000_000,
+    doc: "decryption key applied in part 2",
+};
+
+pub struct EncryptedFile {
+    pub numbers: Vec<i64>,
+    pub decryption_key: i64,
+}
+
+impl Solution for Day20 {
+    const DAY: usize = 20;
+
+    const PARAMS: &'static [Param] = &[DECRYPTION_KEY];
+
+    type Input = EncryptedFile;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
     }
 
-    let part_1 = solve(&input_order_numbers, 1, 1);
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let data_as_lines = data.trim().split('\n');
 
-    println!("Part 1: {part_1}");
+        let numbers = data_as_lines
+            .into_iter()
+            .map(|s| parse::number(data, s.trim()))
+            .collect::<Result<_, _>>()?;
 
-    let part_2 = solve(&input_order_numbers, 811589153, 10);
+        Ok(EncryptedFile {
+            numbers,
+            decryption_key: options.params.get(&DECRYPTION_KEY),
+        })
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        solve(&input.numbers, 1, 1).into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        solve(&input.numbers, input.decryption_key, 10).into()
+    }
 
-    println!("Part 2: {part_2}");
+    // `size` numbers, at least two for the mixing to move anything
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let mut numbers: Vec<i64> = (1..size.max(2))
+            .map(|_| match rng.range(-10000..=10000) {
+                0 => 1,
+                number => number,
+            })
+            .collect();
+        numbers.insert(rng.range(0..=numbers.len()), 0);
+
+        Some(numbers.iter().map(|number| format!("{number}\n")).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1
+2
+-3
+3
+-2
+0
+4
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day20::parse(EXAMPLE).unwrap();
+        assert_eq!(Day20::part_1(&input), Answer::Number(3));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day20::parse(EXAMPLE).unwrap();
+        assert_eq!(Day20::part_2(&input), Answer::Number(1623178306));
+    }
+
+    /// Mixing with a scan for the current position of each number, and moves in a plain list
+    fn reference_mix(numbers: &[i64], decryption_key: i64, rounds: usize) -> i64 {
+        let numbers: Vec<i64> = numbers.iter().map(|number| number * decryption_key).collect();
+        let mut order: std::collections::VecDeque<usize> = (0..numbers.len()).collect();
+
+        for _ in 0..rounds {
+            for (idx, &number) in numbers.iter().enumerate() {
+                let position = order.iter().position(|&other| other == idx).unwrap();
+                order.remove(position);
+                let new_position = (position as i64 + number).rem_euclid(numbers.len() as i64 - 1);
+                order.insert(new_position as usize, idx);
+            }
+        }
+
+        let zero_idx = numbers.iter().position(|&number| number == 0).unwrap();
+        let zero_position = order.iter().position(|&idx| idx == zero_idx).unwrap();
+        [1000, 2000, 3000]
+            .iter()
+            .map(|offset| numbers[order[(zero_position + offset) % numbers.len()]])
+            .sum()
+    }
+
+    #[test]
+    fn matches_reference() {
+        for seed in 0..20 {
+            let size = [2, 3, 10, 100, 500][seed as usize % 5];
+            let data = Day20::generate(&mut Rng::new(seed), size, &Params::default()).unwrap();
+            let input = Day20::parse(&data).unwrap();
+
+            let expected = reference_mix(&input.numbers, 1, 1);
+            assert_eq!(Day20::part_1(&input), Answer::from(expected), "seed {seed}");
+            let expected = reference_mix(&input.numbers, input.decryption_key, 10);
+            assert_eq!(Day20::part_2(&input), Answer::from(expected), "seed {seed}");
+        }
+    }
 }
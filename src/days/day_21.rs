@@ -1,4 +1,9 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, Solution};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
 /// --- Day 21: Monkey Math ---
 /// The monkeys are back! You're worried they're going to try to steal your stuff again, but it
@@ -71,323 +76,585 @@ use super::load_file;
 /// causes root to get the same number, 150, from both of its monkeys.)
 ///
 /// What number do you yell to pass root's equality test?
-pub fn day_21() {
-    let data = load_file(21);
-
-    #[derive(Debug, PartialEq, Clone)]
-    enum Op {
-        Add(String, String),
-        Sub(String, String),
-        Mul(String, String),
-        Div(String, String),
-        Val(f64),
+pub struct Day21;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Op {
+    Add(String, String),
+    Sub(String, String),
+    Mul(String, String),
+    Div(String, String),
+    Val(i64),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Monkey {
+    pub name: String,
+    pub op: Op,
+}
+
+impl Monkey {
+    pub fn name(&self) -> String {
+        self.name.clone()
     }
 
-    #[derive(Debug, PartialEq, Clone)]
-    struct Monkey {
-        pub name: String,
-        pub op: Op,
+    pub fn op(&self) -> &Op {
+        &self.op
     }
+}
+
+impl FromStr for Monkey {
+    type Err = ParseError;
 
-    impl Monkey {
-        pub fn name(&self) -> String {
-            self.name.clone()
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (monkey_name, num_or_op) = parse::split_once(s, s.trim(), ":")?;
+        let num: Result<i64, _> = num_or_op.trim().parse();
+
+        if let Ok(num) = num {
+            return Ok(Monkey {
+                name: monkey_name.to_string(),
+                op: Op::Val(num),
+            });
         }
 
-        pub fn op(&self) -> &Op {
-            &self.op
+        let num_or_op = num_or_op.trim();
+        let mut op_iter = num_or_op.split(' ');
+        let end = &num_or_op[num_or_op.len()..];
+        let mut next_token = |expected| {
+            op_iter
+                .next()
+                .ok_or_else(|| ParseError::at(s, end, expected))
+        };
+        let lhs = next_token("a number or an operation")?.to_string();
+        let op = next_token("an operator")?;
+        let rhs = next_token("a monkey")?.to_string();
+
+        let op_fn = match op {
+            "+" => Op::Add(lhs, rhs),
+            "-" => Op::Sub(lhs, rhs),
+            "*" => Op::Mul(lhs, rhs),
+            "/" => Op::Div(lhs, rhs),
+            _ => return Err(ParseError::at(s, op, "+, -, * or /")),
+        };
+
+        Ok(Monkey {
+            name: monkey_name.to_string(),
+            op: op_fn,
+        })
+    }
+}
+
+impl Op {
+    /// Monkeys whose numbers the operation needs, `None` for a number
+    pub fn operands(&self) -> Option<(&str, &str)> {
+        match self {
+            Op::Add(lhs, rhs) | Op::Sub(lhs, rhs) | Op::Mul(lhs, rhs) | Op::Div(lhs, rhs) => {
+                Some((lhs, rhs))
+            }
+            Op::Val(_) => None,
         }
     }
 
-    use std::str::FromStr;
+    /// Result of the operation on the numbers of its operands, `None` when it overflows or
+    /// divides by zero
+    fn apply(&self, lhs: Rational, rhs: Rational) -> Option<Rational> {
+        match self {
+            Op::Add(_, _) => lhs.checked_add(rhs),
+            Op::Sub(_, _) => lhs.checked_sub(rhs),
+            Op::Mul(_, _) => lhs.checked_mul(rhs),
+            Op::Div(_, _) => lhs.checked_div(rhs),
+            Op::Val(value) => Some(Rational::from(*value)),
+        }
+    }
+}
 
-    impl FromStr for Monkey {
-        type Err = Box<dyn std::error::Error>;
+/// Exact number, as the divisions of the monkeys do not have to fall on integers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    numerator: i128,
+    /// Positive, and without any factor in common with the numerator
+    denominator: i128,
+}
 
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let (monkey_name, num_or_op) = s.trim().split_once(':').unwrap();
-            let num: Result<f64, _> = num_or_op.trim().parse();
+impl Rational {
+    pub const ZERO: Rational = Rational {
+        numerator: 0,
+        denominator: 1,
+    };
 
-            if let Ok(num) = num {
-                return Ok(Monkey {
-                    name: monkey_name.to_string(),
-                    op: Op::Val(num),
-                });
+    /// `None` when the denominator is 0 or when reducing the fraction overflows
+    pub fn new(numerator: i128, denominator: i128) -> Option<Self> {
+        fn gcd(a: i128, b: i128) -> i128 {
+            match b {
+                0 => a.abs(),
+                _ => gcd(b, a % b),
             }
+        }
 
-            let mut op_iter = num_or_op.trim().split(' ');
-            let lhs = op_iter.next().unwrap().to_string();
-            let op = op_iter.next().unwrap();
-            let rhs = op_iter.next().unwrap().to_string();
-
-            let op_fn = match op {
-                "+" => Op::Add(lhs, rhs),
-                "-" => Op::Sub(lhs, rhs),
-                "*" => Op::Mul(lhs, rhs),
-                "/" => Op::Div(lhs, rhs),
-                _ => unreachable!(),
-            };
-
-            Ok(Monkey {
-                name: monkey_name.to_string(),
-                op: op_fn,
-            })
+        if denominator == 0 {
+            return None;
         }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        Some(Rational {
+            numerator: numerator.checked_div(divisor)?,
+            denominator: denominator.checked_div(divisor)?,
+        })
     }
 
-    let data_as_lines = data.trim().split('\n');
+    /// `None` when it is a fraction or does not fit
+    pub fn to_integer(self) -> Option<i64> {
+        match self.denominator {
+            1 => self.numerator.try_into().ok(),
+            _ => None,
+        }
+    }
 
-    use std::collections::HashMap;
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        Rational::new(
+            (self.numerator.checked_mul(other.denominator)?)
+                .checked_add(other.numerator.checked_mul(self.denominator)?)?,
+            self.denominator.checked_mul(other.denominator)?,
+        )
+    }
 
-    let monkey_map: HashMap<String, Monkey> = data_as_lines
-        .map(|s| {
-            let monkey: Monkey = s.parse().unwrap();
-            let name = monkey.name();
-            (name, monkey)
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.checked_add(Rational {
+            numerator: other.numerator.checked_neg()?,
+            denominator: other.denominator,
         })
-        .collect();
-    let mut monkey_results: HashMap<String, f64> = HashMap::new();
-
-    fn solve_monkeys_part_1(
-        monkey_map: &HashMap<String, Monkey>,
-        monkey_results: &mut HashMap<String, f64>,
-        monkey_name: &String,
-    ) -> f64 {
-        if let Some(&monkey_result) = monkey_results.get(monkey_name) {
-            monkey_result
-        } else {
-            let monkey = monkey_map.get(monkey_name).unwrap();
-            let res = if let Op::Val(val) = monkey.op() {
-                *val
-            } else {
-                let (lhs, rhs) = match monkey.op() {
-                    Op::Add(lhs, rhs)
-                    | Op::Sub(lhs, rhs)
-                    | Op::Mul(lhs, rhs)
-                    | Op::Div(lhs, rhs) => (lhs, rhs),
-                    _ => unreachable!(),
-                };
-
-                let lhs_val = solve_monkeys_part_1(monkey_map, monkey_results, lhs);
-                let rhs_val = solve_monkeys_part_1(monkey_map, monkey_results, rhs);
-
-                match monkey.op() {
-                    Op::Add(_, _) => lhs_val + rhs_val,
-                    Op::Sub(_, _) => lhs_val - rhs_val,
-                    Op::Mul(_, _) => lhs_val * rhs_val,
-                    Op::Div(_, _) => lhs_val / rhs_val,
-                    _ => unreachable!(),
-                }
-            };
-
-            monkey_results.insert(monkey.name(), res);
-            res
-        }
     }
 
-    let root_part_1 = solve_monkeys_part_1(&monkey_map, &mut monkey_results, &"root".to_string());
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        Rational::new(
+            self.numerator.checked_mul(other.numerator)?,
+            self.denominator.checked_mul(other.denominator)?,
+        )
+    }
 
-    println!("Part 1: {root_part_1}");
+    /// `None` when dividing by zero
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        Rational::new(
+            self.numerator.checked_mul(other.denominator)?,
+            self.denominator.checked_mul(other.numerator)?,
+        )
+    }
+}
 
-    let root_monkey = monkey_map.get(&"root".to_string()).unwrap();
-    let (lhs_root, rhs_root) = match root_monkey.op() {
-        Op::Add(lhs, rhs) | Op::Sub(lhs, rhs) | Op::Mul(lhs, rhs) | Op::Div(lhs, rhs) => {
-            (lhs.clone(), rhs.clone())
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational {
+            numerator: value.into(),
+            denominator: 1,
         }
-        _ => unreachable!(),
-    };
+    }
+}
 
-    // Like solve part 1 without memoization
-    fn compute_monkeys(monkey_map: &HashMap<String, Monkey>, monkey_name: &String) -> f64 {
-        let monkey = monkey_map.get(monkey_name).unwrap();
-        let res = if let Op::Val(val) = monkey.op() {
-            *val
-        } else {
-            let (lhs, rhs) = match monkey.op() {
-                Op::Add(lhs, rhs) | Op::Sub(lhs, rhs) | Op::Mul(lhs, rhs) | Op::Div(lhs, rhs) => {
-                    (lhs, rhs)
-                }
-                _ => unreachable!(),
-            };
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.denominator {
+            1 => write!(f, "{}", self.numerator),
+            _ => write!(f, "{}/{}", self.numerator, self.denominator),
+        }
+    }
+}
 
-            let lhs_val = compute_monkeys(monkey_map, lhs);
-            let rhs_val = compute_monkeys(monkey_map, rhs);
+/// Why the monkeys do not give a number, or no single number for humn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MathError {
+    /// The monkey gets a number too large to keep exact, or divides by zero
+    Undefined(String),
+    /// humn is in both operands of the monkey, its operation cannot be undone
+    HumnTwice(String),
+    NoSolution,
+    SeveralSolutions,
+    /// The only number that passes root's test, it would be a fraction
+    NotInteger(Rational),
+}
 
-            match monkey.op() {
-                Op::Add(_, _) => lhs_val + rhs_val,
-                Op::Sub(_, _) => lhs_val - rhs_val,
-                Op::Mul(_, _) => lhs_val * rhs_val,
-                Op::Div(_, _) => lhs_val / rhs_val,
-                _ => unreachable!(),
+impl std::fmt::Display for MathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MathError::Undefined(name) => {
+                write!(f, "{name} overflows or divides by zero")
             }
-        };
-
-        res
+            MathError::HumnTwice(name) => {
+                write!(f, "humn is on both sides of {name}, its job cannot be undone")
+            }
+            MathError::NoSolution => write!(f, "no number humn yells passes root's test"),
+            MathError::SeveralSolutions => {
+                write!(f, "more than one number humn yells passes root's test")
+            }
+            MathError::NotInteger(humn) => {
+                write!(f, "humn would have to yell {humn}, which is not an integer")
+            }
+        }
     }
+}
 
-    let lhs = compute_monkeys(&monkey_map, &lhs_root);
-    let rhs = compute_monkeys(&monkey_map, &rhs_root);
+impl std::error::Error for MathError {}
 
-    let mut modified_monkey_map = monkey_map.clone();
+/// Numbers of the monkeys, and whether humn is among the monkeys they wait for, each worked out
+/// once
+struct MonkeyMath<'a> {
+    monkeys: &'a HashMap<String, Monkey>,
+    numbers: HashMap<&'a str, Rational>,
+    waits_for_humn: HashMap<&'a str, bool>,
+}
 
-    let humn_name = "humn".to_string();
+impl<'a> MonkeyMath<'a> {
+    fn new(monkeys: &'a HashMap<String, Monkey>) -> Self {
+        MonkeyMath {
+            monkeys,
+            numbers: HashMap::new(),
+            waits_for_humn: HashMap::new(),
+        }
+    }
 
-    let mut lhs_constant = true;
-    let mut rhs_constant = true;
+    fn number(&mut self, name: &'a str) -> Result<Rational, MathError> {
+        if let Some(&number) = self.numbers.get(name) {
+            return Ok(number);
+        }
 
-    for idx in 0..i64::MAX {
-        modified_monkey_map.insert(
-            humn_name.clone(),
-            Monkey {
-                name: humn_name.clone(),
-                op: Op::Val(idx as f64),
-            },
-        );
-        let tmp_lhs = compute_monkeys(&modified_monkey_map, &lhs_root);
-        let tmp_rhs = compute_monkeys(&modified_monkey_map, &rhs_root);
+        let op = &self.monkeys[name].op;
+        let number = match op.operands() {
+            Some((lhs, rhs)) => {
+                let (lhs, rhs) = (self.number(lhs)?, self.number(rhs)?);
+                op.apply(lhs, rhs)
+                    .ok_or_else(|| MathError::Undefined(name.to_string()))?
+            }
+            None => op.apply(Rational::ZERO, Rational::ZERO).unwrap(),
+        };
 
-        if tmp_lhs != lhs {
-            println!("lhs: {tmp_lhs} != {lhs}");
-            lhs_constant = false;
-        }
+        self.numbers.insert(name, number);
+        Ok(number)
+    }
 
-        if tmp_rhs != rhs {
-            println!("lhs: {tmp_rhs} != {rhs}");
-            rhs_constant = false;
+    fn waits_for_humn(&mut self, name: &'a str) -> bool {
+        if let Some(&waits) = self.waits_for_humn.get(name) {
+            return waits;
         }
 
-        if !lhs_constant || !rhs_constant {
-            break;
-        }
+        let waits = name == "humn"
+            || self.monkeys[name]
+                .op
+                .operands()
+                .is_some_and(|(lhs, rhs)| self.waits_for_humn(lhs) || self.waits_for_humn(rhs));
+
+        self.waits_for_humn.insert(name, waits);
+        waits
     }
 
-    assert_ne!(lhs_constant, rhs_constant);
+    /// Number humn must yell for `name` to yell `target`, undoing the operations from `name` down
+    /// to humn one after the other
+    fn solve(&mut self, name: &'a str, target: Rational) -> Result<Rational, MathError> {
+        trace!("{name} must yell {target}");
 
-    let (eval_branch_name, target, _pruned_branch_name) = if !lhs_constant {
-        (lhs_root, rhs, rhs_root)
-    } else {
-        (rhs_root, lhs, lhs_root)
-    };
+        let monkey = &self.monkeys[name];
+        let Some((lhs, rhs)) = monkey.op.operands() else {
+            return Ok(target);
+        };
+        let undefined = || MathError::Undefined(name.to_string());
+
+        let (humn_side, known, humn_is_lhs) =
+            match (self.waits_for_humn(lhs), self.waits_for_humn(rhs)) {
+                (true, true) => return Err(MathError::HumnTwice(name.to_string())),
+                (true, false) => (lhs, self.number(rhs)?, true),
+                (false, true) => (rhs, self.number(lhs)?, false),
+                (false, false) => unreachable!("{name} does not wait for humn"),
+            };
 
-    let mut modified_monkey_map = monkey_map;
-
-    let mut min_bound = 0f64;
-    let mut max_bound = 1f64;
-
-    modified_monkey_map.insert(
-        humn_name.clone(),
-        Monkey {
-            name: humn_name.clone(),
-            op: Op::Val(min_bound),
-        },
-    );
-    let min_bound_eval = compute_monkeys(&modified_monkey_map, &eval_branch_name);
-
-    modified_monkey_map.insert(
-        humn_name.clone(),
-        Monkey {
-            name: humn_name.clone(),
-            op: Op::Val(max_bound),
-        },
-    );
-    let mut max_bound_eval = compute_monkeys(&modified_monkey_map, &eval_branch_name);
-
-    while min_bound_eval == max_bound_eval {
-        max_bound *= 2.0;
-
-        modified_monkey_map.insert(
-            humn_name.clone(),
-            Monkey {
-                name: humn_name.clone(),
-                op: Op::Val(max_bound),
-            },
-        );
-        max_bound_eval = compute_monkeys(&modified_monkey_map, &eval_branch_name);
+        let humn_side_target = match (&monkey.op, humn_is_lhs) {
+            (Op::Add(_, _), _) => target.checked_sub(known),
+            (Op::Sub(_, _), true) => target.checked_add(known),
+            (Op::Sub(_, _), false) => known.checked_sub(target),
+            (Op::Mul(_, _), _) if known == Rational::ZERO => {
+                return Err(match target == Rational::ZERO {
+                    true => MathError::SeveralSolutions,
+                    false => MathError::NoSolution,
+                });
+            }
+            (Op::Mul(_, _), _) => target.checked_div(known),
+            (Op::Div(_, _), true) if known == Rational::ZERO => return Err(undefined()),
+            (Op::Div(_, _), true) => target.checked_mul(known),
+            // Any number but 0 divides 0 into 0, and nothing else gives 0
+            (Op::Div(_, _), false) if target == Rational::ZERO => {
+                return Err(match known == Rational::ZERO {
+                    true => MathError::SeveralSolutions,
+                    false => MathError::NoSolution,
+                });
+            }
+            // 0 divided by anything is 0, never the target
+            (Op::Div(_, _), false) if known == Rational::ZERO => {
+                return Err(MathError::NoSolution);
+            }
+            (Op::Div(_, _), false) => known.checked_div(target),
+            (Op::Val(_), _) => unreachable!(),
+        };
+
+        self.solve(humn_side, humn_side_target.ok_or_else(undefined)?)
     }
 
-    let fn_increasing = min_bound_eval <= max_bound_eval;
+    /// Number humn must yell for both monkeys root waits for to yell the same number
+    fn solve_root(&mut self) -> Result<i64, MathError> {
+        let (lhs, rhs) = self.monkeys["root"].op.operands().unwrap();
 
-    println!("{fn_increasing}");
+        let humn = match (self.waits_for_humn(lhs), self.waits_for_humn(rhs)) {
+            (true, true) => return Err(MathError::HumnTwice("root".to_string())),
+            (true, false) => {
+                let target = self.number(rhs)?;
+                debug!("humn is left of root, {lhs} must yell {target}");
+                self.solve(lhs, target)?
+            }
+            (false, true) => {
+                let target = self.number(lhs)?;
+                debug!("humn is right of root, {rhs} must yell {target}");
+                self.solve(rhs, target)?
+            }
+            // Whatever humn yells
+            (false, false) => {
+                return Err(match self.number(lhs)? == self.number(rhs)? {
+                    true => MathError::SeveralSolutions,
+                    false => MathError::NoSolution,
+                });
+            }
+        };
+
+        humn.to_integer().ok_or(MathError::NotInteger(humn))
+    }
+}
 
-    if target < max_bound_eval && !fn_increasing {
-        while target < max_bound_eval {
-            println!("{max_bound}");
-            max_bound *= 2.0;
+/// Monkeys of a generated riddle, each with a distinct name
+struct RandomRiddle {
+    jobs: Vec<String>,
+    names: HashSet<String>,
+}
 
-            modified_monkey_map.insert(
-                humn_name.clone(),
-                Monkey {
-                    name: humn_name.clone(),
-                    op: Op::Val(max_bound),
-                },
-            );
-            max_bound_eval = compute_monkeys(&modified_monkey_map, &eval_branch_name);
+impl RandomRiddle {
+    fn name(&mut self, rng: &mut Rng) -> String {
+        loop {
+            let name: String = (0..4).map(|_| rng.range(b'a'..=b'z') as char).collect();
+            if name != "root" && name != "humn" && self.names.insert(name.clone()) {
+                return name;
+            }
         }
-    } else {
-        todo!("Code other cases yourself");
     }
 
-    let mut res = 0.0;
+    /// Monkey yelling `value` once its `depth` levels of monkeys below it have yelled, their
+    /// numbers all positive and their divisions exact
+    fn monkey(&mut self, rng: &mut Rng, value: i64, depth: usize) -> String {
+        let name = self.name(rng);
+        let divisor = (2..=9).rev().find(|divisor| value % divisor == 0);
+
+        let job = match (rng.range(0..4), divisor) {
+            _ if depth == 0 || value < 2 => value.to_string(),
+            (0, _) => {
+                let lhs = rng.range(1..value);
+                self.operation(rng, (lhs, '+', value - lhs), depth)
+            }
+            (1, _) => {
+                let rhs = rng.range(1..=20);
+                self.operation(rng, (value + rhs, '-', rhs), depth)
+            }
+            (2, Some(divisor)) => {
+                self.operation(rng, (value / divisor, '*', divisor), depth)
+            }
+            _ => {
+                let divisor = rng.range(2..=5);
+                self.operation(rng, (value * divisor, '/', divisor), depth)
+            }
+        };
 
-    while (min_bound - max_bound).abs() > 1.0 {
-        let mid = (min_bound + max_bound) / 2.0;
+        self.jobs.push(format!("{name}: {job}"));
+        name
+    }
 
-        modified_monkey_map.insert(
-            humn_name.clone(),
-            Monkey {
-                name: humn_name.clone(),
-                op: Op::Val(mid),
-            },
-        );
+    /// Job of a monkey computing `lhs op rhs` from two other monkeys
+    fn operation(&mut self, rng: &mut Rng, job: (i64, char, i64), depth: usize) -> String {
+        let (lhs, op, rhs) = job;
+        let lhs = self.monkey(rng, lhs, depth - 1);
+        let rhs = self.monkey(rng, rhs, depth - 1);
+        format!("{lhs} {op} {rhs}")
+    }
+}
 
-        let mid_eval = compute_monkeys(&modified_monkey_map, &eval_branch_name);
+impl Solution for Day21 {
+    const DAY: usize = 21;
 
-        println!("target: {target}, mid_eval: {mid_eval}");
-        println!("min: {min_bound}, mid: {mid}, max: {max_bound}");
+    type Input = HashMap<String, Monkey>;
 
-        if mid_eval == target {
-            res = mid;
-            break;
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        let monkeys: HashMap<String, Monkey> = parse::lines::<Monkey>(data)?
+            .into_iter()
+            .map(|monkey| (monkey.name(), monkey))
+            .collect();
+
+        if !monkeys.contains_key("root") {
+            return Err(ParseError::at_end(data, "a root monkey"));
         }
 
-        if !fn_increasing {
-            if mid_eval >= target {
-                min_bound = mid;
-            } else {
-                max_bound = mid;
+        // Every operand must be a monkey, root comparing the numbers of two of them
+        for line in data.lines() {
+            let (name, job) = parse::split_once(data, line, ":")?;
+            if name == "root" && monkeys["root"].op.operands().is_none() {
+                return Err(ParseError::at(data, job.trim(), "an operation"));
+            }
+            for operand in job.split_whitespace().step_by(2) {
+                let is_number = operand.parse::<i64>().is_ok();
+                if !is_number && !monkeys.contains_key(operand) {
+                    return Err(ParseError::at(data, operand, "a known monkey"));
+                }
             }
-        } else {
-            todo!("Code other cases yourself")
         }
+
+        Ok(monkeys)
     }
 
-    if res == 0.0 {
-        if min_bound_eval == target {
-            res = min_bound;
+    fn part_1(input: &Self::Input) -> Answer {
+        let root = MonkeyMath::new(input)
+            .number("root")
+            .unwrap_or_else(|err| panic!("{err}"));
+
+        match root.to_integer() {
+            Some(root) => root.into(),
+            None => panic!("root yells {root}, which is not an integer"),
         }
-        if max_bound_eval == target {
-            res = max_bound;
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        let humn = MonkeyMath::new(input)
+            .solve_root()
+            .unwrap_or_else(|err| panic!("{err}"));
+
+        humn.into()
+    }
+
+    // About `size` monkeys, humn yelling once on one side of root
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let mut riddle = RandomRiddle {
+            jobs: vec![],
+            names: HashSet::new(),
+        };
+        let humn = rng.range(1..=1000);
+        riddle.jobs.push(format!("humn: {humn}"));
+
+        // Each monkey on the way from humn to root does an operation with a number from monkeys
+        // that do not depend on humn, humn is never in a divisor so that part 2 stays linear
+        let (mut name, mut value) = ("humn".to_string(), humn);
+        while riddle.jobs.len() < size {
+            let other = rng.range(1..=20);
+            let (job, next_value) = match rng.range(0..4) {
+                0 if value > other => ((&name, '-', other), value - other),
+                0 => ((&name, '+', other), value + other),
+                1 if value * other < 1_000_000_000_000 => ((&name, '*', other), value * other),
+                2 if value % other == 0 => ((&name, '/', other), value / other),
+                _ => ((&name, '+', other), value + other),
+            };
+
+            let (lhs, op, rhs) = job;
+            let depth = rng.range(0..=2);
+            let other_monkey = riddle.monkey(rng, rhs, depth);
+            let job = match op {
+                '+' | '*' if rng.chance(0.5) => format!("{other_monkey} {op} {lhs}"),
+                _ => format!("{lhs} {op} {other_monkey}"),
+            };
+
+            let monkey = riddle.name(rng);
+            riddle.jobs.push(format!("{monkey}: {job}"));
+            (name, value) = (monkey, next_value);
         }
+
+        // Root compares humn's side to the other one when humn yells the number of part 2
+        let depth = rng.range(0..=3);
+        let other_side = riddle.monkey(rng, value, depth);
+        let op = rng.choose(&['+', '-', '*', '/']);
+        riddle.jobs.push(format!("root: {name} {op} {other_side}"));
+
+        rng.shuffle(&mut riddle.jobs);
+        Some(riddle.jobs.join("\n") + "\n")
     }
+}
 
-    loop {
-        let new_res = res - 1.0;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day21::parse(EXAMPLE).unwrap();
+        assert_eq!(Day21::part_1(&input), Answer::Number(152));
+    }
 
-        modified_monkey_map.insert(
-            humn_name.clone(),
-            Monkey {
-                name: humn_name.clone(),
-                op: Op::Val(new_res),
-            },
-        );
+    #[test]
+    fn part_2_example() {
+        let input = Day21::parse(EXAMPLE).unwrap();
+        assert_eq!(Day21::part_2(&input), Answer::Number(301));
+    }
 
-        let curr_eval = compute_monkeys(&modified_monkey_map, &eval_branch_name);
-        if curr_eval != target {
-            break;
-        } else {
-            res = new_res;
+    #[test]
+    fn generated_humn() {
+        // Root's test passes when humn yells the number of the generated riddle
+        for seed in 0..50 {
+            let data = Day21::generate(&mut Rng::new(seed), 30, &Params::default()).unwrap();
+            let input = Day21::parse(&data).unwrap();
+            let Op::Val(humn) = input["humn"].op else {
+                panic!("humn does not yell a number");
+            };
+            assert_eq!(Day21::part_2(&input), Answer::Number(humn), "seed {seed}\n{data}");
         }
     }
 
-    println!("Part 2: {res}");
+    #[test]
+    fn humn_equations() {
+        let solve = |jobs: &str| {
+            let input = Day21::parse(&format!("root: lhs + rhs\n{jobs}")).unwrap();
+            MonkeyMath::new(&input).solve_root()
+        };
+        let half = Rational::new(3, 2).unwrap();
+
+        assert_eq!(solve("lhs: ten / humn\nten: 10\nrhs: 5\nhumn: 1\n"), Ok(2));
+        assert_eq!(solve("lhs: ten - humn\nten: 10\nrhs: 5\nhumn: 1\n"), Ok(5));
+        assert_eq!(
+            solve("lhs: humn * two\ntwo: 2\nrhs: 3\nhumn: 1\n"),
+            Err(MathError::NotInteger(half))
+        );
+        assert_eq!(
+            solve("lhs: humn * zero\nzero: 0\nrhs: 3\nhumn: 1\n"),
+            Err(MathError::NoSolution)
+        );
+        assert_eq!(
+            solve("lhs: humn * zero\nzero: 0\nrhs: 0\nhumn: 1\n"),
+            Err(MathError::SeveralSolutions)
+        );
+        assert_eq!(
+            solve("lhs: humn * humn\nrhs: 4\nhumn: 1\n"),
+            Err(MathError::HumnTwice("lhs".to_string()))
+        );
+        assert_eq!(
+            solve("lhs: humn / zero\nzero: 0\nrhs: 4\nhumn: 1\n"),
+            Err(MathError::Undefined("lhs".to_string()))
+        );
+        assert_eq!(
+            solve("lhs: zero / humn\nzero: 0\nrhs: 5\nhumn: 1\n"),
+            Err(MathError::NoSolution)
+        );
+        assert_eq!(solve("lhs: 3\nrhs: 4\n"), Err(MathError::NoSolution));
+    }
 }
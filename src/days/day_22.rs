@@ -1,4 +1,9 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::parse;
+use super::utils::grid::{Grid, Pos};
+use super::utils::rng::Rng;
+use super::utils::vector::{Direction, Vec2, Vec3};
+use super::{Answer, Params, ParseError, RunOptions, Solution};
 
 /// --- Day 22: Monkey Map ---
 /// The monkeys take you on a surprisingly easy trail through the jungle. They're even going in
@@ -72,14 +77,14 @@ use super::load_file;
 /// By drawing the last facing you had with an arrow on each tile you visit, the full path taken by
 /// the above example looks like this:
 ///
-///         >>v#    
-///         .#v.    
-///         #.v.    
-///         ..v.    
-/// ...#...v..v#    
-/// >>>v...>#.>>    
-/// ..#v...#....    
-/// ...>>>>v..#.    
+///         >>v#
+///         .#v.
+///         #.v.
+///         ..v.
+/// ...#...v..v#
+/// >>>v...>#.>>
+/// ..#v...#....
+/// ...>>>>v..#.
 ///         ...#....
 ///         .....#..
 ///         .#......
@@ -157,14 +162,14 @@ use super::load_file;
 /// Using the same method of drawing the last facing you had with an arrow on each tile you visit,
 /// the full path taken by the above example now looks like this:
 ///
-///         >>v#    
-///         .#v.    
-///         #.v.    
-///         ..v.    
-/// ...#..^...v#    
-/// .>>>>>^.#.>>    
-/// .^#....#....    
-/// .^........#.    
+///         >>v#
+///         .#v.
+///         #.v.
+///         ..v.
+/// ...#..^...v#
+/// .>>>>>^.#.>>
+/// .^#....#....
+/// .^........#.
 ///         ...#..v.
 ///         .....#v.
 ///         .#v<<<<.
@@ -175,152 +180,429 @@ use super::load_file;
 ///
 /// Fold the map into a cube, then follow the path given in the monkeys' notes. What is the final
 /// password?
-pub fn day_22() {
-    let data = load_file(22);
+pub struct Day22;
 
-    let (map_str, instructions) = data.split_once("\n\n").unwrap();
+#[derive(Debug)]
+pub struct MonkeyMap {
+    pub map: Grid<char>,
+    pub instructions: String,
+    /// `None` when only solving part 1, or when the map does not fold into a cube
+    pub cube: Option<Cube>,
+}
+
+impl MonkeyMap {
+    /// Password of the tile where the path ends, `step` giving the tile in front of the one being
+    /// walked and which way it faces once there
+    fn follow(&self, step: impl Fn(Pos, Direction) -> (Pos, Direction)) -> usize {
+        let column = self.map.rows().next().unwrap().iter().position(|&c| c == '.').unwrap();
+        let mut pos = Vec2::new(column, 0);
+        let mut direction = Direction::Right;
+
+        debug!("Start: row {}, column {}", pos.y + 1, pos.x + 1);
+
+        // Every number of tiles but the last one is followed by a rotation
+        for instruction in self.instructions.split_inclusive(['L', 'R']) {
+            let (moves, rotation) = match instruction.strip_suffix(['L', 'R']) {
+                Some(moves) => (moves, instruction.chars().last()),
+                None => (instruction, None),
+            };
+            let moves: usize = moves.parse().unwrap();
 
-    use std::collections::HashMap;
+            trace!("Direction: {direction:?}, moves: {moves}");
 
-    let mut hashed_map: HashMap<(usize, usize), char> = Default::default();
+            for _ in 0..moves {
+                let (next, next_direction) = step(pos, direction);
+                if self.map[next] == '#' {
+                    break;
+                }
+                (pos, direction) = (next, next_direction);
+            }
 
-    let mut line_count = 0;
-    let mut column_count = 0;
+            trace!("Position: row {}, column {}", pos.y + 1, pos.x + 1);
 
-    for (line_idx, map_line_str) in map_str.split('\n').enumerate() {
-        line_count += 1;
-        for (col_idx, c) in map_line_str.char_indices() {
-            column_count = column_count.max(col_idx + 1);
-            hashed_map.insert((line_idx, col_idx), c);
+            direction = match rotation {
+                Some('L') => direction.turn_left(),
+                Some('R') => direction.turn_right(),
+                _ => direction,
+            };
         }
+
+        1000 * (pos.y + 1) + 4 * (pos.x + 1) + direction as usize
     }
+}
+
+/// Face of the cube, with the directions in space its tiles go along once folded. Going out of
+/// the cube is `normal`, with `right` and `down` going that way on the map.
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    /// Top left tile on the map
+    corner: Pos,
+    right: Vec3<i32>,
+    down: Vec3<i32>,
+    normal: Vec3<i32>,
+}
+
+/// The map folded into a cube
+#[derive(Debug)]
+pub struct Cube {
+    /// Tiles along an edge of a face
+    pub side: usize,
+    faces: Vec<Face>,
+}
+
+impl Cube {
+    /// Cube folded out of the tiles of `map`, whichever of the 11 nets of a cube they form. `None`
+    /// when they do not form one.
+    pub fn fold(map: &Grid<char>) -> Option<Self> {
+        let tiles = map.iter().filter(|&(_, &tile)| tile != ' ').count();
+        let side = (1..).take_while(|side| 6 * side * side <= tiles).last()?;
+        let aligned = map.width().is_multiple_of(side) && map.height().is_multiple_of(side);
+        if 6 * side * side != tiles || !aligned {
+            return None;
+        }
+
+        // Every tile is on a face, a square of `side` tiles aligned with the others
+        let corner = |pos: Pos| Vec2::new(pos.x / side * side, pos.y / side * side);
+        if !map
+            .iter()
+            .all(|(pos, &tile)| (tile == ' ') == (map[corner(pos)] == ' '))
+        {
+            return None;
+        }
+
+        // Faces found from the first one by folding the net along the edges they share on the
+        // map: the face right of a face is the side of the cube its `right` points to, and so on
+        let first = map.iter().find(|&(_, &tile)| tile != ' ')?.0;
+        let mut faces = vec![Face {
+            corner: first,
+            right: Vec3::new(1, 0, 0),
+            down: Vec3::new(0, 1, 0),
+            normal: Vec3::new(0, 0, 1),
+        }];
+        let mut to_visit = vec![faces[0]];
 
-    let line_count = line_count;
-    let column_count = column_count;
-    let mut map = vec![vec![' '; column_count]; line_count];
+        while let Some(face) = to_visit.pop() {
+            for direction in Direction::ALL {
+                let offset = direction.offset::<isize>() * side as isize;
+                let Some(corner) = map.step(face.corner, offset) else {
+                    continue;
+                };
+                if map[corner] == ' ' || faces.iter().any(|known| known.corner == corner) {
+                    continue;
+                }
+
+                let (right, down, normal) = (face.right, face.down, face.normal);
+                let (right, down, normal) = match direction {
+                    Direction::Right => (-normal, down, right),
+                    Direction::Down => (right, -normal, down),
+                    Direction::Left => (normal, down, -right),
+                    Direction::Up => (right, normal, -down),
+                };
+                let next = Face {
+                    corner,
+                    right,
+                    down,
+                    normal,
+                };
+                faces.push(next);
+                to_visit.push(next);
+            }
+        }
+
+        // Overlapping faces when the net is not one of a cube
+        let mut normals: Vec<Vec3<i32>> = faces.iter().map(|face| face.normal).collect();
+        normals.sort();
+        normals.dedup();
+        if normals.len() != 6 || faces.len() != 6 {
+            return None;
+        }
 
-    for ((line_idx, col_idx), terrain) in hashed_map {
-        map[line_idx][col_idx] = terrain;
+        Some(Cube { side, faces })
     }
 
-    let mut line = 0;
-    let mut column = 0;
+    /// Tile in front of `pos` facing `direction` and which way it faces there, going over an edge
+    /// of the cube onto the next face when it is the last tile of its face
+    pub fn step(&self, pos: Pos, direction: Direction) -> (Pos, Direction) {
+        let side = self.side as i32;
+        let corner = Vec2::new(pos.x / self.side * self.side, pos.y / self.side * self.side);
+        let face = self.faces.iter().find(|face| face.corner == corner).unwrap();
+        let tile: Vec2<i32> = (pos - face.corner).try_cast().unwrap();
+        let offset: Vec2<i32> = direction.offset();
 
-    for col_idx in 0..column_count {
-        if map[line][col_idx] == '.' {
-            column = col_idx;
-            break;
+        let next = tile + offset;
+        if (0..side).contains(&next.x) && (0..side).contains(&next.y) {
+            return (face.corner + next.try_cast().unwrap(), direction);
         }
+
+        // In space, in half tiles from the centre of the cube so that the tile centres are whole:
+        // the next tile is half a tile further along the way out of the face, and half a tile
+        // lower, on the face the cube rolls onto
+        let heading = face.right * offset.x + face.down * offset.y;
+        let centre = face.normal * side
+            + face.right * (2 * tile.x + 1 - side)
+            + face.down * (2 * tile.y + 1 - side);
+        let landing = centre + heading - face.normal;
+
+        let next_face = self.faces.iter().find(|face| face.normal == heading).unwrap();
+        let next_tile = Vec2::new(
+            (landing.dot(next_face.right) + side - 1) / 2,
+            (landing.dot(next_face.down) + side - 1) / 2,
+        );
+        let next_direction = Direction::ALL
+            .into_iter()
+            .find(|direction| {
+                let offset: Vec2<i32> = direction.offset();
+                next_face.right * offset.x + next_face.down * offset.y == -face.normal
+            })
+            .unwrap();
+
+        (
+            next_face.corner + next_tile.try_cast().unwrap(),
+            next_direction,
+        )
     }
+}
 
-    // println!("pos: {line}, {column}");
+/// Faces of one of the 11 nets of a cube, in units of faces
+fn random_net(rng: &mut Rng) -> Vec<Vec2<i32>> {
+    let net = match rng.range(0..4) {
+        // A row of four faces, with one face above and one below it
+        0 => vec![(0, 1), (1, 1), (2, 1), (3, 1), (rng.range(0..4), 0), (rng.range(0..4), 2)],
+        // Two, three then one face
+        1 => vec![(0, 0), (1, 0), (1, 1), (2, 1), (3, 1), (rng.range(1..=3), 2)],
+        // A staircase of pairs
+        2 => vec![(0, 0), (1, 0), (1, 1), (2, 1), (2, 2), (3, 2)],
+        // Two rows of three
+        _ => vec![(0, 0), (1, 0), (2, 0), (2, 1), (3, 1), (4, 1)],
+    };
 
-    let instructions = instructions.trim();
+    // Rotated or mirrored
+    let (swap, flip_x, flip_y) = (rng.chance(0.5), rng.chance(0.5), rng.chance(0.5));
+    let net: Vec<Vec2<i32>> = net
+        .into_iter()
+        .map(|(x, y)| {
+            let (x, y) = if swap { (y, x) } else { (x, y) };
+            Vec2::new(if flip_x { -x } else { x }, if flip_y { -y } else { y })
+        })
+        .collect();
 
-    let mut char_iter = instructions.char_indices().peekable();
-    let mut start_number_idx = 0;
-    let mut end_number_idx;
+    let min_x = net.iter().map(|face| face.x).min().unwrap();
+    let min_y = net.iter().map(|face| face.y).min().unwrap();
+    net.iter().map(|&face| face - Vec2::new(min_x, min_y)).collect()
+}
 
-    // 0 right, 1 down, 2 left, 3 up
-    let mut current_direction: i32 = 0;
+impl Solution for Day22 {
+    const DAY: usize = 22;
 
-    let part1_res;
+    type Input = MonkeyMap;
 
-    loop {
-        loop {
-            if let Some((idx, c)) = char_iter.peek() {
-                match c {
-                    '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
-                        char_iter.next();
-                    }
-                    _ => {
-                        end_number_idx = *idx;
-                        break;
-                    }
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
+    }
+
+    // Part 1 walks any map, only part 2 needs it to fold into a cube
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let (map_str, instructions) = parse::split_once(data, data, "\n\n")?;
+
+        let map = Grid::parse_padded(map_str, "a tile: ' ', . or #", ' ', |_, c| {
+            matches!(c, ' ' | '.' | '#').then_some(c)
+        })
+        .map_err(|err| err.within(data, map_str))?;
+
+        if !map.rows().next().is_some_and(|row| row.contains(&'.')) {
+            return Err(ParseError::at(
+                data,
+                map_str,
+                "an open tile on the first line",
+            ));
+        }
+
+        // Numbers of tiles to move separated by single rotations
+        let instructions = instructions.trim();
+        let mut expect_number = true;
+        for (idx, c) in instructions.char_indices() {
+            match c {
+                '0'..='9' => expect_number = false,
+                'L' | 'R' if !expect_number => expect_number = true,
+                _ => {
+                    let found = &instructions[idx..idx + c.len_utf8()];
+                    let expected = if expect_number {
+                        "a number"
+                    } else {
+                        "a number, L or R"
+                    };
+                    return Err(ParseError::at(data, found, expected));
                 }
-            } else {
-                end_number_idx = instructions.len();
-                break;
             }
         }
+        if expect_number {
+            let end = &instructions[instructions.len()..];
+            return Err(ParseError::at(data, end, "a number"));
+        }
 
-        let moves: usize = instructions[start_number_idx..end_number_idx]
-            .parse()
-            .unwrap();
+        let cube = options.parts.includes(2).then(|| Cube::fold(&map)).flatten();
+        match &cube {
+            Some(cube) => debug!("Cube with faces of {} tiles a side", cube.side),
+            None => debug!("No cube folded"),
+        }
+
+        Ok(MonkeyMap {
+            map,
+            instructions: instructions.to_string(),
+            cube,
+        })
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        let map = &input.map;
+        let size = Vec2::new(map.width(), map.height()).try_cast::<isize>().unwrap();
 
-        // move
-
-        // println!(
-        //     "direction: {}, moves: {moves}",
-        //     match current_direction {
-        //         0 => "right",
-        //         1 => "down",
-        //         2 => "left",
-        //         3 => "up",
-        //         _ => unreachable!(),
-        //     }
-        // );
-
-        let (diff_line, diff_col) = match current_direction {
-            // Right
-            0 => (0, 1),
-            // Down
-            1 => (1, 0),
-            // Left
-            2 => (0, -1),
-            // Up
-            3 => (-1, 0),
-            _ => unreachable!(),
+        // One step forward, going around the map edges
+        let step = |pos: Pos, direction: Direction| -> Pos {
+            let next = pos.try_cast::<isize>().unwrap() + direction.offset();
+            Vec2::new(next.x.rem_euclid(size.x), next.y.rem_euclid(size.y))
+                .try_cast()
+                .unwrap()
         };
 
-        for _ in 0..moves {
-            let (next_line, next_col) = (
-                (line as i32 + diff_line).rem_euclid(line_count as i32) as usize,
-                (column as i32 + diff_col).rem_euclid(column_count as i32) as usize,
-            );
-
-            (line, column) = match map[next_line][next_col] {
-                '.' => (next_line, next_col),
-                ' ' => {
-                    let (mut jump_line, mut jump_col) = (next_line, next_col);
-                    while map[jump_line][jump_col] == ' ' {
-                        jump_line =
-                            (jump_line as i32 + diff_line).rem_euclid(line_count as i32) as usize;
-                        jump_col =
-                            (jump_col as i32 + diff_col).rem_euclid(column_count as i32) as usize;
-                    }
-                    if map[jump_line][jump_col] == '#' {
-                        break;
-                    }
-                    (jump_line, jump_col)
+        let password = input.follow(|pos, direction| {
+            // Jump over the void to the other side of the board
+            let mut next = step(pos, direction);
+            while map[next] == ' ' {
+                next = step(next, direction);
+            }
+            (next, direction)
+        });
+
+        password.into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        let cube = input
+            .cube
+            .as_ref()
+            .expect("The map does not fold into a cube");
+        let password = input.follow(|pos, direction| cube.step(pos, direction));
+
+        password.into()
+    }
+
+    // A cube with `size` tiles a side, and `size` moves
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let side = size.max(1);
+        let net = random_net(rng);
+        let width = (net.iter().map(|face| face.x).max().unwrap() + 1) as usize * side;
+        let height = (net.iter().map(|face| face.y).max().unwrap() + 1) as usize * side;
+
+        let mut map = Grid::new(width, height, ' ');
+        for face in &net {
+            let corner = face.try_cast::<usize>().unwrap() * side;
+            for y in 0..side {
+                for x in 0..side {
+                    map[corner + Vec2::new(x, y)] = if rng.chance(0.1) { '#' } else { '.' };
                 }
-                '#' => break,
-                _ => unreachable!(),
-            };
+            }
         }
+        // The path starts on the leftmost open tile of the top row
+        let start_x = map.rows().next().unwrap().iter().position(|&tile| tile != ' ').unwrap();
+        map[Vec2::new(start_x, 0)] = '.';
 
-        // println!("pos: {}, {}", line + 1, column + 1);
+        let mut path = rng.range(1..=2 * side).to_string();
+        for _ in 1..size {
+            path += &format!("{}{}", rng.choose(&['L', 'R']), rng.range(1..=2 * side));
+        }
 
-        if let Some((idx, rotation)) = char_iter.next() {
-            match rotation {
-                'L' => {
-                    current_direction = (current_direction - 1).rem_euclid(4);
-                    start_number_idx = idx + 1;
-                }
-                'R' => {
-                    current_direction = (current_direction + 1).rem_euclid(4);
-                    start_number_idx = idx + 1;
+        // Without the padding right of the faces, as in the puzzle inputs
+        let map: String = map
+            .to_string()
+            .lines()
+            .map(|row| format!("{}\n", row.trim_end()))
+            .collect();
+        Some(format!("{map}\n{path}\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::PartSelection;
+
+    const EXAMPLE: &str = "        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day22::parse(EXAMPLE).unwrap();
+        assert_eq!(Day22::part_1(&input), Answer::Number(6032));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day22::parse(EXAMPLE).unwrap();
+        assert_eq!(Day22::part_2(&input), Answer::Number(5031));
+    }
+
+    #[test]
+    fn cube_walks() {
+        for seed in 0..40 {
+            let data = Day22::generate(&mut Rng::new(seed), 3, &Params::default()).unwrap();
+            let input = Day22::parse(&data).unwrap();
+            let cube = input.cube.as_ref().unwrap();
+            assert_eq!(cube.side, 3);
+
+            for (pos, _) in input.map.iter().filter(|&(_, &tile)| tile != ' ') {
+                for direction in Direction::ALL {
+                    // Straight ahead around the cube and back
+                    let (mut next, mut facing) = (pos, direction);
+                    for _ in 0..4 * cube.side {
+                        (next, facing) = cube.step(next, facing);
+                        assert_ne!(input.map[next], ' ');
+                    }
+                    assert_eq!((next, facing), (pos, direction), "seed {seed}\n{data}");
+
+                    let (next, facing) = cube.step(pos, direction);
+                    let back = cube.step(next, facing.opposite());
+                    assert_eq!(back, (pos, direction.opposite()), "seed {seed}\n{data}");
                 }
-                _ => unreachable!(),
             }
-        } else {
-            part1_res = 1000 * (line + 1) + 4 * (column + 1) + current_direction as usize;
-            break;
         }
     }
 
-    println!("Part 1: {part1_res}");
+    #[test]
+    fn not_a_cube() {
+        // Part 1 still walks around the flat map
+        let maps = [
+            ("......", 1008),
+            ("...\n...", 1008),
+            (".\n..\n...", 1004),
+            ("..\n..\n.#\n..", 1008),
+        ];
+        for (map, password) in maps {
+            let input = Day22::parse(&format!("{map}\n\n1\n")).unwrap();
+            assert!(input.cube.is_none(), "{map}");
+            assert_eq!(Day22::part_1(&input), Answer::Number(password), "{map}");
+        }
 
-    // Part 2
-    todo!()
+        // Only folded for part 2
+        let options = RunOptions {
+            parts: PartSelection::Only(1),
+            ..Default::default()
+        };
+        let input = Day22::parse_with(EXAMPLE, &options).unwrap();
+        assert!(input.cube.is_none());
+        assert_eq!(Day22::part_1(&input), Answer::Number(6032));
+    }
 }
@@ -1,4 +1,9 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::utils::grid::Grid;
+use super::utils::rng::Rng;
+use super::utils::vector::{Direction8, Vec2};
+use super::{Answer, Param, Params, ParseError, RunOptions, Solution};
+use std::collections::{HashMap, HashSet};
 
 /// --- Day 23: Unstable Diffusion ---
 /// You enter a large crater of gray dirt where the grove is supposed to be. All around you, plants
@@ -248,222 +253,215 @@ use super::load_file;
 /// ....#......#..
 /// .......#......
 /// Figure out where the Elves need to go. What is the number of the first round where no Elf moves?
-pub fn day_23() {
-    let data = load_file(23);
-
-    let data_as_lines = data.trim().split('\n');
-
-    use std::collections::{HashMap, HashSet};
-
-    // (line, col)
-    const NORTH_WEST: (i64, i64) = (-1, -1);
-    const NORTH: (i64, i64) = (-1, 0);
-    const NORTH_EAST: (i64, i64) = (-1, 1);
-    const EAST: (i64, i64) = (0, 1);
-    const SOUTH_EAST: (i64, i64) = (1, 1);
-    const SOUTH: (i64, i64) = (1, 0);
-    const SOUTH_WEST: (i64, i64) = (1, -1);
-    const WEST: (i64, i64) = (0, -1);
-
-    type ZoneCheck = [(i64, i64); 3];
-
-    // (check_zone, move)
-    const MOVE_CYCLE: [(ZoneCheck, (i64, i64)); 4] = [
-        ([NORTH_WEST, NORTH, NORTH_EAST], NORTH),
-        ([SOUTH_WEST, SOUTH, SOUTH_EAST], SOUTH),
-        ([NORTH_WEST, WEST, SOUTH_WEST], WEST),
-        ([NORTH_EAST, EAST, SOUTH_EAST], EAST),
-    ];
-
-    let mut elves: HashSet<(i64, i64)> = Default::default();
-
-    for (line_idx, line) in data_as_lines.enumerate() {
-        for (col_idx, c) in line.trim().chars().enumerate() {
-            if c == '#' {
-                elves.insert((line_idx.try_into().unwrap(), col_idx.try_into().unwrap()));
+pub struct Day23;
+
+/// Zone that must be free of elves for an elf to move, and where it moves, in the order the elves
+/// consider them on the first round
+pub const MOVE_CYCLE: [([Direction8; 3], Direction8); 4] = [
+    (
+        [Direction8::UpLeft, Direction8::Up, Direction8::UpRight],
+        Direction8::Up,
+    ),
+    (
+        [Direction8::DownLeft, Direction8::Down, Direction8::DownRight],
+        Direction8::Down,
+    ),
+    (
+        [Direction8::UpLeft, Direction8::Left, Direction8::DownLeft],
+        Direction8::Left,
+    ),
+    (
+        [Direction8::UpRight, Direction8::Right, Direction8::DownRight],
+        Direction8::Right,
+    ),
+];
+
+/// Play round `round_idx`, returns whether any elf wanted to move
+pub fn play_round(elves: &mut HashSet<Vec2<i64>>, round_idx: usize) -> bool {
+    // Destination, elves wanting to move there
+    let mut planned_moves: HashMap<Vec2<i64>, Vec<Vec2<i64>>> = Default::default();
+
+    let first_direction_idx = round_idx % MOVE_CYCLE.len();
+
+    for &elf in elves.iter() {
+        if !elf.neighbours_8().any(|pos| elves.contains(&pos)) {
+            continue;
+        }
+
+        for move_diff in 0..MOVE_CYCLE.len() {
+            let move_idx = (first_direction_idx + move_diff) % MOVE_CYCLE.len();
+
+            let (zone, direction) = MOVE_CYCLE[move_idx];
+            if zone.iter().all(|dir| !elves.contains(&(elf + dir.offset()))) {
+                planned_moves
+                    .entry(elf + direction.offset())
+                    .or_default()
+                    .push(elf);
+                break;
             }
         }
     }
 
-    // Destination, Vec of elves wanting to move there
-    let mut planned_moves: HashMap<(i64, i64), Vec<(i64, i64)>> = Default::default();
-
-    #[allow(dead_code)]
-    fn debug_print(elves: &HashSet<(i64, i64)>) {
-        let (min_line, max_line, min_col, max_col) =
-            elves
-                .iter()
-                .fold((i64::MAX, i64::MIN, i64::MAX, i64::MIN), |acc, x| {
-                    let (min_line, max_line, min_col, max_col) = acc;
-                    let &(line, col) = x;
-                    (
-                        min_line.min(line),
-                        max_line.max(line),
-                        min_col.min(col),
-                        max_col.max(col),
-                    )
-                });
-
-        for line in min_line..=max_line {
-            for col in min_col..=max_col {
-                if elves.contains(&(line, col)) {
-                    print!("#");
-                } else {
-                    print!(".");
-                }
-            }
-            println!();
+    let any_move = !planned_moves.is_empty();
+
+    for (planned_move, elves_moving) in planned_moves {
+        if let [elf] = elves_moving[..] {
+            elves.remove(&elf);
+            elves.insert(planned_move);
         }
-        println!()
     }
 
-    // debug_print(&elves);
+    any_move
+}
 
-    let mut part_1_elves = elves.clone();
+/// Corners of the smallest rectangle holding the elves, bounds included
+pub fn bounds(elves: &HashSet<Vec2<i64>>) -> (Vec2<i64>, Vec2<i64>) {
+    elves.iter().fold(
+        (Vec2::new(i64::MAX, i64::MAX), Vec2::new(i64::MIN, i64::MIN)),
+        |(min, max), elf| {
+            (
+                Vec2::new(min.x.min(elf.x), min.y.min(elf.y)),
+                Vec2::new(max.x.max(elf.x), max.y.max(elf.y)),
+            )
+        },
+    )
+}
 
-    for round_idx in 0..10 {
-        let first_direction_idx = round_idx % MOVE_CYCLE.len();
+/// Smallest rectangle holding the elves, drawn as in the puzzle statement
+pub fn render(elves: &HashSet<Vec2<i64>>) -> Grid<char> {
+    if elves.is_empty() {
+        return Grid::default();
+    }
 
-        for &(line, col) in part_1_elves.iter() {
-            let mut should_move = false;
-            for (diff_line, diff_col) in [
-                NORTH_WEST, NORTH, NORTH_EAST, EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, WEST,
-            ] {
-                let dst = (line + diff_line, col + diff_col);
-                if part_1_elves.get(&dst).is_some() {
-                    should_move = true;
-                    break;
-                }
-            }
+    let (min, max) = bounds(elves);
+    let size: Vec2<usize> = (max - min).try_cast().unwrap();
 
-            if !should_move {
-                continue;
-            }
+    let mut grove = Grid::new(size.x + 1, size.y + 1, '.');
+    for &elf in elves {
+        grove[(elf - min).try_cast().unwrap()] = '#';
+    }
+    grove
+}
 
-            for move_diff in 0..4 {
-                let move_idx = (first_direction_idx + move_diff) % MOVE_CYCLE.len();
-
-                let (zone, direction) = MOVE_CYCLE[move_idx];
-                let mut can_move = true;
-                for (diff_line, diff_col) in zone {
-                    let check_location = (line + diff_line, col + diff_col);
-                    if part_1_elves.contains(&check_location) {
-                        can_move = false;
-                        break;
-                    }
-                }
-
-                if can_move {
-                    let move_location = (line + direction.0, col + direction.1);
-                    if let Some(planned_move) = planned_moves.get_mut(&move_location) {
-                        planned_move.push((line, col));
-                    } else {
-                        planned_moves.insert(move_location, vec![(line, col)]);
-                    }
-                    break;
-                }
-            }
-        }
+const ROUNDS: Param = Param {
+    name: "rounds",
+    default: 10,
+    min: 0,
+    max: i64::MAX,
+    doc: "rounds before part 1 measures the empty ground",
+};
+
+pub struct Grove {
+    pub elves: HashSet<Vec2<i64>>,
+    pub rounds: usize,
+}
 
-        for (planned_move, elves_moving) in planned_moves.drain() {
-            if elves_moving.len() > 1 {
-                continue;
-            }
+impl Solution for Day23 {
+    const DAY: usize = 23;
 
-            part_1_elves.remove(&elves_moving[0]);
-            part_1_elves.insert(planned_move);
-        }
-        // debug_print(&part_1_elves);
+    const PARAMS: &'static [Param] = &[ROUNDS];
+
+    type Input = Grove;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        Self::parse_with(data, &RunOptions::default())
     }
 
-    // debug_print(&part_1_elves);
+    fn parse_with(data: &str, options: &RunOptions) -> Result<Self::Input, ParseError> {
+        let grove = Grid::parse(data, "# or .", |_, c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
 
-    let (min_line, max_line, min_col, max_col) =
-        part_1_elves
+        let elves: HashSet<Vec2<i64>> = grove
             .iter()
-            .fold((i64::MAX, i64::MIN, i64::MAX, i64::MIN), |acc, x| {
-                let (min_line, max_line, min_col, max_col) = acc;
-                let &(line, col) = x;
-                (
-                    min_line.min(line),
-                    max_line.max(line),
-                    min_col.min(col),
-                    max_col.max(col),
-                )
-            });
-
-    // println!("{:?}", (min_line, max_line, min_col, max_col));
-
-    let terrain_area = (max_line - min_line + 1) * (max_col - min_col + 1);
-
-    // println!("{terrain_area}");
-
-    let free_terrain = terrain_area - elves.len() as i64;
-
-    println!("Part 1: {free_terrain}");
-
-    let mut part_2_elves = elves;
-    let mut part_2_res = 0;
-
-    for round_idx in 0.. {
-        let first_direction_idx = round_idx % MOVE_CYCLE.len();
-
-        for &(line, col) in part_2_elves.iter() {
-            let mut should_move = false;
-            for (diff_line, diff_col) in [
-                NORTH_WEST, NORTH, NORTH_EAST, EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, WEST,
-            ] {
-                let dst = (line + diff_line, col + diff_col);
-                if part_2_elves.get(&dst).is_some() {
-                    should_move = true;
-                    break;
-                }
-            }
+            .filter(|&(_, &elf)| elf)
+            .map(|(pos, _)| pos.try_cast().unwrap())
+            .collect();
 
-            if !should_move {
-                continue;
-            }
+        debug!("Initial state:\n{}", render(&elves));
 
-            for move_diff in 0..4 {
-                let move_idx = (first_direction_idx + move_diff) % MOVE_CYCLE.len();
-
-                let (zone, direction) = MOVE_CYCLE[move_idx];
-                let mut can_move = true;
-                for (diff_line, diff_col) in zone {
-                    let check_location = (line + diff_line, col + diff_col);
-                    if part_2_elves.contains(&check_location) {
-                        can_move = false;
-                        break;
-                    }
-                }
-
-                if can_move {
-                    let move_location = (line + direction.0, col + direction.1);
-                    if let Some(planned_move) = planned_moves.get_mut(&move_location) {
-                        planned_move.push((line, col));
-                    } else {
-                        planned_moves.insert(move_location, vec![(line, col)]);
-                    }
-                    break;
-                }
-            }
-        }
+        Ok(Grove {
+            elves,
+            rounds: options.params.get(&ROUNDS),
+        })
+    }
 
-        if planned_moves.is_empty() {
-            part_2_res = round_idx + 1;
-            break;
+    fn part_1(input: &Self::Input) -> Answer {
+        let mut elves = input.elves.clone();
+
+        for round_idx in 0..input.rounds {
+            play_round(&mut elves, round_idx);
+            trace!("End of round {}:\n{}", round_idx + 1, render(&elves));
         }
 
-        for (planned_move, elves_moving) in planned_moves.drain() {
-            if elves_moving.len() > 1 {
-                continue;
-            }
+        debug!("End of round {}:\n{}", input.rounds, render(&elves));
+
+        let (min, max) = bounds(&elves);
+
+        debug!("Columns {}..={}, lines {}..={}", min.x, max.x, min.y, max.y);
+
+        let terrain_area = (max.x - min.x + 1) * (max.y - min.y + 1);
+
+        debug!("Area: {terrain_area}");
+
+        let free_terrain = terrain_area - elves.len() as i64;
 
-            part_2_elves.remove(&elves_moving[0]);
-            part_2_elves.insert(planned_move);
+        free_terrain.into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        let mut elves = input.elves.clone();
+
+        let mut round_idx = 0;
+        while play_round(&mut elves, round_idx) {
+            round_idx += 1;
+            trace!("End of round {round_idx}:\n{}", render(&elves));
         }
-        // debug_print(&part_2_elves);
+
+        // Rounds are counted from 1
+        (round_idx + 1).into()
+    }
+
+    // `size` by `size` tiles, about half of them with an elf
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let grove = (0..size)
+            .map(|_| {
+                let row: String = (0..size)
+                    .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+                    .collect();
+                row + "\n"
+            })
+            .collect();
+
+        Some(grove)
     }
+}
 
-    println!("Part 2: {part_2_res}");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#..
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day23::parse(EXAMPLE).unwrap();
+        assert_eq!(Day23::part_1(&input), Answer::Number(110));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day23::parse(EXAMPLE).unwrap();
+        assert_eq!(Day23::part_2(&input), Answer::Number(20));
+    }
 }
@@ -1,4 +1,9 @@
-use super::load_file;
+use super::log::{debug, trace};
+use super::utils::grid::{Grid, Pos};
+use super::utils::rng::Rng;
+use super::utils::search;
+use super::utils::vector::{Direction, Vec2};
+use super::{Answer, Params, ParseError, Solution};
 
 /// --- Day 24: Blizzard Basin ---
 /// With everything replanted for next year (and with elephants and monkeys to tend the grove), you
@@ -251,8 +256,277 @@ use super::load_file;
 /// #<....>#
 /// ######E#
 /// What is the fewest number of minutes required to avoid the blizzards and reach the goal?
-pub fn day_24() {
-    let _data = load_file(24);
+pub struct Day24;
 
-    todo!("Sigh, yet another path finding thing")
+/// Valley between the walls, with the blizzards where they are at minute 0. Positions are on the
+/// map, walls included, so that the entrance and the exit are in the top and bottom rows.
+#[derive(Debug)]
+pub struct Valley {
+    /// The entrance and the exit are the only gaps in the walls around the valley
+    walls: Grid<bool>,
+    /// Ground inside the walls holding a blizzard at minute 0, a grid per direction indexed by
+    /// `Direction as usize`
+    blizzards: [Grid<bool>; 4],
+    pub entrance: Pos,
+    pub exit: Pos,
+    /// Minutes after which every blizzard is back where it started, the lcm of the width and the
+    /// height of the ground inside the walls
+    pub period: usize,
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+impl Valley {
+    /// Directions of the blizzards at `pos` after `minute` minutes. A blizzard going `direction`
+    /// is there when it started `minute` tiles behind, wrapping around the ground inside the walls.
+    pub fn blizzards_at(&self, pos: Pos, minute: usize) -> impl Iterator<Item = Direction> + '_ {
+        let ground = &self.blizzards[0];
+        let (width, height) = (ground.width(), ground.height());
+        let inside = (1..=width).contains(&pos.x) && (1..=height).contains(&pos.y);
+
+        Direction::ALL.into_iter().filter(move |&direction| {
+            if !inside {
+                return false;
+            }
+            let offset: Vec2<isize> = direction.offset();
+            let start_x = (pos.x - 1) as isize - offset.x * (minute % width) as isize;
+            let start_y = (pos.y - 1) as isize - offset.y * (minute % height) as isize;
+            let start = Pos::new(
+                start_x.rem_euclid(width as isize) as usize,
+                start_y.rem_euclid(height as isize) as usize,
+            );
+            self.blizzards[direction as usize][start]
+        })
+    }
+
+    pub fn has_blizzard(&self, pos: Pos, minute: usize) -> bool {
+        self.blizzards_at(pos, minute).next().is_some()
+    }
+
+    /// Map after `minute` minutes drawn as in the puzzle statement, the number of blizzards
+    /// standing in for their arrows where several of them meet, with the expedition as `E`
+    pub fn render(&self, minute: usize, expedition: Option<Pos>) -> Grid<char> {
+        let cells = self
+            .walls
+            .iter()
+            .map(|(pos, &wall)| {
+                let blizzards: Vec<Direction> = self.blizzards_at(pos, minute).collect();
+                match blizzards[..] {
+                    _ if wall => '#',
+                    [] => '.',
+                    [direction] => ['>', 'v', '<', '^'][direction as usize],
+                    _ => char::from_digit(blizzards.len() as u32, 10).unwrap(),
+                }
+            })
+            .collect();
+
+        let mut map = Grid::from_cells(self.walls.width(), cells);
+        if let Some(expedition) = expedition {
+            map[expedition] = 'E';
+        }
+        map
+    }
+
+    /// Where the expedition can be a minute after being at `pos`, `minute` being taken modulo the
+    /// period so that the states of a search repeat
+    fn moves(&self, (pos, minute): (Pos, usize)) -> impl Iterator<Item = (Pos, usize)> + '_ {
+        let next_minute = (minute + 1) % self.period;
+
+        std::iter::once(pos)
+            .chain(self.walls.neighbours_4(pos))
+            .filter(move |&next| !self.walls[next] && !self.has_blizzard(next, next_minute))
+            .map(move |next| (next, next_minute))
+    }
+
+    /// Minutes to go through each of `stops` in turn, leaving the first one at minute 0. Going as
+    /// fast as possible to each stop is never worse as the expedition can always wait there.
+    pub fn trip(&self, stops: &[Pos]) -> Option<usize> {
+        let mut minute = 0;
+        for leg in stops.windows(2) {
+            minute += self.crossing(leg[0], leg[1], minute)?;
+            debug!("Minute {minute}, reached {:?}:\n{}", leg[1], self.render(minute, Some(leg[1])));
+        }
+        Some(minute)
+    }
+
+    /// Fewest minutes to go from `from` to `to`, setting off after `minute` minutes
+    pub fn crossing(&self, from: Pos, to: Pos, minute: usize) -> Option<usize> {
+        let path = search::bfs(
+            [(from, minute % self.period)],
+            |&state| self.moves(state),
+            |&(pos, _)| pos == to,
+        )?;
+
+        for (step, &(pos, _)) in path.nodes.iter().enumerate() {
+            trace!("Minute {}:\n{}", minute + step, self.render(minute + step, Some(pos)));
+        }
+
+        Some(path.cost)
+    }
+}
+
+impl Solution for Day24 {
+    const DAY: usize = 24;
+
+    type Input = Valley;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        let height = data.trim_end().lines().count();
+        let width = data.lines().next().map_or(0, |line| line.chars().count());
+        if width < 3 || height < 3 {
+            return Err(ParseError::at_end(data, "a valley inside walls"));
+        }
+
+        let mut blizzards: [Grid<bool>; 4] =
+            std::array::from_fn(|_| Grid::new(width - 2, height - 2, false));
+        let walls = Grid::parse(data, "#, . or a blizzard inside the walls", |pos, c| {
+            let on_wall = pos.x == 0 || pos.x == width - 1 || pos.y == 0 || pos.y == height - 1;
+            let direction = match c {
+                '#' if on_wall => return Some(true),
+                '.' if !on_wall || pos.y == 0 || pos.y == height - 1 => return Some(false),
+                '>' => Direction::Right,
+                'v' => Direction::Down,
+                '<' => Direction::Left,
+                '^' => Direction::Up,
+                _ => return None,
+            };
+            match on_wall {
+                true => None,
+                false => {
+                    blizzards[direction as usize][Pos::new(pos.x - 1, pos.y - 1)] = true;
+                    Some(false)
+                }
+            }
+        })?;
+
+        // The only gap in the top and the bottom walls
+        let gap = |y: usize, expected: &str| {
+            let line = data.lines().nth(y).unwrap();
+            (0..width)
+                .find(|&x| !walls[Pos::new(x, y)])
+                .map(|x| Pos::new(x, y))
+                .ok_or_else(|| ParseError::at(data, line, expected))
+        };
+        let entrance = gap(0, "an entrance in the top wall")?;
+        let exit = gap(height - 1, "an exit in the bottom wall")?;
+
+        let (ground_width, ground_height) = (width - 2, height - 2);
+        let period = ground_width / gcd(ground_width, ground_height) * ground_height;
+
+        debug!("Blizzards back in place every {period} minutes");
+
+        Ok(Valley {
+            walls,
+            blizzards,
+            entrance,
+            exit,
+            period,
+        })
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        input.trip(&[input.entrance, input.exit]).unwrap().into()
+    }
+
+    fn part_2(input: &Self::Input) -> Answer {
+        let stops = [input.entrance, input.exit, input.entrance, input.exit];
+        input.trip(&stops).unwrap().into()
+    }
+
+    // A valley `size` tiles wide, a quarter as high, walls excluded
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let (width, height) = (size.max(3), (size / 4).max(2));
+
+        // Blizzards can wall off the exit for good, the valley is drawn again until it cannot
+        loop {
+            let density = rng.range(30..=60) as f64 / 100.0;
+
+            let mut valley = format!("#.{}\n", "#".repeat(width));
+            for _ in 0..height {
+                let row: String = (0..width)
+                    .map(|x| {
+                        // As in the puzzle inputs, no blizzard crosses the entrance or the exit
+                        let blizzards: &[char] = match x == 0 || x == width - 1 {
+                            true => &['<', '>'],
+                            false => &['<', '>', '^', 'v'],
+                        };
+                        match rng.chance(density) {
+                            true => *rng.choose(blizzards),
+                            false => '.',
+                        }
+                    })
+                    .collect();
+                valley += &format!("#{row}#\n");
+            }
+            valley += &format!("{}.#\n", "#".repeat(width));
+
+            let parsed = Self::parse(&valley).ok()?;
+            let stops = [parsed.entrance, parsed.exit, parsed.entrance, parsed.exit];
+            if parsed.trip(&stops).is_some() {
+                return Some(valley);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day24::parse(EXAMPLE).unwrap();
+        assert_eq!(Day24::part_1(&input), Answer::Number(18));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = Day24::parse(EXAMPLE).unwrap();
+        assert_eq!(Day24::part_2(&input), Answer::Number(54));
+    }
+
+    #[test]
+    fn blizzards() {
+        let valley = Day24::parse(EXAMPLE).unwrap();
+        assert_eq!(valley.period, 12);
+        assert_eq!(valley.render(0, None).to_string(), EXAMPLE);
+        assert_eq!(valley.render(12, None).to_string(), EXAMPLE);
+
+        let minute_1 = "\
+#.######
+#E>3.<.#
+#<..<<.#
+#>2.22.#
+#>v..^<#
+######.#
+";
+        let expedition = Pos::new(1, 1);
+        assert_eq!(valley.render(1, Some(expedition)).to_string(), minute_1);
+        assert!(!valley.has_blizzard(expedition, 1));
+        assert!(valley.has_blizzard(Pos::new(3, 1), 1));
+    }
+
+    #[test]
+    fn invalid_valley() {
+        let err = Day24::parse("#.###\n#.>.#\n#####\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        assert_eq!(err.expected, "an exit in the bottom wall");
+
+        let err = Day24::parse("#.###\n>...#\n###.#\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
 }
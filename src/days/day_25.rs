@@ -1,4 +1,8 @@
-use super::load_file;
+use super::parse;
+use super::utils::rng::Rng;
+use super::{Answer, Params, ParseError, Solution};
+use std::fmt::Display;
+use std::str::FromStr;
 
 /// --- Day 25: Full of Hot Air ---
 /// As the expedition finally reaches the extraction point, several large hot air balloons drift
@@ -127,57 +131,140 @@ use super::load_file;
 /// need to supply to Bob's console is 2=-1=0.
 ///
 /// The Elves are starting to get cold. What SNAFU number do you supply to Bob's console?
-pub fn day_25() {
-    let data = load_file(25);
+pub struct Day25;
 
-    struct Snafu {
-        pub dec: i64,
-    }
+/// Number in balanced base 5, its digits `=`, `-`, `0`, `1` and `2` worth -2 to 2, so that
+/// negative numbers need no sign
+pub struct Snafu {
+    pub dec: i64,
+}
 
-    use std::fmt::Display;
+impl Display for Snafu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const SNAFU_DIGITS: [char; 5] = ['0', '1', '2', '=', '-'];
+        if self.dec == 0 {
+            return f.write_str("0");
+        }
 
-    impl Display for Snafu {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            const SNAFU_DIGITS: [char; 5] = ['0', '1', '2', '=', '-'];
-            let mut s = String::new();
-            let mut dec = self.dec;
-            while dec > 0 {
-                s.push(SNAFU_DIGITS[dec.rem_euclid(5) as usize]);
-                dec = (dec + 2) / 5;
-            }
-            let s: String = s.chars().rev().collect();
-            f.write_str(&s)
+        let mut s = String::new();
+        // Wider so that carrying past `i64::MAX` or `i64::MIN` does not overflow
+        let mut dec = i128::from(self.dec);
+        while dec != 0 {
+            s.push(SNAFU_DIGITS[dec.rem_euclid(5) as usize]);
+            dec = (dec + 2).div_euclid(5);
         }
+        let s: String = s.chars().rev().collect();
+        f.write_str(&s)
     }
+}
+
+impl FromStr for Snafu {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseError::at(s, s, "a SNAFU number"));
+        }
 
-    use std::str::FromStr;
-
-    impl FromStr for Snafu {
-        type Err = Box<dyn std::error::Error>;
-
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            let mut dec: i64 = 0;
-            for (power, char) in s.chars().rev().enumerate() {
-                match char {
-                    '2' => dec += 2 * 5i64.pow(power as u32),
-                    '1' => dec += 5i64.pow(power as u32),
-                    '0' => (),
-                    '-' => dec -= 5i64.pow(power as u32),
-                    '=' => dec -= 2 * 5i64.pow(power as u32),
-                    _ => (),
+        let mut dec: i64 = 0;
+        for (power, (idx, char)) in s.char_indices().rev().enumerate() {
+            match char {
+                '2' => dec += 2 * 5i64.pow(power as u32),
+                '1' => dec += 5i64.pow(power as u32),
+                '0' => (),
+                '-' => dec -= 5i64.pow(power as u32),
+                '=' => dec -= 2 * 5i64.pow(power as u32),
+                _ => {
+                    let found = &s[idx..idx + char.len_utf8()];
+                    return Err(ParseError::at(s, found, "a SNAFU digit"));
                 }
             }
+        }
+
+        Ok(Self { dec })
+    }
+}
+
+impl Solution for Day25 {
+    const DAY: usize = 25;
+
+    const PARTS: usize = 1;
 
-            Ok(Self { dec })
+    type Input = Vec<Snafu>;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError> {
+        parse::lines(data)
+    }
+
+    fn part_1(input: &Self::Input) -> Answer {
+        let mut total = 0;
+
+        for snafu in input {
+            total += snafu.dec;
         }
+
+        Snafu { dec: total }.to_string().into()
+    }
+
+    // `size` numbers
+    fn generate(rng: &mut Rng, size: usize, _params: &Params) -> Option<String> {
+        let numbers = (0..size.max(1))
+            .map(|_| {
+                let dec = rng.range(1..=1_000_000_000_000);
+                format!("{}\n", Snafu { dec })
+            })
+            .collect();
+
+        Some(numbers)
     }
+}
 
-    let mut total = 0;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for line in data.trim().split('\n') {
-        let snafu: Snafu = line.parse().unwrap();
-        total += snafu.dec;
+    const EXAMPLE: &str = "\
+1=-0-2
+12111
+2=0=
+21
+2=01
+111
+20012
+112
+1=-1=
+1-12
+12
+1=
+122
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = Day25::parse(EXAMPLE).unwrap();
+        assert_eq!(Day25::part_1(&input), Answer::from("2=-1=0"));
     }
 
-    println!("Part 1: {}", Snafu { dec: total });
+    #[test]
+    fn round_trip() {
+        let known = [
+            (0, "0"),
+            (1, "1"),
+            (3, "1="),
+            (2022, "1=11-2"),
+            (314159265, "1121-1110-1=0"),
+            (-1, "-"),
+            (-3, "-2"),
+            (-4890, "=21-20"),
+        ];
+        for (dec, snafu) in known {
+            assert_eq!(Snafu { dec }.to_string(), snafu);
+            assert_eq!(snafu.parse::<Snafu>().unwrap().dec, dec);
+        }
+
+        for dec in (-1000..=1000).chain([i64::MIN, i64::MIN + 1, i64::MAX - 1, i64::MAX]) {
+            let snafu = Snafu { dec }.to_string();
+            assert_eq!(snafu.parse::<Snafu>().unwrap().dec, dec, "{snafu}");
+        }
+    }
 }
@@ -0,0 +1,103 @@
+//! Runtime switchable trace output of the days, written to stderr so that it does not mix with the
+//! answers. `-v` enables the [`debug!`] messages, such as the state of a puzzle after each round,
+//! `-vv` also the [`trace!`] ones, such as every step of a round. The output can be limited to
+//! some days, messages are prefixed with the day they come from.
+//!
+//! Arguments of the macros are only evaluated when the message is printed, rendering a grid for a
+//! message costs nothing otherwise.
+
+use std::cell::Cell;
+use std::fmt::Arguments;
+use std::io::Write;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    /// `-v`
+    Debug,
+    /// `-vv`
+    Trace,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+/// Bit N set when day N is traced, 0 for all days
+static DAY_FILTER: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+    /// Day running on this thread, 0 outside of [`DayScope`]
+    static CURRENT_DAY: Cell<usize> = const { Cell::new(0) };
+}
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Only trace `days`, all of them when empty
+pub fn set_days(days: &[usize]) {
+    let filter = days.iter().fold(0, |filter, day| filter | 1 << day);
+    DAY_FILTER.store(filter, Ordering::Relaxed);
+}
+
+/// Whether messages of `level` from the day running on this thread are printed
+pub fn enabled(level: Level) -> bool {
+    if LEVEL.load(Ordering::Relaxed) < level as u8 {
+        return false;
+    }
+
+    let filter = DAY_FILTER.load(Ordering::Relaxed);
+    filter == 0 || CURRENT_DAY.with(|day| filter & 1 << day.get() != 0)
+}
+
+/// Print a message, each of its lines prefixed with the current day
+pub fn write(message: Arguments) {
+    let message = message.to_string();
+    let prefix = match CURRENT_DAY.get() {
+        0 => String::new(),
+        day => format!("[day {day:02}] "),
+    };
+
+    let mut stderr = std::io::stderr().lock();
+    for line in message.lines() {
+        let _ = writeln!(stderr, "{prefix}{line}");
+    }
+}
+
+/// Marks the day running on the current thread until dropped
+pub struct DayScope {
+    previous: usize,
+}
+
+impl DayScope {
+    pub fn enter(day: usize) -> Self {
+        DayScope {
+            previous: CURRENT_DAY.replace(day),
+        }
+    }
+}
+
+impl Drop for DayScope {
+    fn drop(&mut self) {
+        CURRENT_DAY.set(self.previous);
+    }
+}
+
+/// Message printed with `-v`
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::days::log::enabled($crate::days::log::Level::Debug) {
+            $crate::days::log::write(format_args!($($arg)*));
+        }
+    };
+}
+
+/// Message printed with `-vv`
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::days::log::enabled($crate::days::log::Level::Trace) {
+            $crate::days::log::write(format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use {debug, trace};
@@ -1,28 +1,74 @@
-pub mod day_01;
-pub mod day_02;
-pub mod day_03;
-pub mod day_04;
-pub mod day_05;
-pub mod day_06;
-pub mod day_07;
-pub mod day_08;
-pub mod day_09;
-pub mod day_10;
-pub mod day_11;
-pub mod day_12;
-pub mod day_13;
-pub mod day_14;
-pub mod day_15;
-pub mod day_16;
-pub mod day_17;
-pub mod day_18;
-pub mod day_19;
-pub mod day_20;
-pub mod day_21;
-pub mod day_22;
-pub mod day_23;
-pub mod day_24;
-pub mod day_25;
+pub mod alloc;
+pub mod answers;
+pub mod bench;
+pub mod log;
+pub mod params;
+pub mod parse;
+pub mod solution;
+pub mod toml;
 pub mod utils;
 
-pub use utils::load_file;
+pub use params::{Param, Params};
+pub use parse::ParseError;
+pub use solution::{
+    Answer, DayEntry, DayReport, PartOutcome, PartReport, PartSelection, RunOptions, Solution,
+    Solver,
+};
+pub use utils::{load_file, InputSource};
+
+// The `day_XX` modules and `DAYS`, generated by build.rs from the files of this directory
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
+
+/// `DAYS` entry of `day`, if there is a module for it
+pub fn find_day(day: usize) -> Option<&'static DayEntry> {
+    DAYS.iter().find(|entry| entry.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        let days: Vec<usize> = DAYS.iter().map(|entry| entry.day).collect();
+        assert_eq!(days, (1..=25).collect::<Vec<_>>());
+
+        assert_eq!(find_day(7).unwrap().title, "No Space Left On Device");
+        assert_eq!(find_day(25).unwrap().parts, 1);
+        assert!(find_day(26).is_none());
+    }
+
+    #[test]
+    fn generated_inputs() {
+        let mut options = RunOptions::default();
+        // Day 15 part 2 scans the whole area, keep it small
+        options.params.set("max", 40);
+        options.params.set("y", 20);
+
+        for entry in DAYS {
+            for seed in 0..3 {
+                let data = entry.solver.generate(seed, 8, &options.params);
+                let data = data.unwrap_or_else(|| panic!("No generator for day {}", entry.day));
+
+                let report = entry.solver.run(&data, &options);
+                let report = report.unwrap_or_else(|err| panic!("Day {}: {err}", entry.day));
+                for part in &report.parts {
+                    let failed = matches!(part.outcome, PartOutcome::Failed(_));
+                    assert!(
+                        !failed,
+                        "Day {}, seed {seed}: {:?}",
+                        entry.day, part.outcome
+                    );
+                }
+
+                let again = entry.solver.generate(seed, 8, &options.params);
+                assert_eq!(
+                    again.as_ref(),
+                    Some(&data),
+                    "Day {} is not seeded",
+                    entry.day
+                );
+            }
+        }
+    }
+}
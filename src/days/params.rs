@@ -0,0 +1,182 @@
+//! Puzzle parameters: values hardcoded in the puzzle statements that differ between the examples
+//! and the real inputs, such as the row scanned by day 15. Each day declares its parameters with
+//! the default for the real input in [`super::Solution::PARAMS`], they can be changed in
+//! `aoc.toml`:
+//!
+//! ```toml
+//! [day_15]
+//! y = 10
+//! max = 20
+//! ```
+//!
+//! or on the command line with `--param y=10`, which takes precedence.
+
+use super::toml::{self, TomlError, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the parameters file, it sits next to the `data` directory
+pub const CONFIG_FILE_NAME: &str = "aoc.toml";
+
+/// A parameter declared by a day, all of them are integers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    /// Value for the real inputs
+    pub default: i64,
+    /// Smallest and largest values the day can solve with, both included
+    pub min: i64,
+    pub max: i64,
+    pub doc: &'static str,
+}
+
+impl Param {
+    /// Error when `value` is out of the range of the parameter
+    pub fn check(&self, value: i64) -> Result<(), String> {
+        if (self.min..=self.max).contains(&value) {
+            return Ok(());
+        }
+
+        let expected = match self.max {
+            i64::MAX => format!("at least {}", self.min),
+            max => format!("{}..={max}", self.min),
+        };
+        Err(format!(
+            "Parameter {} = {value} is out of range, expected {expected}",
+            self.name
+        ))
+    }
+}
+
+/// Values given for the parameters of a day, the others keep their default
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params {
+    values: BTreeMap<String, i64>,
+}
+
+impl Params {
+    /// Value of `param`, panics when it does not fit in `T`, which [`Params::check`] rules out
+    /// when the range of the parameter fits in `T`
+    pub fn get<T: TryFrom<i64>>(&self, param: &Param) -> T {
+        let value = self
+            .values
+            .get(param.name)
+            .copied()
+            .unwrap_or(param.default);
+        T::try_from(value)
+            .unwrap_or_else(|_| panic!("Parameter {} = {value} is out of range", param.name))
+    }
+
+    pub fn set(&mut self, name: &str, value: i64) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Error on values given for parameters that are not in `declared`, or out of their range
+    pub fn check(&self, declared: &[Param]) -> Result<(), String> {
+        for param in declared {
+            if let Some(&value) = self.values.get(param.name) {
+                param.check(value)?;
+            }
+        }
+
+        match self
+            .values
+            .keys()
+            .find(|name| !declared.iter().any(|param| param.name == *name))
+        {
+            Some(name) if declared.is_empty() => {
+                Err(format!("Unknown parameter {name}, there are none"))
+            }
+            Some(name) => {
+                let names: Vec<_> = declared.iter().map(|param| param.name).collect();
+                Err(format!(
+                    "Unknown parameter {name}, expected {}",
+                    names.join(", ")
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parameters of every day, as read from `aoc.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParamsConfig {
+    days: BTreeMap<usize, Params>,
+}
+
+/// `aoc.toml` in the directory holding `data_dir`
+pub fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(CONFIG_FILE_NAME)
+}
+
+impl ParamsConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|err| format!("could not read {}: {err}", path.display()))?;
+        data.parse()
+            .map_err(|err| format!("{}, {err}", path.display()))
+    }
+
+    pub fn day(&self, day: usize) -> Params {
+        self.days.get(&day).cloned().unwrap_or_default()
+    }
+}
+
+impl std::str::FromStr for ParamsConfig {
+    type Err = TomlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = ParamsConfig::default();
+
+        for entry in toml::parse(s)? {
+            let day = toml::parse_suffix(&entry.table, "day_").map_err(|err| entry.error(err))?;
+            let Value::Integer(value) = entry.value else {
+                return Err(entry.error(format!("Expected a number for {}", entry.key)));
+            };
+            config.days.entry(day).or_default().set(&entry.key, value);
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Y: Param = Param {
+        name: "y",
+        default: 2_000_000,
+        min: 0,
+        max: 4_000_000,
+        doc: "",
+    };
+
+    #[test]
+    fn config() {
+        let config: ParamsConfig = "[day_15]\ny = 10\n".parse().unwrap();
+
+        assert_eq!(config.day(15).get::<i64>(&Y), 10);
+        assert_eq!(config.day(14).get::<i64>(&Y), 2_000_000);
+        assert!(config.day(15).check(&[Y]).is_ok());
+        assert!(config.day(15).check(&[]).is_err());
+
+        let config: ParamsConfig = "[day_15]\ny = -1\n".parse().unwrap();
+        assert_eq!(
+            config.day(15).check(&[Y]),
+            Err("Parameter y = -1 is out of range, expected 0..=4000000".to_string())
+        );
+
+        assert_eq!(
+            "[day_15]\ny = \"a\"\n"
+                .parse::<ParamsConfig>()
+                .unwrap_err()
+                .line,
+            2
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Error type shared by the input parsers of every day, with a few helpers to produce it.
+//!
+//! Positions are not tracked by hand, an error is created from the string being parsed and the
+//! slice of it that is wrong, the line and column come from where that slice starts. Parsers
+//! delegating a slice of their input to another one move the error back with
+//! [`ParseError::within`].
+
+use std::str::FromStr;
+
+/// What was expected and what was found instead, at a given position of a day input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Day of the input, filled in by [`super::Solver::run`]
+    pub day: usize,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, counted in chars
+    pub column: usize,
+    pub expected: String,
+    /// Empty when the input ended too early
+    pub found: String,
+}
+
+impl ParseError {
+    /// Error at `found`, which must be a slice of `s`
+    pub fn at(s: &str, found: &str, expected: impl Into<String>) -> Self {
+        let before = &s[..offset(s, found)];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+        ParseError {
+            day: 0,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            expected: expected.into(),
+            // Only the first line, multi-line blocks would make an unreadable message
+            found: found.lines().next().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Error at the end of `s`, for input that stops too early
+    pub fn at_end(s: &str, expected: impl Into<String>) -> Self {
+        Self::at(s, &s[s.len()..], expected)
+    }
+
+    /// Move an error found while parsing `inner`, a slice of `outer`, to its position in `outer`
+    pub fn within(mut self, outer: &str, inner: &str) -> Self {
+        let before = &outer[..offset(outer, inner)];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+        if self.line == 1 {
+            self.column += before[line_start..].chars().count();
+        }
+        self.line += before.matches('\n').count();
+        self
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "day {:02}, line {}, column {}: expected {}, ",
+            self.day, self.line, self.column, self.expected
+        )?;
+
+        if self.found.is_empty() {
+            write!(f, "found nothing")
+        } else {
+            write!(f, "found {:?}", self.found)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Byte offset of `inner` in `outer`, `inner` must be a slice of `outer`
+fn offset(outer: &str, inner: &str) -> usize {
+    let offset = (inner.as_ptr() as usize).wrapping_sub(outer.as_ptr() as usize);
+    assert!(
+        offset <= outer.len(),
+        "ParseError position taken from a string that is not a slice of the parsed one"
+    );
+    offset
+}
+
+/// Parse `token`, a slice of `s`, as a number
+pub fn number<T: FromStr>(s: &str, token: &str) -> Result<T, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::at(s, token, "a number"))
+}
+
+/// `token` without `prefix`, `token` being a slice of `s`
+pub fn strip_prefix<'a>(s: &str, token: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+    token
+        .strip_prefix(prefix)
+        .ok_or_else(|| ParseError::at(s, token, format!("{prefix:?}")))
+}
+
+/// `token` split around the first `delimiter`, `token` being a slice of `s`
+pub fn split_once<'a>(
+    s: &str,
+    token: &'a str,
+    delimiter: &str,
+) -> Result<(&'a str, &'a str), ParseError> {
+    token
+        .split_once(delimiter)
+        .ok_or_else(|| ParseError::at(s, token, format!("{delimiter:?}")))
+}
+
+/// Parse each line of `data`
+pub fn lines<T: FromStr<Err = ParseError>>(data: &str) -> Result<Vec<T>, ParseError> {
+    data.lines()
+        .map(|line| {
+            line.parse()
+                .map_err(|err: ParseError| err.within(data, line))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_position() {
+        let data = "1,2\n3,x\n";
+        let line = data.lines().nth(1).unwrap();
+        let err = number::<u32>(data, &line[2..]).unwrap_err();
+
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.found, "x");
+    }
+
+    #[test]
+    fn error_within() {
+        let data = "a\nb: [1,x]\n";
+        let item = &data[8..9];
+        let err = ParseError::at(item, item, "a number").within(data, item);
+
+        assert_eq!((err.line, err.column), (2, 7));
+    }
+
+    #[test]
+    fn error_at_end() {
+        let data = "ab\ncd";
+        let err = split_once(data, &data[3..], ",").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+
+        let err = ParseError::at_end(data, "more");
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 3, ""));
+    }
+}
@@ -0,0 +1,271 @@
+use super::alloc::{self, AllocStats};
+use super::log;
+use super::params::{Param, Params};
+use super::utils::rng::Rng;
+use super::ParseError;
+use std::any::Any;
+use std::fmt::Display;
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
+
+/// Answer to one part of a puzzle, most days give a number but some spell out letters (day 05
+/// crates, day 25 SNAFU) or draw them (day 10 CRT).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    /// For parts that have no puzzle to solve, i.e. the second part of day 25
+    Empty,
+}
+
+impl Answer {
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Text(text) if text.contains('\n'))
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{number}"),
+            Answer::Text(text) => f.write_str(text),
+            Answer::Empty => Ok(()),
+        }
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::Number(value.try_into().expect("Answer does not fit in an i64"))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i32, u32, i64, u64, usize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+/// A day of the advent calendar, the input is parsed once and shared by both parts.
+pub trait Solution {
+    /// Day of the advent calendar, 1-based
+    const DAY: usize;
+
+    /// Values of the puzzle statement that the example and real inputs don't share, the days using
+    /// some read them from the options given to [`Solution::parse_with`]
+    const PARAMS: &'static [Param] = &[];
+
+    /// Number of puzzles of the day, 1 for day 25 which has no second one to solve
+    const PARTS: usize = 2;
+
+    type Input;
+
+    fn parse(data: &str) -> Result<Self::Input, ParseError>;
+
+    /// Parse with the parameters of the run and for the selected parts only, for days with
+    /// [`Solution::PARAMS`] or whose parsing prepares data that only one part uses. Parts that were
+    /// not selected are not run on the result.
+    fn parse_with(data: &str, _options: &RunOptions) -> Result<Self::Input, ParseError> {
+        Self::parse(data)
+    }
+
+    fn part_1(input: &Self::Input) -> Answer;
+
+    fn part_2(_input: &Self::Input) -> Answer {
+        Answer::Empty
+    }
+
+    /// Random input that the solver accepts, for the given parameters. `size` scales it, each day
+    /// saying what it counts, e.g. the number of commands of day 07 or of sensors of day 15. `None`
+    /// for days without a generator.
+    fn generate(_rng: &mut Rng, _size: usize, _params: &Params) -> Option<String> {
+        None
+    }
+}
+
+/// Parts of a day to run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PartSelection {
+    #[default]
+    Both,
+    /// 1 or 2
+    Only(usize),
+}
+
+impl PartSelection {
+    pub fn includes(self, part_nb: usize) -> bool {
+        match self {
+            PartSelection::Both => true,
+            PartSelection::Only(selected) => selected == part_nb,
+        }
+    }
+}
+
+/// How to run a day, besides its input
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunOptions {
+    pub parts: PartSelection,
+    pub params: Params,
+}
+
+/// What came out of running one part of a day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartOutcome {
+    Solved(Answer),
+    /// The part hit a `todo!()`
+    Unimplemented,
+    /// The part panicked, contains the panic message
+    Failed(String),
+    /// The part was not selected
+    Skipped,
+}
+
+impl PartOutcome {
+    fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        let message = panic_message(payload.as_ref()).unwrap_or("Box<dyn Any>");
+
+        if is_todo_message(message) {
+            PartOutcome::Unimplemented
+        } else {
+            PartOutcome::Failed(message.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartReport {
+    pub outcome: PartOutcome,
+    pub time: Duration,
+    /// With the `alloc-stats` feature only
+    pub allocs: Option<AllocStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayReport {
+    pub parse_time: Duration,
+    pub parse_allocs: Option<AllocStats>,
+    pub parts: [PartReport; 2],
+}
+
+impl DayReport {
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.parts.iter().map(|part| part.time).sum::<Duration>()
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    if let Some(&message) = payload.downcast_ref::<&'static str>() {
+        Some(message)
+    } else {
+        payload.downcast_ref::<String>().map(String::as_str)
+    }
+}
+
+/// `todo!()` panics with this message, optionally followed by `: <reason>`
+pub fn is_todo_message(message: &str) -> bool {
+    message.starts_with("not yet implemented")
+}
+
+/// Run `f` catching panics, with its time and allocations
+fn timed<T>(f: impl FnOnce() -> T) -> (std::thread::Result<T>, Duration, Option<AllocStats>) {
+    let start = Instant::now();
+    let (res, allocs) = alloc::measure(|| std::panic::catch_unwind(AssertUnwindSafe(f)));
+    (res, start.elapsed(), allocs)
+}
+
+/// Object safe version of [`Solution`] so that days can be stored in a single table.
+pub trait Solver {
+    /// Parse and solve the selected parts, timing each step and counting its allocations when the
+    /// `alloc-stats` feature is enabled. Panics are caught so that a day
+    /// hitting a `todo!()` or a bug does not take down the other days being run.
+    fn run(&self, data: &str, options: &RunOptions) -> Result<DayReport, ParseError>;
+
+    /// Input made up from `seed`, see [`Solution::generate`]
+    fn generate(&self, seed: u64, size: usize, params: &Params) -> Option<String>;
+}
+
+impl<S: Solution> Solver for S {
+    fn run(&self, data: &str, options: &RunOptions) -> Result<DayReport, ParseError> {
+        let parts = options.parts;
+        let _day = log::DayScope::enter(S::DAY);
+        let skipped = PartReport {
+            outcome: PartOutcome::Skipped,
+            time: Duration::ZERO,
+            allocs: None,
+        };
+        let (input, parse_time, parse_allocs) = timed(|| S::parse_with(data, options));
+
+        let input = match input {
+            Ok(Ok(input)) => input,
+            Ok(Err(err)) => return Err(ParseError { day: S::DAY, ..err }),
+            Err(payload) => {
+                let part = PartReport {
+                    outcome: PartOutcome::from_panic(payload),
+                    time: Duration::ZERO,
+                    allocs: None,
+                };
+                let parts = [1, 2].map(|part_nb| match parts.includes(part_nb) {
+                    true => part.clone(),
+                    false => skipped.clone(),
+                });
+                return Ok(DayReport {
+                    parse_time,
+                    parse_allocs,
+                    parts,
+                });
+            }
+        };
+
+        let parts = [(1, S::part_1 as fn(&_) -> _), (2, S::part_2)].map(|(part_nb, part)| {
+            if !parts.includes(part_nb) {
+                return skipped.clone();
+            }
+
+            let (answer, time, allocs) = timed(|| part(&input));
+            let outcome = match answer {
+                Ok(answer) => PartOutcome::Solved(answer),
+                Err(payload) => PartOutcome::from_panic(payload),
+            };
+            PartReport {
+                outcome,
+                time,
+                allocs,
+            }
+        });
+
+        Ok(DayReport {
+            parse_time,
+            parse_allocs,
+            parts,
+        })
+    }
+
+    fn generate(&self, seed: u64, size: usize, params: &Params) -> Option<String> {
+        S::generate(&mut Rng::new(seed), size, params)
+    }
+}
+
+/// A day found by the build script, see [`super::DAYS`]
+pub struct DayEntry {
+    pub day: usize,
+    /// From the `--- Day N: Title ---` header of the puzzle statement
+    pub title: &'static str,
+    /// [`Solution::PARTS`] of the day
+    pub parts: usize,
+    pub params: &'static [Param],
+    pub solver: &'static (dyn Solver + Sync),
+}
@@ -0,0 +1,197 @@
+//! The small subset of TOML used by the answers and parameters files: `[table]` headers followed
+//! by `key = value` lines, the values being integers (`_` separators allowed), strings or
+//! multi-line strings, and `#` comments.
+
+/// Value of a key, multi-line strings are plain strings once parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Integer(i64),
+    String(String),
+}
+
+/// A `key = value` line, along with the table it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// 1-based line of the key
+    pub line: usize,
+    pub table: String,
+    pub key: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TomlError {
+    /// 1-based line of the file
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for TomlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+impl Entry {
+    pub fn error(&self, message: String) -> TomlError {
+        TomlError {
+            line: self.line,
+            message,
+        }
+    }
+}
+
+/// Every key of `s` in order, keys outside of a table are an error
+pub fn parse(s: &str) -> Result<Vec<Entry>, TomlError> {
+    let mut entries = vec![];
+    let mut table = None;
+    let mut lines = s.lines().enumerate().map(|(idx, line)| (idx + 1, line));
+
+    while let Some((line_nb, line)) = lines.next() {
+        let error = |message: String| TomlError {
+            line: line_nb,
+            message,
+        };
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| error(format!("Unclosed table header: {line}")))?;
+            table = Some(name.trim().to_string());
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| error(format!("Expected key = value, got: {line}")))?;
+        let table = table
+            .clone()
+            .ok_or_else(|| error(format!("Key outside of a table: {}", key.trim())))?;
+        let value = value.trim();
+
+        let value = if let Some(rest) = value.strip_prefix("\"\"\"") {
+            // The newline right after the opening quotes is not part of the string
+            let mut text = String::new();
+            let mut rest = rest.to_string();
+            loop {
+                if let Some(end) = rest.find("\"\"\"") {
+                    text.push_str(&rest[..end]);
+                    break;
+                }
+                text.push_str(&rest);
+                text.push('\n');
+                rest = match lines.next() {
+                    Some((_, line)) => line.to_string(),
+                    None => return Err(error("Unclosed multi-line string".to_string())),
+                };
+            }
+            Value::String(unescape(text.strip_prefix('\n').unwrap_or(&text)).map_err(error)?)
+        } else if let Some(rest) = value.strip_prefix('"') {
+            let text = rest
+                .strip_suffix('"')
+                .ok_or_else(|| error(format!("Unclosed string: {value}")))?;
+            Value::String(unescape(text).map_err(error)?)
+        } else {
+            let number = value
+                .replace('_', "")
+                .parse()
+                .map_err(|_| error(format!("Expected a number or a string, got: {value}")))?;
+            Value::Integer(number)
+        };
+
+        entries.push(Entry {
+            line: line_nb,
+            table,
+            key: key.trim().to_string(),
+            value,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// `N` out of `<prefix>N`, e.g. the day of a `day_05` table
+pub fn parse_suffix(name: &str, prefix: &str) -> Result<usize, String> {
+    name.strip_prefix(prefix)
+        .and_then(|nb| nb.parse().ok())
+        .ok_or_else(|| format!("Expected {prefix}<number>, got: {name}"))
+}
+
+/// Drop a `#` comment, unless it is inside a string: the day 10 CRT is drawn with `#`
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..idx],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => res.push('\\'),
+            Some('"') => res.push('"'),
+            Some('n') => res.push('\n'),
+            Some('t') => res.push('\t'),
+            other => {
+                return Err(format!(
+                    "Unsupported escape sequence: \\{}",
+                    other.unwrap_or(' ')
+                ))
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_entries() {
+        let entries = parse("# Comment\n[a]\nb = 1_000 # Trailing\n\n[c]\nd = \"#\"\n").unwrap();
+
+        assert_eq!(
+            entries,
+            [
+                Entry {
+                    line: 3,
+                    table: "a".to_string(),
+                    key: "b".to_string(),
+                    value: Value::Integer(1000),
+                },
+                Entry {
+                    line: 6,
+                    table: "c".to_string(),
+                    key: "d".to_string(),
+                    value: Value::String("#".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn key_outside_table() {
+        assert_eq!(parse("\na = 1").unwrap_err().line, 2);
+    }
+}
@@ -0,0 +1,323 @@
+//! Rectangular grid of cells, such as the maps drawn in many puzzle inputs.
+//!
+//! Positions are [`Vec2`]s, `x` being the column and `y` the row, counted from the top left corner
+//! as in the puzzle statements. Days where `y` goes up flip the rows when drawing.
+
+use super::vector::{Direction, Direction8, Vec2};
+use crate::days::ParseError;
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+/// Column and row of a cell
+pub type Pos = Vec2<usize>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    /// Row after row
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Grid of `width` columns filled with `value`
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Grid out of its cells, row after row
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Self {
+        assert!(
+            width != 0 && cells.len().is_multiple_of(width) || cells.is_empty(),
+            "{} cells do not make rows of {width}",
+            cells.len()
+        );
+
+        Grid {
+            height: cells.len().checked_div(width).unwrap_or(0),
+            cells,
+            width,
+        }
+    }
+
+    /// Grid with a row per line of `data`, every line being as long as the first one. `cell` turns
+    /// each char into a cell, `None` being an error described by `expected`.
+    pub fn parse(
+        data: &str,
+        expected: &str,
+        cell: impl FnMut(Pos, char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        Self::parse_lines(data, expected, None, cell)
+    }
+
+    /// Same as [`Grid::parse`] for lines of different lengths, the short ones are padded with
+    /// `fill` to the length of the longest one
+    pub fn parse_padded(
+        data: &str,
+        expected: &str,
+        fill: T,
+        cell: impl FnMut(Pos, char) -> Option<T>,
+    ) -> Result<Self, ParseError>
+    where
+        T: Clone,
+    {
+        Self::parse_lines(data, expected, Some(&|| fill.clone()), cell)
+    }
+
+    fn parse_lines(
+        data: &str,
+        expected: &str,
+        fill: Option<&dyn Fn() -> T>,
+        mut cell: impl FnMut(Pos, char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let lines: Vec<&str> = data.trim_end().lines().collect();
+        let line_len = |line: &str| line.chars().count();
+        let width = match fill {
+            Some(_) => lines.iter().map(|line| line_len(line)).max(),
+            None => lines.first().map(|line| line_len(line)),
+        };
+        let width = width.unwrap_or_default();
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            if fill.is_none() && line_len(line) != width {
+                let expected = format!("lines of {width} characters");
+                return Err(ParseError::at(data, line, expected));
+            }
+
+            for (x, (idx, c)) in line.char_indices().enumerate() {
+                let value = cell(Vec2::new(x, y), c).ok_or_else(|| {
+                    ParseError::at(data, &line[idx..idx + c.len_utf8()], expected)
+                })?;
+                cells.push(value);
+            }
+            if let Some(fill) = fill {
+                cells.resize_with(width * (y + 1), fill);
+            }
+        }
+
+        Ok(Grid {
+            cells,
+            width,
+            height: lines.len(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.x + pos.y * self.width])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.x + pos.y * self.width])
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    /// `pos` when it is inside the grid, for coordinates that can go negative
+    pub fn position<U: TryInto<usize>>(&self, pos: Vec2<U>) -> Option<Pos> {
+        pos.try_cast().filter(|&pos| self.contains(pos))
+    }
+
+    /// Position one `step` away from `pos`, when it is inside the grid
+    pub fn step(&self, pos: Pos, step: Vec2<isize>) -> Option<Pos> {
+        let pos = Vec2::new(
+            pos.x.checked_add_signed(step.x)?,
+            pos.y.checked_add_signed(step.y)?,
+        );
+        self.contains(pos).then_some(pos)
+    }
+
+    /// Every cell with its position, row after row
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(idx, cell)| (Vec2::new(idx % width, idx / width), cell))
+    }
+
+    /// Rows from the top one
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        // chunks panics on 0, a grid without columns has no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Cells of column `x` from the top one
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(
+            x < self.width,
+            "Column {x} outside of a grid {} wide",
+            self.width
+        );
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// Cells seen from `from` looking towards `step`, up to the edge, `from` excluded
+    pub fn ray(&self, from: Pos, step: Vec2<isize>) -> impl Iterator<Item = &T> {
+        std::iter::successors(Some(from), move |&pos| self.step(pos, step))
+            .skip(1)
+            .map(|pos| &self[pos])
+    }
+
+    /// Positions of the up to 4 cells sharing a side with `pos`
+    pub fn neighbours_4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(pos, dir.offset()))
+    }
+
+    /// Positions of the up to 8 cells around `pos`
+    pub fn neighbours_8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Direction8::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(pos, dir.offset()))
+    }
+
+    /// Add a row at the bottom
+    pub fn push_row(&mut self, row: impl IntoIterator<Item = T>) {
+        self.cells.extend(row);
+        assert_eq!(
+            self.cells.len(),
+            self.width * (self.height + 1),
+            "Row of a different width than the grid"
+        );
+        self.height += 1;
+    }
+
+    /// Grid of the same size with `f` applied to every cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside of a {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside of a {width}x{height} grid"))
+    }
+}
+
+/// Drawn as in the puzzle statements, each row ending with a newline
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows().filter(|_| self.width > 0) {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+123
+456
+";
+
+    fn digits(data: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(data, "a digit", |_, c| c.to_digit(10))
+    }
+
+    #[test]
+    fn parse() {
+        let grid = digits(EXAMPLE).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Vec2::new(2, 0)], 3);
+        assert_eq!(grid[Vec2::new(0, 1)], 4);
+        assert_eq!(grid.get(Vec2::new(3, 0)), None);
+        assert_eq!(grid.to_string(), EXAMPLE);
+
+        let err = digits("123\n4\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.expected, "lines of 3 characters");
+
+        let err = digits("123\n4a6\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 2, "a"));
+    }
+
+    #[test]
+    fn parse_padded() {
+        let grid = Grid::parse_padded(" #\n#\n", "# or ' '", ' ', |_, c| Some(c)).unwrap();
+
+        assert_eq!(grid.to_string(), " #\n# \n");
+    }
+
+    #[test]
+    fn lines() {
+        let grid = digits(EXAMPLE).unwrap();
+
+        let rows: Vec<_> = grid.rows().collect();
+        assert_eq!(rows, [[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(grid.column(1).rev().collect::<Vec<_>>(), [&5, &2]);
+        assert_eq!(
+            grid.ray(Vec2::new(0, 0), Direction::Right.offset())
+                .collect::<Vec<_>>(),
+            [&2, &3]
+        );
+        assert_eq!(
+            grid.ray(Vec2::new(0, 0), Direction8::DownRight.offset())
+                .collect::<Vec<_>>(),
+            [&5]
+        );
+        assert_eq!(
+            grid.ray(Vec2::new(2, 1), Direction::Right.offset()).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = digits(EXAMPLE).unwrap();
+
+        let mut neighbours: Vec<_> = grid.neighbours_4(Vec2::new(0, 0)).collect();
+        neighbours.sort();
+        assert_eq!(neighbours, [Vec2::new(0, 1), Vec2::new(1, 0)]);
+        assert_eq!(grid.neighbours_4(Vec2::new(1, 1)).count(), 3);
+        assert_eq!(grid.neighbours_8(Vec2::new(1, 1)).count(), 5);
+        assert_eq!(grid.position(Vec2::new(-1, 0)), None);
+        assert_eq!(grid.position(Vec2::new(2i32, 1)), Some(Vec2::new(2, 1)));
+    }
+}
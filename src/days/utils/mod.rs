@@ -0,0 +1,93 @@
+pub mod grid;
+pub mod order_list;
+pub mod rng;
+pub mod search;
+pub mod vector;
+
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the directory holding the `day_XX.txt` puzzle inputs
+pub const DATA_DIR_ENV_VAR: &str = "AOC_DATA_DIR";
+
+/// Where the puzzle input of a day is read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// `day_XX.txt` inside the given directory
+    DataDir(PathBuf),
+    /// A single file, whatever the day
+    File(PathBuf),
+    Stdin,
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        InputSource::DataDir(default_data_dir())
+    }
+}
+
+impl InputSource {
+    pub fn load(&self, day: usize) -> Result<String, InputError> {
+        match self {
+            InputSource::DataDir(data_dir) => load_file(data_dir, day),
+            InputSource::File(path) => read_to_string(path),
+            InputSource::Stdin => {
+                let mut data = String::new();
+                std::io::Read::read_to_string(&mut std::io::stdin(), &mut data).map_err(
+                    |error| InputError {
+                        origin: "stdin".to_string(),
+                        error,
+                    },
+                )?;
+                Ok(data)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputError {
+    pub origin: String,
+    pub error: std::io::Error,
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "could not read input from {}: {}",
+            self.origin, self.error
+        )
+    }
+}
+
+impl std::error::Error for InputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// `AOC_DATA_DIR` if set, otherwise `data` in the current directory and finally the `data`
+/// directory of this crate so that the binary can be run from anywhere in the repo.
+pub fn default_data_dir() -> PathBuf {
+    if let Some(data_dir) = std::env::var_os(DATA_DIR_ENV_VAR) {
+        return data_dir.into();
+    }
+
+    let cwd_data_dir = PathBuf::from("data");
+    if cwd_data_dir.is_dir() {
+        return cwd_data_dir;
+    }
+
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("data")
+}
+
+pub fn load_file(data_dir: &Path, day: usize) -> Result<String, InputError> {
+    read_to_string(&data_dir.join(format!("day_{day:02}.txt")))
+}
+
+fn read_to_string(path: &Path) -> Result<String, InputError> {
+    std::fs::read_to_string(path).map_err(|error| InputError {
+        origin: path.display().to_string(),
+        error,
+    })
+}
@@ -0,0 +1,302 @@
+//! List of the items `0..len` in some order, where an item can be found, taken out and put back at
+//! any position in `O(log len)`, so that moving every item of a long list around is not
+//! quadratic.
+//!
+//! It is an implicit treap: a binary tree of the items in list order, kept balanced by random
+//! priorities, where the position of an item is the number of items left of it in the tree. Each
+//! node knows its parent, so that the position of an item can be worked out from its own node.
+
+use super::rng::Rng;
+use std::cmp::Ordering;
+
+/// No node, for the children of a leaf and the parent of the root
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Node {
+    left: usize,
+    right: usize,
+    parent: usize,
+    /// Higher than the priorities of the nodes below
+    priority: u64,
+    /// Number of nodes in the subtree of this one, itself included
+    size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderList {
+    /// Node of each item
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+impl OrderList {
+    /// Items `0..len` in increasing order
+    pub fn new(len: usize) -> Self {
+        // Seeded so that the shape of the tree, and the time taken, are the same on every run
+        let mut rng = Rng::new(len as u64);
+        let nodes = (0..len)
+            .map(|_| Node {
+                left: NIL,
+                right: NIL,
+                parent: NIL,
+                priority: rng.next_u64(),
+                size: 1,
+            })
+            .collect();
+
+        let mut list = OrderList { nodes, root: NIL };
+        for item in 0..len {
+            list.root = list.merge(list.root, item);
+        }
+        list.detach(list.root);
+        list
+    }
+
+    /// Number of items in the list, without the ones taken out
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root == NIL
+    }
+
+    /// Where `item` is in the list, panics when it was taken out
+    pub fn position(&self, item: usize) -> usize {
+        assert!(self.is_in_list(item), "{item} is not in the list");
+
+        let mut position = self.size(self.nodes[item].left);
+        let mut node = item;
+        while self.nodes[node].parent != NIL {
+            let parent = self.nodes[node].parent;
+            if self.nodes[parent].right == node {
+                position += self.size(self.nodes[parent].left) + 1;
+            }
+            node = parent;
+        }
+        position
+    }
+
+    /// Item at `position`, panics when there are not that many items
+    pub fn get(&self, position: usize) -> usize {
+        assert!(position < self.len(), "No item at {position}");
+
+        // Position in the subtree of `node`
+        let (mut node, mut offset) = (self.root, position);
+        loop {
+            let left_size = self.size(self.nodes[node].left);
+            match offset.cmp(&left_size) {
+                Ordering::Less => node = self.nodes[node].left,
+                Ordering::Equal => return node,
+                Ordering::Greater => {
+                    offset -= left_size + 1;
+                    node = self.nodes[node].right;
+                }
+            }
+        }
+    }
+
+    /// Take out `item`, the items after it move up by one
+    pub fn remove(&mut self, item: usize) {
+        assert!(self.is_in_list(item), "{item} is not in the list");
+
+        // The children of the item take its place, merged into one tree
+        let Node {
+            left,
+            right,
+            parent,
+            ..
+        } = self.nodes[item];
+        let children = self.merge(left, right);
+        self.replace_child(parent, item, children);
+
+        let mut node = parent;
+        while node != NIL {
+            self.nodes[node].size -= 1;
+            node = self.nodes[node].parent;
+        }
+
+        let node = &mut self.nodes[item];
+        (node.left, node.right, node.parent, node.size) = (NIL, NIL, NIL, 1);
+    }
+
+    /// Take out the item at `position`, the items after it move up by one
+    pub fn remove_at(&mut self, position: usize) -> usize {
+        let item = self.get(position);
+        self.remove(item);
+        item
+    }
+
+    /// Put back `item`, taken out before, so that it ends up at `position`
+    pub fn insert_at(&mut self, position: usize, item: usize) {
+        assert!(!self.is_in_list(item), "{item} is already in the list");
+        assert!(position <= self.len(), "No position {position}");
+
+        // Down to where the item goes in the tree, below the nodes of higher priority, the
+        // subtree there being split around it
+        let priority = self.nodes[item].priority;
+        let (mut parent, mut node, mut offset) = (NIL, self.root, position);
+        let mut left_of_parent = false;
+        while node != NIL && self.nodes[node].priority > priority {
+            self.nodes[node].size += 1;
+            parent = node;
+
+            let left_size = self.size(self.nodes[node].left);
+            left_of_parent = offset <= left_size;
+            if left_of_parent {
+                node = self.nodes[node].left;
+            } else {
+                offset -= left_size + 1;
+                node = self.nodes[node].right;
+            }
+        }
+
+        let (before, after) = self.split(node, offset);
+        self.set_children(item, before, after);
+
+        self.nodes[item].parent = parent;
+        match parent {
+            NIL => self.root = item,
+            _ if left_of_parent => self.nodes[parent].left = item,
+            _ => self.nodes[parent].right = item,
+        }
+    }
+
+    /// Items in list order
+    pub fn items(&self) -> Vec<usize> {
+        let mut items = Vec::with_capacity(self.len());
+        let mut stack = Vec::new();
+        let mut node = self.root;
+        while node != NIL || !stack.is_empty() {
+            while node != NIL {
+                stack.push(node);
+                node = self.nodes[node].left;
+            }
+            let next = stack.pop().unwrap();
+            items.push(next);
+            node = self.nodes[next].right;
+        }
+        items
+    }
+
+    fn is_in_list(&self, item: usize) -> bool {
+        item == self.root || self.nodes[item].parent != NIL
+    }
+
+    fn size(&self, node: usize) -> usize {
+        match node {
+            NIL => 0,
+            _ => self.nodes[node].size,
+        }
+    }
+
+    fn detach(&mut self, node: usize) {
+        if node != NIL {
+            self.nodes[node].parent = NIL;
+        }
+    }
+
+    /// Put `new_child` where `child` was below `parent`, at the root without a parent
+    fn replace_child(&mut self, parent: usize, child: usize, new_child: usize) {
+        if parent == NIL {
+            self.root = new_child;
+        } else if self.nodes[parent].left == child {
+            self.nodes[parent].left = new_child;
+        } else {
+            self.nodes[parent].right = new_child;
+        }
+
+        if new_child != NIL {
+            self.nodes[new_child].parent = parent;
+        }
+    }
+
+    fn set_children(&mut self, node: usize, left: usize, right: usize) {
+        for child in [left, right] {
+            if child != NIL {
+                self.nodes[child].parent = node;
+            }
+        }
+        self.nodes[node].left = left;
+        self.nodes[node].right = right;
+        self.nodes[node].size = self.size(left) + self.size(right) + 1;
+    }
+
+    /// Subtree of `node` cut into its first `count` items and the others. The parents of the two
+    /// trees are left as they were, for the caller to set.
+    fn split(&mut self, node: usize, count: usize) -> (usize, usize) {
+        if node == NIL {
+            return (NIL, NIL);
+        }
+
+        let Node { left, right, .. } = self.nodes[node];
+        let left_size = self.size(left);
+        if count <= left_size {
+            let (before, after) = self.split(left, count);
+            self.set_children(node, after, right);
+            (before, node)
+        } else {
+            let (before, after) = self.split(right, count - left_size - 1);
+            self.set_children(node, left, before);
+            (node, after)
+        }
+    }
+
+    /// Tree of the items of `first` followed by the ones of `second`
+    fn merge(&mut self, first: usize, second: usize) -> usize {
+        if first == NIL {
+            return second;
+        }
+        if second == NIL {
+            return first;
+        }
+
+        if self.nodes[first].priority > self.nodes[second].priority {
+            let Node { left, right, .. } = self.nodes[first];
+            let right = self.merge(right, second);
+            self.set_children(first, left, right);
+            first
+        } else {
+            let Node { left, right, .. } = self.nodes[second];
+            let left = self.merge(first, left);
+            self.set_children(second, left, right);
+            second
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves() {
+        let mut list = OrderList::new(200);
+        let mut expected: Vec<usize> = (0..200).collect();
+        assert_eq!(list.items(), expected);
+
+        let mut rng = Rng::new(3);
+        for _ in 0..1000 {
+            let from = rng.range(0..expected.len());
+            let item = expected.remove(from);
+            assert_eq!(list.position(item), from);
+            match rng.chance(0.5) {
+                true => assert_eq!(list.remove_at(from), item),
+                false => list.remove(item),
+            }
+            assert_eq!(list.len(), expected.len());
+
+            // Anywhere, the end included
+            let to = rng.range(0..=expected.len());
+            expected.insert(to, item);
+            list.insert_at(to, item);
+            assert_eq!(list.get(to), item);
+        }
+
+        assert_eq!(list.items(), expected);
+        for (position, &item) in expected.iter().enumerate() {
+            assert_eq!(list.position(item), position);
+        }
+    }
+}
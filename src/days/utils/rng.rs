@@ -0,0 +1,119 @@
+//! Small seeded random number generator for the input generators, the same seed always gives the
+//! same input. SplitMix64, which is plenty for making up puzzle inputs but not for anything that
+//! needs real randomness.
+
+use std::ops::{Bound, RangeBounds};
+
+/// Integer that [`Rng::range`] can pick
+pub trait RandomInt: Copy {
+    fn to_i128(self) -> i128;
+    fn from_i128(value: i128) -> Self;
+}
+
+macro_rules! impl_random_int {
+    ($($t:ty),*) => {
+        $(
+            impl RandomInt for $t {
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+
+                fn from_i128(value: i128) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_random_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound`, panics when `bound` is 0
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "Empty range");
+        // Multiply and keep the high bits, the bias is negligible for the bounds used here
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// Uniform in `range`, panics when it is empty
+    pub fn range<T: RandomInt>(&mut self, range: impl RangeBounds<T>) -> T {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start.to_i128(),
+            Bound::Excluded(&start) => start.to_i128() + 1,
+            Bound::Unbounded => panic!("Unbounded range"),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.to_i128() + 1,
+            Bound::Excluded(&end) => end.to_i128(),
+            Bound::Unbounded => panic!("Unbounded range"),
+        };
+        assert!(start < end, "Empty range {start}..{end}");
+
+        T::from_i128(start + self.below((end - start) as u64) as i128)
+    }
+
+    /// `true` with the probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        unit < p
+    }
+
+    /// Uniformly picked item, panics when `items` is empty
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx as u64 + 1) as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded() {
+        let numbers = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..10).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+        assert_eq!(numbers(7), numbers(7));
+        assert_ne!(numbers(7), numbers(8));
+
+        let mut rng = Rng::new(0);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let value: i32 = rng.range(-2..=3);
+            seen[(value + 2) as usize] = true;
+        }
+        assert_eq!(seen, [true; 6]);
+        assert_eq!(rng.range(5u8..6), 5);
+
+        let mut items: Vec<_> = (0..20).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
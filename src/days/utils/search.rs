@@ -0,0 +1,277 @@
+//! Shortest path searches over graphs given by a `neighbours` callback, so that a grid, a set of
+//! positions or the tunnels between valves can be searched without building a graph first.
+//!
+//! Every search starts from any number of nodes at once, finding the path from the closest one,
+//! and stops at the first node `is_goal` accepts.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Nodes from a start to a goal, both included, with the cost of going through them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    pub nodes: Vec<N>,
+    pub cost: C,
+}
+
+/// Path with the fewest steps, breadth first
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut came_from: HashMap<N, Option<N>> = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if came_from.insert(start.clone(), None).is_none() {
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((node, steps)) = queue.pop_front() {
+        if is_goal(&node) {
+            let nodes = reconstruct_path(node, |node| came_from[node].clone());
+            return Some(Path { nodes, cost: steps });
+        }
+
+        for next in neighbours(&node) {
+            if let Entry::Vacant(entry) = came_from.entry(next.clone()) {
+                entry.insert(Some(node.clone()));
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// Number of steps to every node reachable from `starts`, breadth first
+pub fn distances<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let steps = distances[&node];
+        for next in neighbours(&node) {
+            if let Entry::Vacant(entry) = distances.entry(next.clone()) {
+                entry.insert(steps + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Cheapest path, `neighbours` gives the cost of moving to each neighbour
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, neighbours, |_| C::default(), is_goal)
+}
+
+/// Cheapest path, trying first the nodes `heuristic` estimates closer to a goal. The heuristic must
+/// never overestimate the remaining cost, otherwise the path found may not be the cheapest.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    // Cheapest known cost to each node and where it was reached from
+    let mut best: HashMap<N, (C, Option<N>)> = HashMap::new();
+    let mut open_set = BinaryHeap::new();
+
+    for start in starts {
+        best.insert(start.clone(), (C::default(), None));
+        open_set.push(Open {
+            estimate: heuristic(&start),
+            cost: C::default(),
+            node: start,
+        });
+    }
+
+    while let Some(Open { cost, node, .. }) = open_set.pop() {
+        // Reached more cheaply since it was pushed, it has already been expanded
+        if best[&node].0 < cost {
+            continue;
+        }
+
+        if is_goal(&node) {
+            let nodes = reconstruct_path(node, |node| best[node].1.clone());
+            return Some(Path { nodes, cost });
+        }
+
+        for (next, step_cost) in neighbours(&node) {
+            let next_cost = cost + step_cost;
+            if best
+                .get(&next)
+                .is_some_and(|&(known_cost, _)| known_cost <= next_cost)
+            {
+                continue;
+            }
+
+            best.insert(next.clone(), (next_cost, Some(node.clone())));
+            open_set.push(Open {
+                estimate: next_cost + heuristic(&next),
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+
+    None
+}
+
+/// Node of the open set, the one with the lowest estimate comes out of the heap first
+struct Open<N, C> {
+    /// Cost to the node plus the heuristic
+    estimate: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Open<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<N, C: Ord> Eq for Open<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Open<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Open<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max heap
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+/// Path ending at `goal`, following `came_from` back to a start
+fn reconstruct_path<N>(goal: N, mut came_from: impl FnMut(&N) -> Option<N>) -> Vec<N> {
+    let mut path = vec![goal];
+    while let Some(previous) = came_from(path.last().unwrap()) {
+        path.push(previous);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::utils::grid::{Grid, Pos};
+    use crate::days::utils::vector::Vec2;
+
+    const MAZE: &str = "\
+S....
+.###.
+...9E
+";
+
+    fn maze() -> Grid<char> {
+        Grid::parse(MAZE, "a tile", |_, c| Some(c)).unwrap()
+    }
+
+    fn open_neighbours(maze: &Grid<char>, pos: &Pos) -> Vec<Pos> {
+        maze.neighbours_4(*pos)
+            .filter(|&next| maze[next] != '#')
+            .collect()
+    }
+
+    #[test]
+    fn breadth_first() {
+        let maze = maze();
+        let goal = Vec2::new(4, 2);
+
+        let path = bfs(
+            [Vec2::new(0, 0)],
+            |pos| open_neighbours(&maze, pos),
+            |&pos| pos == goal,
+        )
+        .unwrap();
+        assert_eq!(path.cost, 6);
+        assert_eq!(path.nodes.len(), 7);
+        assert_eq!(path.nodes.first(), Some(&Vec2::new(0, 0)));
+        assert_eq!(path.nodes.last(), Some(&goal));
+
+        // The closest start wins
+        let path = bfs(
+            [Vec2::new(0, 0), Vec2::new(4, 0)],
+            |pos| open_neighbours(&maze, pos),
+            |&pos| pos == goal,
+        )
+        .unwrap();
+        assert_eq!(path.cost, 2);
+
+        assert_eq!(bfs([Vec2::new(0, 0)], |_| [], |_| false), None);
+
+        let distances = distances([Vec2::new(0, 0)], |pos| open_neighbours(&maze, pos));
+        assert_eq!(distances[&goal], 6);
+        assert_eq!(distances.len(), 12);
+    }
+
+    #[test]
+    fn weighted() {
+        let maze = maze();
+        let goal = Vec2::new(4, 2);
+        // Going through the 9 costs 9, the other tiles cost 1
+        let weighted_neighbours = |pos: &Pos| {
+            open_neighbours(&maze, pos)
+                .into_iter()
+                .map(|next| (next, maze[next].to_digit(10).unwrap_or(1)))
+                .collect::<Vec<_>>()
+        };
+
+        let path = dijkstra([Vec2::new(0, 0)], weighted_neighbours, |&pos| pos == goal).unwrap();
+        assert_eq!(path.cost, 6);
+        assert!(!path.nodes.contains(&Vec2::new(3, 2)));
+
+        let path = astar(
+            [Vec2::new(0, 0)],
+            weighted_neighbours,
+            |pos| pos.manhattan(goal) as u32,
+            |&pos| pos == goal,
+        )
+        .unwrap();
+        assert_eq!(path.cost, 6);
+    }
+}
@@ -0,0 +1,369 @@
+//! Positions and moves on a plane or in space, with the distances and directions the puzzles use.
+//!
+//! On a plane `y` grows downwards as in the puzzle drawings: [`Direction::Up`] is `(0, -1)` and
+//! turning right goes clockwise on screen. Days where `y` goes up only have to swap up and down.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Integer type usable as a coordinate
+pub trait Coord:
+    Copy
+    + Debug
+    + Default
+    + Ord
+    + Hash
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Absolute difference, which does not overflow for unsigned types
+    fn distance(self, other: Self) -> Self;
+}
+
+/// Coordinate that can go negative, needed for directions
+pub trait SignedCoord: Coord + Neg<Output = Self> {
+    fn signum(self) -> Self;
+}
+
+macro_rules! impl_coord {
+    ($($unsigned:ty),* ; $($signed:ty),*) => {
+        $(
+            impl Coord for $unsigned {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn distance(self, other: Self) -> Self {
+                    self.abs_diff(other)
+                }
+            }
+        )*
+        $(
+            impl Coord for $signed {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn distance(self, other: Self) -> Self {
+                    (self - other).abs()
+                }
+            }
+
+            impl SignedCoord for $signed {
+                fn signum(self) -> Self {
+                    self.signum()
+                }
+            }
+        )*
+    };
+}
+
+impl_coord!(u8, u16, u32, u64, usize; i8, i16, i32, i64, isize);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Vec2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Vec2 { x, y }
+    }
+
+    /// Same vector with another coordinate type, `None` when a coordinate does not fit
+    pub fn try_cast<U>(self) -> Option<Vec2<U>>
+    where
+        T: TryInto<U>,
+    {
+        Some(Vec2::new(self.x.try_into().ok()?, self.y.try_into().ok()?))
+    }
+}
+
+impl<T: Coord> Vec2<T> {
+    pub fn manhattan(self, other: Self) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y)
+    }
+
+    /// Number of king moves between the two positions, 1 when they touch, diagonally included
+    pub fn chebyshev(self, other: Self) -> T {
+        self.x.distance(other.x).max(self.y.distance(other.y))
+    }
+}
+
+impl<T: SignedCoord> Vec2<T> {
+    /// Each coordinate replaced by its sign, a single step towards the vector direction
+    pub fn signum(self) -> Self {
+        Vec2::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Quarter turn clockwise on screen
+    pub fn rotate_right(self) -> Self {
+        Vec2::new(-self.y, self.x)
+    }
+
+    /// Quarter turn counterclockwise on screen
+    pub fn rotate_left(self) -> Self {
+        Vec2::new(self.y, -self.x)
+    }
+
+    /// The 4 positions sharing a side with this one
+    pub fn neighbours_4(self) -> impl Iterator<Item = Self> {
+        Direction::ALL
+            .into_iter()
+            .map(move |dir| self + dir.offset())
+    }
+
+    /// The 8 positions around this one
+    pub fn neighbours_8(self) -> impl Iterator<Item = Self> {
+        Direction8::ALL
+            .into_iter()
+            .map(move |dir| self + dir.offset())
+    }
+}
+
+impl<T> Vec3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
+impl<T: Coord> Vec3<T> {
+    pub fn manhattan(self, other: Self) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y) + self.z.distance(other.z)
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        self.x
+            .distance(other.x)
+            .max(self.y.distance(other.y))
+            .max(self.z.distance(other.z))
+    }
+
+    pub fn dot(self, other: Self) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+}
+
+impl<T: SignedCoord> Vec3<T> {
+    /// The 6 positions sharing a face with this one
+    pub fn neighbours_6(self) -> impl Iterator<Item = Self> {
+        let (zero, one) = (T::ZERO, T::ONE);
+        [
+            Vec3::new(one, zero, zero),
+            Vec3::new(-one, zero, zero),
+            Vec3::new(zero, one, zero),
+            Vec3::new(zero, -one, zero),
+            Vec3::new(zero, zero, one),
+            Vec3::new(zero, zero, -one),
+        ]
+        .into_iter()
+        .map(move |offset| self + offset)
+    }
+}
+
+macro_rules! impl_ops {
+    ($vec:ident, $($field:ident),*) => {
+        impl<T: Coord> Add for $vec<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                $vec { $($field: self.$field + other.$field),* }
+            }
+        }
+
+        impl<T: Coord> Sub for $vec<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                $vec { $($field: self.$field - other.$field),* }
+            }
+        }
+
+        impl<T: Coord> AddAssign for $vec<T> {
+            fn add_assign(&mut self, other: Self) {
+                $(self.$field += other.$field;)*
+            }
+        }
+
+        impl<T: Coord> SubAssign for $vec<T> {
+            fn sub_assign(&mut self, other: Self) {
+                $(self.$field -= other.$field;)*
+            }
+        }
+
+        impl<T: SignedCoord> Neg for $vec<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $vec { $($field: -self.$field),* }
+            }
+        }
+
+        /// Scaling
+        impl<T: Coord> Mul<T> for $vec<T> {
+            type Output = Self;
+
+            fn mul(self, factor: T) -> Self {
+                $vec { $($field: self.$field * factor),* }
+            }
+        }
+    };
+}
+
+impl_ops!(Vec2, x, y);
+impl_ops!(Vec3, x, y, z);
+
+/// The 4 directions along the axes, clockwise from `Right` so that they match the facing values
+/// of day 22
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+        Direction::Up,
+    ];
+
+    /// Unit vector pointing this way
+    pub fn offset<T: SignedCoord>(self) -> Vec2<T> {
+        let (zero, one) = (T::ZERO, T::ONE);
+        match self {
+            Direction::Right => Vec2::new(one, zero),
+            Direction::Down => Vec2::new(zero, one),
+            Direction::Left => Vec2::new(-one, zero),
+            Direction::Up => Vec2::new(zero, -one),
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+}
+
+/// The 8 directions of a compass, clockwise from `Up`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    pub const ALL: [Direction8; 8] = [
+        Direction8::Up,
+        Direction8::UpRight,
+        Direction8::Right,
+        Direction8::DownRight,
+        Direction8::Down,
+        Direction8::DownLeft,
+        Direction8::Left,
+        Direction8::UpLeft,
+    ];
+
+    /// Vector to the neighbouring position this way, diagonals move on both axes
+    pub fn offset<T: SignedCoord>(self) -> Vec2<T> {
+        let (zero, one) = (T::ZERO, T::ONE);
+        let (x, y) = match self {
+            Direction8::Up => (zero, -one),
+            Direction8::UpRight => (one, -one),
+            Direction8::Right => (one, zero),
+            Direction8::DownRight => (one, one),
+            Direction8::Down => (zero, one),
+            Direction8::DownLeft => (-one, one),
+            Direction8::Left => (-one, zero),
+            Direction8::UpLeft => (-one, -one),
+        };
+        Vec2::new(x, y)
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Right => Direction8::Right,
+            Direction::Down => Direction8::Down,
+            Direction::Left => Direction8::Left,
+            Direction::Up => Direction8::Up,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        let a = Vec2::new(1u32, 5);
+        let b = Vec2::new(4, 1);
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+
+        let a = Vec3::new(-1i64, 0, 2);
+        assert_eq!(a.manhattan(Vec3::default()), 3);
+        assert_eq!(a.chebyshev(Vec3::default()), 2);
+        assert_eq!(a.dot(Vec3::new(3, 5, 2)), 1);
+    }
+
+    #[test]
+    fn ops() {
+        let a = Vec2::new(3i32, -2);
+        assert_eq!(a + Vec2::new(1, 1), Vec2::new(4, -1));
+        assert_eq!(a - Vec2::new(1, 1), Vec2::new(2, -3));
+        assert_eq!(-a, Vec2::new(-3, 2));
+        assert_eq!(a * 2, Vec2::new(6, -4));
+        assert_eq!(a.signum(), Vec2::new(1, -1));
+        assert_eq!(a.try_cast::<u32>(), None);
+        assert_eq!(Vec2::new(3i32, 2).try_cast(), Some(Vec2::new(3u32, 2)));
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Right.turn_left(), Direction::Up);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+
+        for dir in Direction::ALL {
+            let offset: Vec2<i32> = dir.offset();
+            assert_eq!(offset.rotate_right(), dir.turn_right().offset());
+            assert_eq!(offset.rotate_left(), dir.turn_left().offset());
+            assert_eq!(Direction8::from(dir).offset(), offset);
+        }
+
+        assert_eq!(Vec2::new(0i64, 0).neighbours_8().count(), 8);
+        assert_eq!(Vec3::new(0i64, 0, 0).neighbours_6().count(), 6);
+    }
+}
@@ -0,0 +1,236 @@
+//! `--format json` output, one object per line and day:
+//!
+//! ```json
+//! {"day":10,"title":"Cathode-Ray Tube","parse_ns":12000,"total_ns":52000,"parts":[
+//!   {"part":1,"status":"solved","answer":{"type":"number","value":13140},"time_ns":20000},
+//!   {"part":2,"status":"solved","answer":{"type":"multiline","value":"##..\n"},"time_ns":20000}
+//! ]}
+//! ```
+//!
+//! Parts left out by `--part` are not listed, the others are `solved` with a `number`, `string` or
+//! `multiline` answer, `unimplemented` or `failed` with a `message`. A day that could not be run
+//! has an `error` of kind `input` or `parse`, the latter with its `line` and `column`, instead of
+//! the timings and parts. With `--check` each part also gets a `check` of `pass`, `fail` along with
+//! the `expected` answer, or `missing`. Built with the `alloc-stats` feature, the day has a
+//! `parse_memory` and each part a `memory` object with the number of `allocations`, the `bytes`
+//! they requested and the `peak_bytes` live at once.
+
+use crate::report::{DayError, DayRow};
+use aoc_2022::days;
+use aoc_2022::days::alloc::AllocStats;
+use aoc_2022::days::answers::{CheckStatus, KnownAnswers};
+use aoc_2022::days::{Answer, PartOutcome, PartReport, PartSelection};
+use std::time::Duration;
+
+/// Print the object of each day, returns whether all the recorded answers matched
+pub fn print_days(
+    rows: &[DayRow],
+    parts: PartSelection,
+    known_answers: Option<&KnownAnswers>,
+) -> bool {
+    let mut all_passed = true;
+
+    for row in rows {
+        let (line, passed) = day_object(row, parts, known_answers);
+        all_passed &= passed;
+        println!("{line}");
+    }
+
+    all_passed
+}
+
+fn day_object(
+    row: &DayRow,
+    parts: PartSelection,
+    known_answers: Option<&KnownAnswers>,
+) -> (String, bool) {
+    let entry = days::find_day(row.day).expect("rows are only made for registered days");
+    let mut fields = vec![("day", row.day.to_string()), ("title", string(entry.title))];
+
+    let report = match &row.report {
+        Ok(report) => report,
+        Err(err) => {
+            let mut error = vec![];
+            match err {
+                DayError::Input(_) => error.push(("kind", string("input"))),
+                DayError::Parse(parse_error) => {
+                    error.push(("kind", string("parse")));
+                    error.push(("line", parse_error.line.to_string()));
+                    error.push(("column", parse_error.column.to_string()));
+                }
+            }
+            error.push(("message", string(&err.to_string())));
+            fields.push(("error", object(&error)));
+
+            // Same as the text output, recorded answers of a day that did not run are failures
+            let passed = known_answers.is_none_or(|known_answers| {
+                (1..=entry.parts)
+                    .filter(|part| parts.includes(*part))
+                    .all(|part| known_answers.get(row.day, part).is_none())
+            });
+            return (object(&fields), passed);
+        }
+    };
+
+    let mut passed = true;
+    let parts = report.parts[..entry.parts]
+        .iter()
+        .enumerate()
+        .filter(|(_, part)| part.outcome != PartOutcome::Skipped)
+        .map(|(part_idx, part)| {
+            let mut part_fields = part_fields(part_idx + 1, part);
+            let status = known_answers.and_then(|known_answers| {
+                known_answers.check(row.day, part_idx + 1, &part.outcome)
+            });
+
+            match status {
+                Some(CheckStatus::Pass) => part_fields.push(("check", string("pass"))),
+                Some(CheckStatus::Missing) => part_fields.push(("check", string("missing"))),
+                Some(CheckStatus::Fail { expected, .. }) => {
+                    passed = false;
+                    part_fields.push(("check", string("fail")));
+                    part_fields.push(("expected", answer(&expected)));
+                }
+                None => (),
+            }
+
+            object(&part_fields)
+        })
+        .collect::<Vec<_>>();
+
+    fields.push(("parse_ns", nanos(report.parse_time)));
+    if let Some(allocs) = &report.parse_allocs {
+        fields.push(("parse_memory", memory(allocs)));
+    }
+    fields.push(("total_ns", nanos(report.total_time())));
+    fields.push(("parts", format!("[{}]", parts.join(","))));
+
+    (object(&fields), passed)
+}
+
+fn part_fields(part_nb: usize, part: &PartReport) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("part", part_nb.to_string())];
+
+    match &part.outcome {
+        PartOutcome::Solved(solved) => {
+            fields.push(("status", string("solved")));
+            fields.push(("answer", answer(solved)));
+        }
+        PartOutcome::Unimplemented => fields.push(("status", string("unimplemented"))),
+        PartOutcome::Failed(message) => {
+            fields.push(("status", string("failed")));
+            fields.push(("message", string(message)));
+        }
+        PartOutcome::Skipped => fields.push(("status", string("skipped"))),
+    }
+    fields.push(("time_ns", nanos(part.time)));
+    if let Some(allocs) = &part.allocs {
+        fields.push(("memory", memory(allocs)));
+    }
+
+    fields
+}
+
+fn answer(answer: &Answer) -> String {
+    let (kind, value) = match answer {
+        Answer::Number(number) => ("number", number.to_string()),
+        Answer::Text(text) if answer.is_multiline() => ("multiline", string(text)),
+        Answer::Text(text) => ("string", string(text)),
+        Answer::Empty => ("empty", "null".to_string()),
+    };
+
+    object(&[("type", string(kind)), ("value", value)])
+}
+
+fn memory(allocs: &AllocStats) -> String {
+    object(&[
+        ("allocations", allocs.allocations.to_string()),
+        ("bytes", allocs.bytes.to_string()),
+        ("peak_bytes", allocs.peak_bytes.to_string()),
+    ])
+}
+
+fn nanos(duration: Duration) -> String {
+    duration.as_nanos().to_string()
+}
+
+/// Object from fields whose values are already JSON
+fn object(fields: &[(&str, String)]) -> String {
+    let fields = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{value}", string(key)))
+        .collect::<Vec<_>>();
+
+    format!("{{{}}}", fields.join(","))
+}
+
+fn string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c.is_control() => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+
+    res.push('"');
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_2022::days::DayReport;
+
+    #[test]
+    fn escape() {
+        assert_eq!(string("a\"b\\c\n\u{1}"), r#""a\"b\\c\n\u0001""#);
+    }
+
+    #[test]
+    fn day() {
+        let part = |outcome| PartReport {
+            outcome,
+            time: Duration::from_nanos(5),
+            allocs: None,
+        };
+        let row = DayRow {
+            day: 10,
+            report: Ok(DayReport {
+                parse_time: Duration::from_nanos(2),
+                parse_allocs: None,
+                parts: [
+                    part(PartOutcome::Solved(Answer::Number(13140))),
+                    part(PartOutcome::Solved(Answer::from("#.\n.#\n"))),
+                ],
+            }),
+        };
+
+        let mut known_answers = KnownAnswers::default();
+        known_answers.insert(10, 1, Answer::Number(13140));
+        known_answers.insert(10, 2, Answer::from("..\n..\n"));
+
+        assert_eq!(
+            day_object(&row, PartSelection::Both, Some(&known_answers)),
+            (
+                [
+                    r#"{"day":10,"title":"Cathode-Ray Tube","parse_ns":2,"total_ns":12,"parts":["#,
+                    r#"{"part":1,"status":"solved","answer":{"type":"number","value":13140},"#,
+                    r#""time_ns":5,"check":"pass"},"#,
+                    r#"{"part":2,"status":"solved","answer":{"type":"multiline","value":"#,
+                    r##""#.\n.#\n"},"time_ns":5,"check":"fail","##,
+                    r#""expected":{"type":"multiline","value":"..\n..\n"}}]}"#,
+                ]
+                .concat(),
+                false
+            )
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Advent of Code 2022 solutions, each day lives in its own module under [`days`] with its parser,
+//! domain types and solver functions, [`Solution`] gives a uniform way to run any of them.
+
+// Puzzle statements are pasted as is in the doc comments, they are not markdown
+#![allow(clippy::doc_lazy_continuation, rustdoc::broken_intra_doc_links)]
+
+pub mod days;
+
+pub use days::{Answer, ParseError, Solution, Solver};
+
+/// Counts the allocations of each day and part, see [`days::alloc`]
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: days::alloc::CountingAllocator = days::alloc::CountingAllocator;
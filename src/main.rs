@@ -1,45 +1,308 @@
-mod days;
-
-const DAY_FUNCS: [fn() -> (); 25] = [
-    days::day_01::day_01,
-    days::day_02::day_02,
-    days::day_03::day_03,
-    days::day_04::day_04,
-    days::day_05::day_05,
-    days::day_06::day_06,
-    days::day_07::day_07,
-    days::day_08::day_08,
-    days::day_09::day_09,
-    days::day_10::day_10,
-    days::day_11::day_11,
-    days::day_12::day_12,
-    days::day_13::day_13,
-    days::day_14::day_14,
-    days::day_15::day_15,
-    days::day_16::day_16,
-    days::day_17::day_17,
-    days::day_18::day_18,
-    days::day_19::day_19,
-    days::day_20::day_20,
-    days::day_21::day_21,
-    days::day_22::day_22,
-    days::day_23::day_23,
-    days::day_24::day_24,
-    days::day_25::day_25,
-];
+use aoc_2022::days;
+use aoc_2022::days::answers::KnownAnswers;
+use aoc_2022::days::bench::Baseline;
+use aoc_2022::days::log;
+use aoc_2022::days::params::ParamsConfig;
+use aoc_2022::days::{DayEntry, InputSource, RunOptions};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+mod bench;
+mod cli;
+mod json;
+mod report;
+
+/// Input could not be read or answers did not match
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_PARSE_ERROR: i32 = 3;
 
 fn main() {
-    let day_arg: usize = std::env::args()
-        .skip(1)
-        .take(1)
-        .next()
-        .expect("Expected the number of the day as first argument")
-        .parse()
-        .expect("Expected an integer.");
-
-    let day_func = DAY_FUNCS
-        .get(day_arg - 1)
-        .unwrap_or_else(|| panic!("Day argument out of range, max: {}", DAY_FUNCS.len()));
-
-    day_func()
+    let args = cli::Args::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{err}\n\n{}", cli::USAGE);
+        std::process::exit(EXIT_USAGE);
+    });
+
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    if args.command == cli::Command::List {
+        for entry in days::DAYS {
+            let parts = if entry.parts == 1 { " (1 part)" } else { "" };
+            println!("{:02}  {}{parts}", entry.day, entry.title);
+            for param in entry.params {
+                println!("      {} = {}, {}", param.name, param.default, param.doc);
+            }
+        }
+        return;
+    }
+
+    let selected: Vec<&DayEntry> = days::DAYS
+        .iter()
+        .filter(|entry| args.days.contains(entry.day))
+        .collect();
+    if selected.is_empty() {
+        eprintln!("No solution for the selected days, see `aoc_2022 list`");
+        std::process::exit(EXIT_USAGE);
+    }
+
+    if let Some(day) = args
+        .trace_days
+        .iter()
+        .find(|day| days::find_day(**day).is_none())
+    {
+        eprintln!("No solution for day {day} to trace, see `aoc_2022 list`");
+        std::process::exit(EXIT_USAGE);
+    }
+    log::set_level(match args.verbosity {
+        0 => log::Level::Off,
+        1 => log::Level::Debug,
+        _ => log::Level::Trace,
+    });
+    log::set_days(&args.trace_days);
+
+    let entries = day_runs(&selected, &args).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(EXIT_USAGE);
+    });
+
+    // Parts still to write are reported as such, no need for the whole panic message
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if !info
+            .payload_as_str()
+            .is_some_and(days::solution::is_todo_message)
+        {
+            default_hook(info);
+        }
+    }));
+
+    let input_source = args.input_source();
+
+    if args.command == cli::Command::Generate {
+        let (entry, options) = &entries[0];
+        match entry.solver.generate(args.seed, args.size, &options.params) {
+            Some(data) => print!("{data}"),
+            None => {
+                eprintln!("No input generator for day {}", entry.day);
+                std::process::exit(EXIT_USAGE);
+            }
+        }
+        return;
+    }
+
+    if args.command == cli::Command::Bench {
+        run_bench(&entries, &input_source, &args);
+        return;
+    }
+
+    let known_answers = args.check.then(|| {
+        KnownAnswers::load(&args.answers_path()).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(EXIT_FAILURE);
+        })
+    });
+
+    if args.format == cli::Format::Json {
+        let rows = run_days(&entries, &input_source, args.jobs);
+        let all_passed = json::print_days(&rows, args.parts, known_answers.as_ref());
+
+        if rows
+            .iter()
+            .any(|row| matches!(row.report, Err(report::DayError::Parse(_))))
+        {
+            std::process::exit(EXIT_PARSE_ERROR);
+        }
+        if !all_passed || input_failed(&rows) {
+            std::process::exit(EXIT_FAILURE);
+        }
+        return;
+    }
+
+    if let Some(known_answers) = known_answers {
+        let rows = run_days(&entries, &input_source, args.jobs);
+        let all_passed = report::print_check(&rows, args.parts, &known_answers);
+        if !all_passed || input_failed(&rows) {
+            std::process::exit(EXIT_FAILURE);
+        }
+        return;
+    }
+
+    if let cli::DaySelection::Single(day) = args.days {
+        let (entry, options) = &entries[0];
+        let data = input_source.load(day).unwrap_or_else(|err| {
+            eprintln!("Day {day}: {err}");
+            std::process::exit(EXIT_FAILURE);
+        });
+        match entry.solver.run(&data, options) {
+            Ok(day_report) => {
+                report::print_answers(&day_report);
+                report::print_memory(&[report::DayRow {
+                    day,
+                    report: Ok(day_report),
+                }]);
+            }
+            Err(err) => {
+                eprintln!("{}", report::parse_diagnostic(&err, &data));
+                std::process::exit(EXIT_PARSE_ERROR);
+            }
+        }
+        return;
+    }
+
+    let rows = run_days(&entries, &input_source, args.jobs);
+    report::print_table(&rows);
+    report::print_memory(&rows);
+
+    let parse_failed = rows
+        .iter()
+        .any(|row| matches!(row.report, Err(report::DayError::Parse(_))));
+    if parse_failed {
+        std::process::exit(EXIT_PARSE_ERROR);
+    }
+    if input_failed(&rows) {
+        std::process::exit(EXIT_FAILURE);
+    }
+}
+
+/// Whether the input of one of the days could not be read
+fn input_failed(rows: &[report::DayRow]) -> bool {
+    rows.iter()
+        .any(|row| matches!(row.report, Err(report::DayError::Input(_))))
+}
+
+/// Bench the days, compare them with the baseline and record them in it if asked to
+fn run_bench(entries: &[(&DayEntry, RunOptions)], input_source: &InputSource, args: &cli::Args) {
+    let baseline_path = args.baseline_path();
+    // A baseline given explicitly must exist, unless this run is the one creating it
+    let must_exist = args.baseline.is_some() && !args.save_baseline;
+    let baseline = (baseline_path.is_file() || must_exist).then(|| {
+        Baseline::load(&baseline_path).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(EXIT_FAILURE);
+        })
+    });
+
+    let rows = bench::bench_days(entries, input_source, args.runs);
+    bench::print_bench(&rows, baseline.as_ref());
+
+    if args.save_baseline {
+        let mut baseline = baseline.unwrap_or_default();
+        bench::update_baseline(&mut baseline, &rows);
+        if let Err(err) = baseline.save(&baseline_path) {
+            eprintln!("{err}");
+            std::process::exit(EXIT_FAILURE);
+        }
+        println!("\nBaseline saved to {}", baseline_path.display());
+    }
+
+    if rows
+        .iter()
+        .any(|row| matches!(row.steps, Err(report::DayError::Parse(_))))
+    {
+        std::process::exit(EXIT_PARSE_ERROR);
+    }
+}
+
+/// Options of each selected day: the parameters from the config file, overridden by the ones from
+/// the command line
+fn day_runs(
+    selected: &[&'static DayEntry],
+    args: &cli::Args,
+) -> Result<Vec<(&'static DayEntry, RunOptions)>, String> {
+    let config = match args.config_path() {
+        Some(path) => ParamsConfig::load(&path)?,
+        None => ParamsConfig::default(),
+    };
+
+    for (name, _) in &args.params {
+        let declared = selected
+            .iter()
+            .any(|entry| entry.params.iter().any(|param| param.name == name));
+        if !declared {
+            return Err(format!(
+                "None of the selected days has a parameter {name}, see `aoc_2022 list`"
+            ));
+        }
+    }
+
+    selected
+        .iter()
+        .map(|entry| {
+            let mut params = config.day(entry.day);
+            params
+                .check(entry.params)
+                .map_err(|err| format!("Day {:02} in the config file: {err}", entry.day))?;
+
+            for (name, value) in &args.params {
+                if let Some(param) = entry.params.iter().find(|param| param.name == name) {
+                    param
+                        .check(*value)
+                        .map_err(|err| format!("Day {:02}: {err}", entry.day))?;
+                    params.set(name, *value);
+                }
+            }
+
+            let options = RunOptions {
+                parts: args.parts,
+                params,
+            };
+            Ok((*entry, options))
+        })
+        .collect()
+}
+
+/// Run the days on up to `jobs` threads, the rows stay in the order of `entries`
+fn run_days(
+    entries: &[(&DayEntry, RunOptions)],
+    input_source: &InputSource,
+    jobs: usize,
+) -> Vec<report::DayRow> {
+    let run_day = |(entry, options): &(&DayEntry, RunOptions)| {
+        let report = match input_source.load(entry.day) {
+            Ok(data) => entry
+                .solver
+                .run(&data, options)
+                .map_err(report::DayError::Parse),
+            Err(err) => Err(report::DayError::Input(err)),
+        };
+        report::DayRow {
+            day: entry.day,
+            report,
+        }
+    };
+
+    if jobs == 1 {
+        return entries.iter().map(run_day).collect();
+    }
+
+    // Each thread takes the next day not started yet, so that a slow day does not hold back the
+    // ones queued after it
+    let next_idx = AtomicUsize::new(0);
+    let rows = Mutex::new(
+        std::iter::repeat_with(|| None)
+            .take(entries.len())
+            .collect::<Vec<_>>(),
+    );
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(entries.len()) {
+            scope.spawn(|| loop {
+                let idx = next_idx.fetch_add(1, Ordering::Relaxed);
+                let Some(entry) = entries.get(idx) else {
+                    break;
+                };
+                let row = run_day(entry);
+                rows.lock().unwrap()[idx] = Some(row);
+            });
+        }
+    });
+
+    rows.into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|row| row.expect("every day is run by a thread"))
+        .collect()
 }
@@ -0,0 +1,310 @@
+use aoc_2022::days::alloc::AllocStats;
+use aoc_2022::days::answers::{CheckStatus, KnownAnswers};
+use aoc_2022::days::utils::InputError;
+use aoc_2022::days::{Answer, DayReport, ParseError, PartOutcome, PartSelection};
+use std::time::Duration;
+
+/// Outcome of a day when running several of them
+pub struct DayRow {
+    pub day: usize,
+    pub report: Result<DayReport, DayError>,
+}
+
+/// Why a day could not be run at all
+#[derive(Debug)]
+pub enum DayError {
+    Input(InputError),
+    Parse(ParseError),
+}
+
+impl std::fmt::Display for DayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DayError::Input(err) => write!(f, "{err}"),
+            DayError::Parse(err) => write!(f, "invalid input, {err}"),
+        }
+    }
+}
+
+const HEADERS: [&str; 5] = ["Day", "Parse", "Part 1", "Part 2", "Total"];
+
+/// Answers of a single day, unimplemented and failed parts are reported but don't stop the other
+/// part from being printed.
+pub fn print_answers(report: &DayReport) {
+    for (part_idx, part) in report.parts.iter().enumerate() {
+        let part_nb = part_idx + 1;
+        match &part.outcome {
+            PartOutcome::Solved(Answer::Empty) | PartOutcome::Skipped => (),
+            PartOutcome::Solved(answer) if answer.is_multiline() => {
+                println!("Part {part_nb}:\n{answer}")
+            }
+            PartOutcome::Solved(answer) => println!("Part {part_nb}: {answer}"),
+            PartOutcome::Unimplemented => println!("Part {part_nb}: unimplemented"),
+            PartOutcome::Failed(message) => println!("Part {part_nb}: failed, {message}"),
+        }
+    }
+}
+
+/// Timing table of several days, with a total line and the errors listed below it
+pub fn print_table(rows: &[DayRow]) {
+    let mut lines = vec![HEADERS.map(String::from)];
+    let mut errors = Vec::new();
+    let mut total = [Duration::ZERO; 4];
+
+    for row in rows {
+        let report = match &row.report {
+            Ok(report) => report,
+            Err(err) => {
+                let status = match err {
+                    DayError::Input(_) => "no input",
+                    DayError::Parse(_) => "parse error",
+                };
+                lines.push([
+                    format!("{:02}", row.day),
+                    status.to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                ]);
+                errors.push(format!("Day {:02}: {err}", row.day));
+                continue;
+            }
+        };
+
+        let part_cells = [0, 1].map(|part_idx| {
+            let part = &report.parts[part_idx];
+            match &part.outcome {
+                PartOutcome::Solved(_) => format_duration(part.time),
+                PartOutcome::Skipped => "-".to_string(),
+                PartOutcome::Unimplemented => "unimplemented".to_string(),
+                PartOutcome::Failed(message) => {
+                    errors.push(format!(
+                        "Day {:02} part {}: {message}",
+                        row.day,
+                        part_idx + 1
+                    ));
+                    "failed".to_string()
+                }
+            }
+        });
+        let [part_1, part_2] = part_cells;
+
+        for (total, time) in total.iter_mut().zip([
+            report.parse_time,
+            report.parts[0].time,
+            report.parts[1].time,
+            report.total_time(),
+        ]) {
+            *total += time;
+        }
+
+        lines.push([
+            format!("{:02}", row.day),
+            format_duration(report.parse_time),
+            part_1,
+            part_2,
+            format_duration(report.total_time()),
+        ]);
+    }
+
+    let [parse, part_1, part_2, all] = total.map(format_duration);
+    lines.push(["Total".to_string(), parse, part_1, part_2, all]);
+
+    print_aligned(&lines, 1, true);
+
+    if !errors.is_empty() {
+        println!();
+        for error in errors {
+            println!("{error}");
+        }
+    }
+}
+
+/// Allocations of each step of the days that ran, when they were counted
+pub fn print_memory(rows: &[DayRow]) {
+    let mut lines = vec![["Day", "Step", "Allocations", "Allocated", "Peak"].map(String::from)];
+
+    for row in rows {
+        let Ok(report) = &row.report else {
+            continue;
+        };
+
+        let steps = [("parse", report.parse_allocs)].into_iter().chain(
+            [("part 1", 0), ("part 2", 1)]
+                .map(|(name, part_idx)| (name, report.parts[part_idx].allocs)),
+        );
+        for (name, allocs) in steps {
+            let Some(allocs) = allocs else {
+                continue;
+            };
+            let AllocStats {
+                allocations,
+                bytes,
+                peak_bytes,
+            } = allocs;
+            lines.push([
+                format!("{:02}", row.day),
+                name.to_string(),
+                allocations.to_string(),
+                format_bytes(bytes),
+                format_bytes(peak_bytes),
+            ]);
+        }
+    }
+
+    if lines.len() > 1 {
+        println!();
+        print_aligned(&lines, 2, false);
+    }
+}
+
+/// Lines of cells in columns, below a separated header line. The first `left_aligned` columns are
+/// aligned to the left and the others, numbers, to the right. The last line is separated too when
+/// it is a total.
+pub fn print_aligned(lines: &[impl AsRef<[String]>], left_aligned: usize, total: bool) {
+    let mut widths = vec![0; lines[0].as_ref().len()];
+    for line in lines {
+        for (width, cell) in widths.iter_mut().zip(line.as_ref()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let separator = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<_>>()
+        .join("-+-");
+    let last_line_idx = lines.len() - 1;
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        if total && line_idx == last_line_idx {
+            println!("{separator}");
+        }
+
+        let cells = line
+            .as_ref()
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(cell_idx, (cell, width))| match cell_idx < left_aligned {
+                true => format!("{cell:<width$}", width = *width),
+                false => format!("{cell:>width$}", width = *width),
+            })
+            .collect::<Vec<_>>();
+        println!("{}", cells.join(" | "));
+
+        if line_idx == 0 {
+            println!("{separator}");
+        }
+    }
+}
+
+/// Compare each part with the recorded answers, returns whether everything that was recorded
+/// matched
+pub fn print_check(rows: &[DayRow], parts: PartSelection, known_answers: &KnownAnswers) -> bool {
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+    for row in rows {
+        let report = match &row.report {
+            Ok(report) => report,
+            Err(err) => {
+                let recorded = (1..=2)
+                    .filter(|part| parts.includes(*part))
+                    .filter(|part| known_answers.get(row.day, *part).is_some())
+                    .count();
+                if recorded > 0 {
+                    println!("Day {:02}: FAIL, {err}", row.day);
+                    failed += recorded;
+                }
+                continue;
+            }
+        };
+
+        for (part_idx, part) in report.parts.iter().enumerate() {
+            let part_nb = part_idx + 1;
+            let Some(status) = known_answers.check(row.day, part_nb, &part.outcome) else {
+                continue;
+            };
+
+            let name = format!("Day {:02} part {part_nb}", row.day);
+            match status {
+                CheckStatus::Pass => {
+                    passed += 1;
+                    println!("{name}: pass");
+                }
+                CheckStatus::Missing => {
+                    missing += 1;
+                    println!("{name}: missing");
+                }
+                CheckStatus::Fail { expected, got } => {
+                    failed += 1;
+                    println!("{name}: FAIL");
+                    println!("  expected:{}", format_answer(&expected));
+                    println!("  got:{}", format_outcome(&got));
+                }
+            }
+        }
+    }
+
+    println!("\n{passed} passed, {failed} failed, {missing} missing");
+    failed == 0
+}
+
+/// Answer to put after a label, multi-line ones start on their own line
+fn format_answer(answer: &Answer) -> String {
+    if answer.is_multiline() {
+        format!("\n{}", answer.to_string().trim_end())
+    } else {
+        format!(" {answer}")
+    }
+}
+
+fn format_outcome(outcome: &PartOutcome) -> String {
+    match outcome {
+        PartOutcome::Solved(answer) => format_answer(answer),
+        PartOutcome::Unimplemented => " unimplemented".to_string(),
+        PartOutcome::Failed(message) => format!(" failed, {message}"),
+        PartOutcome::Skipped => " skipped".to_string(),
+    }
+}
+
+/// Parse error with the offending line of the input and a caret under the wrong column
+pub fn parse_diagnostic(err: &ParseError, data: &str) -> String {
+    let mut diagnostic = format!("Invalid input: {err}");
+
+    if let Some(line) = err
+        .line
+        .checked_sub(1)
+        .and_then(|idx| data.lines().nth(idx))
+    {
+        let line_nb = err.line.to_string();
+        let margin = " ".repeat(line_nb.len());
+        let caret_offset = " ".repeat(err.column.saturating_sub(1));
+        let caret_len = err.found.chars().count().max(1);
+
+        diagnostic += &format!("\n{margin} |\n{line_nb} | {line}\n{margin} | {caret_offset}");
+        diagnostic += &"^".repeat(caret_len);
+    }
+
+    diagnostic
+}
+
+/// Size with a binary unit, `1.5 MiB`
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{duration:.3?}")
+}